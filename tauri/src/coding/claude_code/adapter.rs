@@ -1,6 +1,10 @@
 use serde_json::{json, Value};
-use super::types::{ClaudeCodeProvider, ClaudeCodeProviderContent, ClaudeCommonConfig};
+use super::types::{
+    ClaudeCodeProvider, ClaudeCodeProviderContent, ClaudeCommonConfig, ClaudeProject,
+    ClaudeProjectContent,
+};
 use crate::coding::db_id::db_extract_id;
+use crate::secrets::fields::{reveal_json_str, seal_claude_settings};
 use chrono::Local;
 
 // ============================================================================
@@ -55,7 +59,7 @@ pub fn from_db_value_provider(value: Value) -> ClaudeCodeProvider {
         id,
         name: get_str_compat(&value, "name", "name", "Unnamed Provider"),
         category: get_str_compat(&value, "category", "category", "other"),
-        settings_config: get_str_compat(&value, "settings_config", "settingsConfig", "{}"),
+        settings_config: reveal_json_str(&get_str_compat(&value, "settings_config", "settingsConfig", "{}")),
         source_provider_id: get_opt_str_compat(&value, "source_provider_id", "sourceProviderId"),
         website_url: get_opt_str_compat(&value, "website_url", "websiteUrl"),
         notes: get_opt_str_compat(&value, "notes", "notes"),
@@ -71,10 +75,15 @@ pub fn from_db_value_provider(value: Value) -> ClaudeCodeProvider {
 
 /// Convert ClaudeCodeProviderContent to database Value
 pub fn to_db_value_provider(content: &ClaudeCodeProviderContent) -> Value {
-    serde_json::to_value(content).unwrap_or_else(|e| {
+    let mut value = serde_json::to_value(content).unwrap_or_else(|e| {
         eprintln!("Failed to serialize provider content: {}", e);
         json!({})
-    })
+    });
+    // Store API keys as vault references
+    if let Some(Value::String(config)) = value.get_mut("settings_config") {
+        *config = seal_claude_settings(config);
+    }
+    value
 }
 
 // ============================================================================
@@ -111,3 +120,44 @@ pub fn to_db_value_common(config: &str) -> Value {
     })
 }
 
+
+// ============================================================================
+// Project Adapter Functions
+// ============================================================================
+
+/// Convert database Value to ClaudeProject with fault tolerance
+/// `provider_name` and `settings_path` are resolved by the caller
+pub fn from_db_value_project(value: Value) -> ClaudeProject {
+    let path = get_str_compat(&value, "path", "path", "");
+    let settings_path = project_settings_path(&path)
+        .to_string_lossy()
+        .to_string();
+
+    ClaudeProject {
+        id: db_extract_id(&value),
+        name: get_str_compat(&value, "name", "name", "Unnamed Project"),
+        path,
+        provider_id: get_opt_str_compat(&value, "provider_id", "providerId"),
+        provider_name: None,
+        settings_path,
+        applied_at: get_opt_str_compat(&value, "applied_at", "appliedAt"),
+        created_at: get_str_compat(&value, "created_at", "createdAt", ""),
+        updated_at: get_str_compat(&value, "updated_at", "updatedAt", ""),
+    }
+}
+
+/// Convert ClaudeProjectContent to database Value
+pub fn to_db_value_project(content: &ClaudeProjectContent) -> Value {
+    serde_json::to_value(content).unwrap_or_else(|e| {
+        eprintln!("Failed to serialize project content: {}", e);
+        json!({})
+    })
+}
+
+/// Settings file the provider is written to inside a project directory. Always the
+/// uncommitted local file: the provider env holds the API key.
+pub fn project_settings_path(project_path: &str) -> std::path::PathBuf {
+    std::path::Path::new(project_path)
+        .join(".claude")
+        .join("settings.local.json")
+}
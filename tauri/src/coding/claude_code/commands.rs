@@ -3,11 +3,16 @@ use std::fs;
 use std::path::Path;
 use serde_json::Value;
 
+use crate::coding::config_writer::{preview_config_file, write_config_file, ConfigFilePreview};
 use crate::db::DbState;
 use super::adapter;
+use super::settings_merge;
 use super::types::*;
 use tauri::Emitter;
 
+/// Merge base key of the global ~/.claude/settings.json
+const GLOBAL_SETTINGS_KEY: &str = "current";
+
 const KNOWN_ENV_FIELDS: [&str; 7] = [
     "ANTHROPIC_AUTH_TOKEN",
     "ANTHROPIC_API_KEY",
@@ -281,6 +286,9 @@ pub async fn update_claude_provider(
         }
     }
 
+    // Keep projects pinned to this provider in sync
+    reapply_pinned_projects(&db, Some(&id)).await;
+
     // Notify frontend and tray to refresh
     let _ = app.emit("config-changed", "window");
 
@@ -458,17 +466,61 @@ pub async fn read_claude_settings() -> Result<ClaudeSettings, String> {
 async fn apply_config_to_file(
     db: &surrealdb::Surreal<surrealdb::engine::local::Db>,
     provider_id: &str,
-) -> Result<(), String> {
+) -> Result<Vec<ClaudeSettingsConflict>, String> {
     apply_config_to_file_public(db, provider_id).await
 }
 
 /// Public version of apply_config_to_file for tray module
+/// Returns the settings.json keys that were changed outside the app since the last apply
 pub async fn apply_config_to_file_public(
     db: &surrealdb::Surreal<surrealdb::engine::local::Db>,
     provider_id: &str,
-) -> Result<(), String> {
+) -> Result<Vec<ClaudeSettingsConflict>, String> {
+    let config_path = std::path::PathBuf::from(get_claude_config_path()?);
+    let pending = build_claude_settings(db, provider_id, &config_path, GLOBAL_SETTINGS_KEY).await?;
+    write_pending_settings(db, pending, GLOBAL_SETTINGS_KEY).await
+}
+
+/// Write built settings to disk and remember the managed part as the next merge base
+async fn write_pending_settings(
+    db: &surrealdb::Surreal<surrealdb::engine::local::Db>,
+    pending: PendingClaudeSettings,
+    base_key: &str,
+) -> Result<Vec<ClaudeSettingsConflict>, String> {
+    for conflict in &pending.conflicts {
+        log::warn!(
+            "Claude settings key '{}' in {} was changed outside the app ({:?})",
+            conflict.key,
+            pending.config_path.display(),
+            conflict.resolution
+        );
+    }
 
+    write_config_file(&pending.config_path, &pending.content)
+        .map_err(|e| format!("Failed to write settings file: {}", e))?;
+
+    save_applied_settings(db, base_key, &pending.managed_settings).await?;
 
+    Ok(pending.conflicts)
+}
+
+/// settings.json content built for a provider, not yet written
+struct PendingClaudeSettings {
+    config_path: std::path::PathBuf,
+    content: String,
+    /// Settings owned by the app (common config + provider), the merge base for the next apply
+    managed_settings: serde_json::Map<String, Value>,
+    conflicts: Vec<ClaudeSettingsConflict>,
+}
+
+/// Build the merged settings content for a provider without writing it.
+/// `base_key` identifies the merge base of the target file (global or a project).
+async fn build_claude_settings(
+    db: &surrealdb::Surreal<surrealdb::engine::local::Db>,
+    provider_id: &str,
+    config_path: &Path,
+    base_key: &str,
+) -> Result<PendingClaudeSettings, String> {
     // Get the provider
     // Use type::thing(table, id) to create a Thing from table name and id
     let provider_result: Result<Vec<Value>, _> = db
@@ -497,6 +549,7 @@ pub async fn apply_config_to_file_public(
     }
 
     // Parse provider settings_config
+    crate::secrets::check_resolved(&provider.settings_config)?;
     let provider_config: serde_json::Value = serde_json::from_str(&provider.settings_config)
         .map_err(|e| format!("Failed to parse provider config: {}", e))?;
 
@@ -589,30 +642,116 @@ pub async fn apply_config_to_file_public(
         merged_env.insert(key, value);
     }
 
-    // Remove old env and insert merged env at the end (env should be at the bottom)
     final_settings.remove("env");
     final_settings.insert("env".to_string(), serde_json::json!(merged_env));
 
-    // Write to settings.json
-    let config_path_str = get_claude_config_path()?;
-    let config_path = Path::new(&config_path_str);
+    // Merge into the existing settings file so keys written by Claude Code itself survive
+    let current_settings = read_settings_object(config_path)?;
+    let previous_settings = load_applied_settings(db, base_key).await;
 
-    // Ensure directory exists
-    if let Some(parent) = config_path.parent() {
-        if !parent.exists() {
-            fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create .claude directory: {}", e))?;
-        }
-    }
+    let (merged_settings, conflicts) = settings_merge::merge_settings(
+        &current_settings,
+        previous_settings.as_ref(),
+        &final_settings,
+        &KNOWN_ENV_FIELDS,
+    );
 
-    let json_content = serde_json::to_string_pretty(&final_settings)
+    let json_content = serde_json::to_string_pretty(&merged_settings)
         .map_err(|e| format!("Failed to serialize settings: {}", e))?;
 
-    fs::write(config_path, json_content)
-        .map_err(|e| format!("Failed to write settings file: {}", e))?;
+    Ok(PendingClaudeSettings {
+        config_path: config_path.to_path_buf(),
+        content: json_content,
+        managed_settings: final_settings,
+        conflicts,
+    })
+}
+
+/// Preview applying a provider: returns the would-be settings.json and a diff against the current file
+#[tauri::command]
+pub async fn preview_claude_config(
+    state: tauri::State<'_, DbState>,
+    provider_id: String,
+) -> Result<Vec<ConfigFilePreview>, String> {
+    let db = state.0.lock().await;
+    let config_path = std::path::PathBuf::from(get_claude_config_path()?);
+    let pending = build_claude_settings(&db, &provider_id, &config_path, GLOBAL_SETTINGS_KEY).await?;
+    Ok(vec![preview_config_file(&pending.config_path, &pending.content)])
+}
+
+/// Read settings.json as a JSON object (empty when missing or blank).
+/// A file that cannot be parsed is an error: replacing it would drop every key
+/// the app does not manage (permissions, hooks, env, ...)
+fn read_settings_object(config_path: &Path) -> Result<serde_json::Map<String, Value>, String> {
+    if !config_path.exists() {
+        return Ok(serde_json::Map::new());
+    }
+
+    let content = fs::read_to_string(config_path)
+        .map_err(|e| format!("Failed to read Claude settings file: {}", e))?;
+    if content.trim().is_empty() {
+        return Ok(serde_json::Map::new());
+    }
+
+    match serde_json::from_str::<Value>(&content) {
+        Ok(Value::Object(map)) => Ok(map),
+        Ok(_) => Err(format!(
+            "Claude settings file {} is not a JSON object, fix it before applying a provider",
+            config_path.display()
+        )),
+        Err(e) => Err(format!(
+            "Failed to parse Claude settings file {} ({}), fix it before applying a provider",
+            config_path.display(),
+            e
+        )),
+    }
+}
+
+/// Load the managed settings written by the last apply (merge base)
+async fn load_applied_settings(
+    db: &surrealdb::Surreal<surrealdb::engine::local::Db>,
+    base_key: &str,
+) -> Option<serde_json::Map<String, Value>> {
+    let records: Vec<Value> = db
+        .query("SELECT * OMIT id FROM claude_applied_settings WHERE id = type::thing('claude_applied_settings', $key) LIMIT 1")
+        .bind(("key", base_key.to_string()))
+        .await
+        .ok()?
+        .take(0)
+        .ok()?;
+
+    let settings = records.first()?.get("settings")?.as_str()?;
+    match serde_json::from_str(&crate::secrets::fields::reveal_json_str(settings)) {
+        Ok(Value::Object(map)) => Some(map),
+        _ => None,
+    }
+}
+
+/// Store the managed settings that were just applied (merge base for the next apply)
+async fn save_applied_settings(
+    db: &surrealdb::Surreal<surrealdb::engine::local::Db>,
+    base_key: &str,
+    settings: &serde_json::Map<String, Value>,
+) -> Result<(), String> {
+    let settings_str = serde_json::to_string(settings)
+        .map_err(|e| format!("Failed to serialize applied settings: {}", e))?;
+    let settings_str = crate::secrets::fields::seal_claude_settings(&settings_str);
+
+    db.query("UPSERT type::thing('claude_applied_settings', $key) CONTENT $data")
+        .bind(("key", base_key.to_string()))
+        .bind((
+            "data",
+            serde_json::json!({
+                "settings": settings_str,
+                "updated_at": Local::now().to_rfc3339(),
+            }),
+        ))
+        .await
+        .map_err(|e| format!("Failed to save applied settings: {}", e))?;
 
     Ok(())
 }
+
 /// Toggle is_disabled status for a provider
 #[tauri::command]
 pub async fn toggle_claude_code_provider_disabled(
@@ -660,12 +799,13 @@ pub async fn toggle_claude_code_provider_disabled(
 }
 
 /// Apply Claude Code provider configuration to settings.json
+/// Returns the keys that were changed outside the app since the last apply
 #[tauri::command]
 pub async fn apply_claude_config(
     state: tauri::State<'_, DbState>,
     app: tauri::AppHandle,
     provider_id: String,
-) -> Result<(), String> {
+) -> Result<Vec<ClaudeSettingsConflict>, String> {
     let db = state.0.lock().await;
     apply_config_internal(&db, &app, &provider_id, false).await
 }
@@ -677,9 +817,9 @@ pub async fn apply_config_internal<R: tauri::Runtime>(
     app: &tauri::AppHandle<R>,
     provider_id: &str,
     from_tray: bool,
-) -> Result<(), String> {
+) -> Result<Vec<ClaudeSettingsConflict>, String> {
     // 应用配置到文件
-    apply_config_to_file(db, provider_id).await?;
+    let conflicts = apply_config_to_file(db, provider_id).await?;
 
     // Update provider's is_applied status
     let now = Local::now().to_rfc3339();
@@ -701,11 +841,18 @@ pub async fn apply_config_internal<R: tauri::Runtime>(
     let payload = if from_tray { "tray" } else { "window" };
     let _ = app.emit("config-changed", payload);
 
+    // Tray applies have no caller to return conflicts to, so they are also broadcast
+    if !conflicts.is_empty() {
+        let _ = app.emit("claude-settings-conflicts", &conflicts);
+    }
+
     // Trigger WSL sync via event (Windows only)
     #[cfg(target_os = "windows")]
     let _ = app.emit("wsl-sync-request-claude", ());
+    // Trigger SSH sync via event (debounced by the SSH module)
+    let _ = app.emit("ssh-sync-request-claude", ());
 
-    Ok(())
+    Ok(conflicts)
 }
 
 // ============================================================================
@@ -842,6 +989,9 @@ pub async fn save_claude_common_config(
         }
     }
 
+    // Common config is also part of every pinned project's settings
+    reapply_pinned_projects(&db, None).await;
+
     // Notify frontend to refresh
     let _ = app.emit("config-changed", "window");
 
@@ -1045,7 +1195,7 @@ pub async fn apply_claude_plugin_config(enabled: bool) -> Result<bool, String> {
     let serialized = serde_json::to_string_pretty(&serde_json::Value::Object(obj))
         .map_err(|e| format!("Failed to serialize config: {}", e))?;
 
-    fs::write(&config_path, format!("{serialized}\n"))
+    write_config_file(&config_path, format!("{serialized}\n"))
         .map_err(|e| format!("Failed to write config file: {}", e))?;
 
     Ok(true)
@@ -1302,7 +1452,7 @@ pub async fn apply_claude_onboarding_skip() -> Result<bool, String> {
     let serialized = serde_json::to_string_pretty(&serde_json::Value::Object(obj))
         .map_err(|e| format!("Failed to serialize config: {}", e))?;
 
-    fs::write(&config_path, format!("{serialized}\n"))
+    write_config_file(&config_path, format!("{serialized}\n"))
         .map_err(|e| format!("Failed to write config file: {}", e))?;
 
     Ok(true)
@@ -1338,8 +1488,291 @@ pub async fn clear_claude_onboarding_skip() -> Result<bool, String> {
     let serialized = serde_json::to_string_pretty(&serde_json::Value::Object(obj))
         .map_err(|e| format!("Failed to serialize config: {}", e))?;
 
-    fs::write(&config_path, format!("{serialized}\n"))
+    write_config_file(&config_path, format!("{serialized}\n"))
         .map_err(|e| format!("Failed to write config file: {}", e))?;
 
     Ok(true)
 }
+
+// ============================================================================
+// Claude Project Commands
+// ============================================================================
+
+/// Merge base key of a project settings file
+fn project_settings_key(project_id: &str) -> String {
+    format!("project_{}", project_id)
+}
+
+/// Validate and normalize a project directory
+fn normalize_project_path(path: &str) -> Result<String, String> {
+    let expanded = crate::coding::expand_local_path(path.trim())?;
+    let dir = Path::new(&expanded);
+    if !dir.is_dir() {
+        return Err(format!("Project directory does not exist: {}", expanded));
+    }
+    let canonical = fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+    Ok(canonical.to_string_lossy().to_string())
+}
+
+async fn get_project_record(
+    db: &surrealdb::Surreal<surrealdb::engine::local::Db>,
+    project_id: &str,
+) -> Result<ClaudeProject, String> {
+    let records: Vec<Value> = db
+        .query("SELECT *, type::string(id) as id FROM claude_project WHERE id = type::thing('claude_project', $id) LIMIT 1")
+        .bind(("id", project_id.to_string()))
+        .await
+        .map_err(|e| format!("Failed to query project: {}", e))?
+        .take(0)
+        .map_err(|e| format!("Failed to parse project: {}", e))?;
+
+    records
+        .first()
+        .map(|record| adapter::from_db_value_project(record.clone()))
+        .ok_or_else(|| format!("Claude project '{}' not found", project_id))
+}
+
+/// List registered projects with the name of the provider pinned to each
+#[tauri::command]
+pub async fn list_claude_projects(
+    state: tauri::State<'_, DbState>,
+) -> Result<Vec<ClaudeProject>, String> {
+    let db = state.0.lock().await;
+    list_claude_projects_internal(&db).await
+}
+
+pub async fn list_claude_projects_internal(
+    db: &surrealdb::Surreal<surrealdb::engine::local::Db>,
+) -> Result<Vec<ClaudeProject>, String> {
+    let records: Vec<Value> = db
+        .query("SELECT *, type::string(id) as id FROM claude_project ORDER BY name ASC")
+        .await
+        .map_err(|e| format!("Failed to query projects: {}", e))?
+        .take(0)
+        .map_err(|e| format!("Failed to parse projects: {}", e))?;
+
+    let providers: Vec<Value> = db
+        .query("SELECT type::string(id) as id, name FROM claude_provider")
+        .await
+        .map_err(|e| format!("Failed to query providers: {}", e))?
+        .take(0)
+        .unwrap_or_default();
+    let provider_names: std::collections::HashMap<String, String> = providers
+        .iter()
+        .filter_map(|p| {
+            let name = p.get("name")?.as_str()?.to_string();
+            Some((crate::coding::db_extract_id(p), name))
+        })
+        .collect();
+
+    Ok(records
+        .into_iter()
+        .map(|record| {
+            let mut project = adapter::from_db_value_project(record);
+            project.provider_name = project
+                .provider_id
+                .as_ref()
+                .and_then(|id| provider_names.get(id).cloned());
+            project
+        })
+        .collect())
+}
+
+/// Register a project directory
+#[tauri::command]
+pub async fn create_claude_project(
+    state: tauri::State<'_, DbState>,
+    app: tauri::AppHandle,
+    project: ClaudeProjectInput,
+) -> Result<ClaudeProject, String> {
+    let db = state.0.lock().await;
+    let path = normalize_project_path(&project.path)?;
+
+    let existing = list_claude_projects_internal(&db).await?;
+    if existing.iter().any(|p| p.path == path) {
+        return Err(format!("Project is already registered: {}", path));
+    }
+
+    let now = Local::now().to_rfc3339();
+    let project_id = uuid::Uuid::new_v4().simple().to_string();
+    let content = ClaudeProjectContent {
+        name: project.name,
+        path,
+        provider_id: None,
+        applied_at: None,
+        created_at: now.clone(),
+        updated_at: now,
+    };
+
+    db.query("CREATE type::thing('claude_project', $id) CONTENT $data")
+        .bind(("id", project_id.clone()))
+        .bind(("data", adapter::to_db_value_project(&content)))
+        .await
+        .map_err(|e| format!("Failed to create project: {}", e))?;
+
+    let _ = app.emit("config-changed", "window");
+
+    get_project_record(&db, &project_id).await
+}
+
+/// Update a registered project (name, directory)
+#[tauri::command]
+pub async fn update_claude_project(
+    state: tauri::State<'_, DbState>,
+    app: tauri::AppHandle,
+    project_id: String,
+    project: ClaudeProjectInput,
+) -> Result<ClaudeProject, String> {
+    let db = state.0.lock().await;
+    let existing = get_project_record(&db, &project_id).await?;
+    let path = normalize_project_path(&project.path)?;
+
+    let content = ClaudeProjectContent {
+        name: project.name,
+        path,
+        provider_id: existing.provider_id,
+        applied_at: existing.applied_at,
+        created_at: existing.created_at,
+        updated_at: Local::now().to_rfc3339(),
+    };
+
+    db.query("UPDATE type::thing('claude_project', $id) CONTENT $data")
+        .bind(("id", project_id.clone()))
+        .bind(("data", adapter::to_db_value_project(&content)))
+        .await
+        .map_err(|e| format!("Failed to update project: {}", e))?;
+
+    let _ = app.emit("config-changed", "window");
+
+    get_project_record(&db, &project_id).await
+}
+
+/// Unregister a project. The project's settings file is left untouched.
+#[tauri::command]
+pub async fn delete_claude_project(
+    state: tauri::State<'_, DbState>,
+    app: tauri::AppHandle,
+    project_id: String,
+) -> Result<(), String> {
+    let db = state.0.lock().await;
+
+    db.query("DELETE type::thing('claude_project', $id)")
+        .bind(("id", project_id.clone()))
+        .await
+        .map_err(|e| format!("Failed to delete project: {}", e))?;
+    db.query("DELETE type::thing('claude_applied_settings', $key)")
+        .bind(("key", project_settings_key(&project_id)))
+        .await
+        .map_err(|e| format!("Failed to delete project applied settings: {}", e))?;
+
+    let _ = app.emit("config-changed", "window");
+
+    Ok(())
+}
+
+/// Apply a provider (and common config) to a project's settings file and pin it
+#[tauri::command]
+pub async fn apply_claude_project_provider(
+    state: tauri::State<'_, DbState>,
+    app: tauri::AppHandle,
+    project_id: String,
+    provider_id: String,
+) -> Result<Vec<ClaudeSettingsConflict>, String> {
+    let db = state.0.lock().await;
+    apply_project_provider_internal(&db, &app, &project_id, &provider_id, false).await
+}
+
+/// Preview applying a provider to a project
+#[tauri::command]
+pub async fn preview_claude_project_provider(
+    state: tauri::State<'_, DbState>,
+    project_id: String,
+    provider_id: String,
+) -> Result<Vec<ConfigFilePreview>, String> {
+    let db = state.0.lock().await;
+    let project = get_project_record(&db, &project_id).await?;
+    let pending = build_claude_settings(
+        &db,
+        &provider_id,
+        Path::new(&project.settings_path),
+        &project_settings_key(&project_id),
+    )
+    .await?;
+    Ok(vec![preview_config_file(&pending.config_path, &pending.content)])
+}
+
+/// Internal function to apply a provider to a project: writes the file and pins the provider
+pub async fn apply_project_provider_internal<R: tauri::Runtime>(
+    db: &surrealdb::Surreal<surrealdb::engine::local::Db>,
+    app: &tauri::AppHandle<R>,
+    project_id: &str,
+    provider_id: &str,
+    from_tray: bool,
+) -> Result<Vec<ClaudeSettingsConflict>, String> {
+    let conflicts = apply_project_settings_file(db, project_id, provider_id).await?;
+
+    let now = Local::now().to_rfc3339();
+    db.query("UPDATE type::thing('claude_project', $id) SET provider_id = $provider_id, applied_at = $now, updated_at = $now")
+        .bind(("id", project_id.to_string()))
+        .bind(("provider_id", provider_id.to_string()))
+        .bind(("now", now))
+        .await
+        .map_err(|e| format!("Failed to pin provider to project: {}", e))?;
+
+    let payload = if from_tray { "tray" } else { "window" };
+    let _ = app.emit("config-changed", payload);
+
+    if !conflicts.is_empty() {
+        let _ = app.emit("claude-settings-conflicts", &conflicts);
+    }
+
+    Ok(conflicts)
+}
+
+/// Write the project settings file for a provider (does not change the pinned provider)
+async fn apply_project_settings_file(
+    db: &surrealdb::Surreal<surrealdb::engine::local::Db>,
+    project_id: &str,
+    provider_id: &str,
+) -> Result<Vec<ClaudeSettingsConflict>, String> {
+    let project = get_project_record(db, project_id).await?;
+    if !Path::new(&project.path).is_dir() {
+        return Err(format!("Project directory does not exist: {}", project.path));
+    }
+
+    let base_key = project_settings_key(project_id);
+    let pending = build_claude_settings(
+        db,
+        provider_id,
+        Path::new(&project.settings_path),
+        &base_key,
+    )
+    .await?;
+    write_pending_settings(db, pending, &base_key).await
+}
+
+/// Re-write the settings file of every project pinned to `provider_id` (all pinned projects when None)
+async fn reapply_pinned_projects(
+    db: &surrealdb::Surreal<surrealdb::engine::local::Db>,
+    provider_id: Option<&str>,
+) {
+    let projects = match list_claude_projects_internal(db).await {
+        Ok(projects) => projects,
+        Err(e) => {
+            log::warn!("Failed to list Claude projects: {}", e);
+            return;
+        }
+    };
+
+    for project in &projects {
+        let Some(pinned) = project.provider_id.as_deref() else {
+            continue;
+        };
+        if provider_id.is_some_and(|id| id != pinned) {
+            continue;
+        }
+        if let Err(e) = apply_project_settings_file(db, &project.id, pinned).await {
+            log::warn!("Failed to re-apply provider to project {}: {}", project.path, e);
+        }
+    }
+}
@@ -1,5 +1,6 @@
 pub mod adapter;
 pub mod commands;
+pub mod settings_merge;
 pub mod types;
 pub mod tray_support;
 
@@ -0,0 +1,203 @@
+//! Three-way merge for Claude Code settings.json
+//!
+//! - base: the managed settings written by the last apply (stored in the database)
+//! - managed: the settings built from the common config and the provider being applied
+//! - current: the settings.json on disk, possibly edited by Claude Code itself
+//!
+//! Managed keys are replaced, unmanaged keys in the file are preserved, and managed keys
+//! that were changed in the file since the last apply are reported as conflicts.
+//! The `env` object is merged per variable the same way.
+
+use serde_json::{Map, Value};
+
+use super::types::{ClaudeConflictResolution, ClaudeSettingsConflict};
+
+/// Merge `managed` settings into `current` settings.json content.
+///
+/// `owned_env_keys` are provider-owned env variables: they are removed from the file
+/// when the applied provider does not set them. Without a `base` (first apply) no
+/// conflicts can be detected, so none are reported.
+pub fn merge_settings(
+    current: &Map<String, Value>,
+    base: Option<&Map<String, Value>>,
+    managed: &Map<String, Value>,
+    owned_env_keys: &[&str],
+) -> (Map<String, Value>, Vec<ClaudeSettingsConflict>) {
+    let mut result = current.clone();
+    let mut conflicts = Vec::new();
+
+    let empty = Map::new();
+    let mut managed_top = managed.clone();
+    managed_top.remove("env");
+    merge_level(&mut result, current, base, &managed_top, "", &["env"], &mut conflicts);
+
+    // env is shared between the provider, the common config and the user
+    let current_env = current.get("env").and_then(|v| v.as_object()).unwrap_or(&empty);
+    let base_env = base.map(|b| b.get("env").and_then(|v| v.as_object()).unwrap_or(&empty));
+    let managed_env = managed.get("env").and_then(|v| v.as_object()).unwrap_or(&empty);
+
+    let mut merged_env = current_env.clone();
+    merge_level(
+        &mut merged_env,
+        current_env,
+        base_env,
+        managed_env,
+        "env.",
+        owned_env_keys,
+        &mut conflicts,
+    );
+
+    // Provider-owned variables that the applied provider does not set
+    for key in owned_env_keys {
+        if managed_env.contains_key(*key) {
+            continue;
+        }
+        let Some(file_value) = merged_env.remove(*key) else {
+            continue;
+        };
+        let previous = base_env.and_then(|b| b.get(*key));
+        if base_env.is_some() && previous != Some(&file_value) {
+            conflicts.push(ClaudeSettingsConflict {
+                key: format!("env.{}", key),
+                file_value: Some(file_value),
+                previous_value: previous.cloned(),
+                managed_value: None,
+                resolution: ClaudeConflictResolution::Removed,
+            });
+        }
+    }
+
+    if managed.contains_key("env") || !merged_env.is_empty() || current.contains_key("env") {
+        result.insert("env".to_string(), Value::Object(merged_env));
+    }
+
+    (result, conflicts)
+}
+
+/// Merge one object level.
+/// Keys in `caller_handled` are left alone when they drop out of the managed settings.
+fn merge_level(
+    result: &mut Map<String, Value>,
+    current: &Map<String, Value>,
+    base: Option<&Map<String, Value>>,
+    managed: &Map<String, Value>,
+    prefix: &str,
+    caller_handled: &[&str],
+    conflicts: &mut Vec<ClaudeSettingsConflict>,
+) {
+    for (key, managed_value) in managed {
+        if let (Some(file_value), Some(base)) = (current.get(key), base) {
+            let previous = base.get(key);
+            if file_value != managed_value && previous != Some(file_value) {
+                conflicts.push(ClaudeSettingsConflict {
+                    key: format!("{}{}", prefix, key),
+                    file_value: Some(file_value.clone()),
+                    previous_value: previous.cloned(),
+                    managed_value: Some(managed_value.clone()),
+                    resolution: ClaudeConflictResolution::Overwritten,
+                });
+            }
+        }
+        result.insert(key.clone(), managed_value.clone());
+    }
+
+    // Keys written by the last apply that are no longer managed
+    if let Some(base) = base {
+        for (key, previous) in base {
+            if caller_handled.contains(&key.as_str()) || managed.contains_key(key) {
+                continue;
+            }
+            match current.get(key) {
+                Some(file_value) if file_value == previous => {
+                    result.remove(key);
+                }
+                Some(file_value) => conflicts.push(ClaudeSettingsConflict {
+                    key: format!("{}{}", prefix, key),
+                    file_value: Some(file_value.clone()),
+                    previous_value: Some(previous.clone()),
+                    managed_value: None,
+                    resolution: ClaudeConflictResolution::Kept,
+                }),
+                None => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const OWNED: [&str; 2] = ["ANTHROPIC_MODEL", "ANTHROPIC_BASE_URL"];
+
+    fn obj(value: Value) -> Map<String, Value> {
+        value.as_object().cloned().unwrap()
+    }
+
+    #[test]
+    fn test_preserves_unmanaged_keys() {
+        let current = obj(json!({
+            "permissions": { "allow": ["Bash(ls)"] },
+            "env": { "MY_VAR": "1", "ANTHROPIC_MODEL": "old" }
+        }));
+        let managed = obj(json!({
+            "includeCoAuthoredBy": false,
+            "env": { "ANTHROPIC_BASE_URL": "https://api.example.com" }
+        }));
+
+        let (merged, conflicts) = merge_settings(&current, None, &managed, &OWNED);
+
+        assert!(conflicts.is_empty());
+        assert_eq!(
+            Value::Object(merged),
+            json!({
+                "permissions": { "allow": ["Bash(ls)"] },
+                "includeCoAuthoredBy": false,
+                "env": { "MY_VAR": "1", "ANTHROPIC_BASE_URL": "https://api.example.com" }
+            })
+        );
+    }
+
+    #[test]
+    fn test_reports_externally_changed_managed_key() {
+        let base = obj(json!({ "model": "opus", "env": { "ANTHROPIC_MODEL": "a" } }));
+        let current = obj(json!({ "model": "sonnet", "env": { "ANTHROPIC_MODEL": "a" } }));
+        let managed = obj(json!({ "model": "opus", "env": { "ANTHROPIC_MODEL": "b" } }));
+
+        let (merged, conflicts) = merge_settings(&current, Some(&base), &managed, &OWNED);
+
+        assert_eq!(merged.get("model"), Some(&json!("opus")));
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].key, "model");
+        assert_eq!(conflicts[0].file_value, Some(json!("sonnet")));
+        assert_eq!(conflicts[0].resolution, ClaudeConflictResolution::Overwritten);
+    }
+
+    #[test]
+    fn test_dropped_managed_keys() {
+        let base = obj(json!({ "a": 1, "b": 2, "env": { "ANTHROPIC_MODEL": "x" } }));
+        let current = obj(json!({ "a": 1, "b": 3, "env": { "ANTHROPIC_MODEL": "y" } }));
+        let managed = obj(json!({ "env": {} }));
+
+        let (merged, conflicts) = merge_settings(&current, Some(&base), &managed, &OWNED);
+
+        // Unchanged key is removed, edited key is kept
+        assert!(!merged.contains_key("a"));
+        assert_eq!(merged.get("b"), Some(&json!(3)));
+        // Provider-owned env var is always removed
+        assert_eq!(merged.get("env"), Some(&json!({})));
+
+        let keys: Vec<(&str, ClaudeConflictResolution)> = conflicts
+            .iter()
+            .map(|c| (c.key.as_str(), c.resolution))
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                ("b", ClaudeConflictResolution::Kept),
+                ("env.ANTHROPIC_MODEL", ClaudeConflictResolution::Removed),
+            ]
+        );
+    }
+}
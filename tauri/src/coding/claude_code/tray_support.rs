@@ -2,7 +2,9 @@
 //!
 //! Provides standardized API for tray menu integration.
 
-use crate::coding::claude_code::apply_config_internal;
+use crate::coding::claude_code::{
+    apply_config_internal, apply_project_provider_internal, list_claude_projects_internal,
+};
 use crate::coding::db_id::db_clean_id;
 use crate::db::DbState;
 use serde_json::Value;
@@ -32,6 +34,17 @@ pub struct TrayProviderData {
     pub items: Vec<TrayProviderItem>,
 }
 
+/// Registered project with its provider choices in tray menu
+#[derive(Debug, Clone)]
+pub struct TrayProjectItem {
+    /// Project ID (used in event handling)
+    pub id: String,
+    /// Display name in menu, including the pinned provider
+    pub display_name: String,
+    /// Providers; `is_selected` marks the one pinned to this project
+    pub providers: Vec<TrayProviderItem>,
+}
+
 /// Get tray provider data for Claude Code
 pub async fn get_claude_code_tray_data<R: Runtime>(
     app: &AppHandle<R>,
@@ -114,6 +127,54 @@ pub async fn apply_claude_code_provider<R: Runtime>(
     Ok(())
 }
 
+/// Get registered projects for tray menu, each with the provider list
+pub async fn get_claude_project_tray_data<R: Runtime>(
+    app: &AppHandle<R>,
+) -> Result<Vec<TrayProjectItem>, String> {
+    let providers = get_claude_code_tray_data(app).await?.items;
+
+    let state = app.state::<DbState>();
+    let db = state.0.lock().await;
+    let projects = list_claude_projects_internal(&db).await?;
+
+    Ok(projects
+        .into_iter()
+        .map(|project| {
+            let display_name = match &project.provider_name {
+                Some(provider_name) => format!("{} ({})", project.name, provider_name),
+                None => project.name.clone(),
+            };
+            let providers = providers
+                .iter()
+                .cloned()
+                .map(|mut item| {
+                    item.is_selected = project.provider_id.as_deref() == Some(item.id.as_str());
+                    item
+                })
+                .collect();
+            TrayProjectItem {
+                id: project.id,
+                display_name,
+                providers,
+            }
+        })
+        .collect())
+}
+
+/// Apply provider to a project from tray menu
+pub async fn apply_claude_project_provider<R: Runtime>(
+    app: &AppHandle<R>,
+    project_id: &str,
+    provider_id: &str,
+) -> Result<(), String> {
+    let state = app.state::<DbState>();
+    let db = state.0.lock().await;
+
+    apply_project_provider_internal(&db, app, project_id, provider_id, true).await?;
+
+    Ok(())
+}
+
 /// Check if Claude Code should be shown in tray menu
 /// Returns true - Claude Code is always visible as a core feature
 pub async fn is_enabled_for_tray<R: Runtime>(_app: &AppHandle<R>) -> bool {
@@ -177,3 +177,81 @@ pub struct ClaudePluginStatus {
     /// Whether ~/.claude/config.json exists
     pub has_config_file: bool,
 }
+
+// ============================================================================
+// Claude Settings Merge Types
+// ============================================================================
+
+/// How a conflicting settings.json key was resolved during apply
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ClaudeConflictResolution {
+    /// The file value was replaced by the managed value
+    Overwritten,
+    /// The key is no longer managed, the edited file value was kept
+    Kept,
+    /// The key is provider-owned and was removed from the file
+    Removed,
+}
+
+/// A settings.json key that was changed outside the app since the last apply
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaudeSettingsConflict {
+    /// Key path, e.g. `permissions` or `env.ANTHROPIC_MODEL`
+    pub key: String,
+    /// Value found in settings.json before apply
+    pub file_value: Option<serde_json::Value>,
+    /// Value written by the last apply
+    pub previous_value: Option<serde_json::Value>,
+    /// Value managed by the current provider/common config
+    pub managed_value: Option<serde_json::Value>,
+    pub resolution: ClaudeConflictResolution,
+}
+
+// ============================================================================
+// Claude Project Types
+// ============================================================================
+
+/// ClaudeProject - Database record content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaudeProjectContent {
+    pub name: String,
+    /// Absolute project directory
+    pub path: String,
+    /// Provider pinned to this project
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub applied_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// ClaudeProject - API response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaudeProject {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_id: Option<String>,
+    /// Name of the pinned provider (None when unpinned or the provider was deleted)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_name: Option<String>,
+    /// Resolved settings file path inside the project
+    pub settings_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub applied_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// ClaudeProject - Input for registering or updating a project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaudeProjectInput {
+    pub name: String,
+    pub path: String,
+}
@@ -3,6 +3,7 @@ use serde_json::Value;
 
 use super::types::{CodexCommonConfig, CodexProvider, CodexProviderContent};
 use crate::coding::db_id::db_extract_id;
+use crate::secrets::fields::{reveal_json_str, seal_codex_settings};
 
 // ============================================================================
 // Provider Adapter Functions
@@ -26,11 +27,12 @@ pub fn from_db_value_provider(value: Value) -> CodexProvider {
             .and_then(|v| v.as_str())
             .unwrap_or("")
             .to_string(),
-        settings_config: value
-            .get("settings_config")
-            .and_then(|v| v.as_str())
-            .unwrap_or("{}")
-            .to_string(),
+        settings_config: reveal_json_str(
+            value
+                .get("settings_config")
+                .and_then(|v| v.as_str())
+                .unwrap_or("{}"),
+        ),
         source_provider_id: value
             .get("source_provider_id")
             .and_then(|v| v.as_str())
@@ -85,9 +87,10 @@ pub fn to_db_value_provider(content: &CodexProviderContent) -> Value {
         "category".to_string(),
         Value::String(content.category.clone()),
     );
+    // Store auth.json content as vault references
     map.insert(
         "settings_config".to_string(),
-        Value::String(content.settings_config.clone()),
+        Value::String(seal_codex_settings(&content.settings_config)),
     );
 
     if let Some(ref source_id) = content.source_provider_id {
@@ -2,6 +2,7 @@ use std::fs;
 use std::path::Path;
 use serde_json::Value;
 
+use crate::coding::config_writer::{preview_config_file, write_config_file, ConfigFilePreview};
 use crate::db::DbState;
 use super::adapter;
 use super::types::*;
@@ -487,6 +488,16 @@ pub async fn apply_config_to_file_public(
     db: &surrealdb::Surreal<surrealdb::engine::local::Db>,
     provider_id: &str,
 ) -> Result<(), String> {
+    let (auth, final_config) = build_codex_provider_config(db, provider_id).await?;
+    write_codex_config_files(&auth, &final_config)?;
+    Ok(())
+}
+
+/// Build auth.json value and config.toml content (provider + common) for a provider
+async fn build_codex_provider_config(
+    db: &surrealdb::Surreal<surrealdb::engine::local::Db>,
+    provider_id: &str,
+) -> Result<(serde_json::Value, String), String> {
     // Get the provider
     let provider_result: Result<Vec<Value>, _> = db
         .query("SELECT *, type::string(id) as id FROM codex_provider WHERE id = type::thing('codex_provider', $id) LIMIT 1")
@@ -512,6 +523,7 @@ pub async fn apply_config_to_file_public(
     }
 
     // Parse provider settings_config
+    crate::secrets::check_resolved(&provider.settings_config)?;
     let provider_config: serde_json::Value = serde_json::from_str(&provider.settings_config)
         .map_err(|e| format!("Failed to parse provider config: {}", e))?;
 
@@ -548,8 +560,29 @@ pub async fn apply_config_to_file_public(
         config_toml
     };
 
-    write_codex_config_files(&auth, &final_config)?;
-    Ok(())
+    Ok((auth, final_config))
+}
+
+/// Preview applying a provider: returns the would-be auth.json and config.toml
+/// (with preserved mcp_servers) and diffs against the current files
+#[tauri::command]
+pub async fn preview_codex_config(
+    state: tauri::State<'_, DbState>,
+    provider_id: String,
+) -> Result<Vec<ConfigFilePreview>, String> {
+    let db = state.0.lock().await;
+    let (auth, final_config) = build_codex_provider_config(&db, &provider_id).await?;
+
+    let auth_path = get_codex_auth_path()?;
+    let config_path = get_codex_config_path()?;
+    let auth_content = serde_json::to_string_pretty(&auth)
+        .map_err(|e| format!("Failed to serialize auth: {}", e))?;
+    let config_content = build_codex_config_toml_preserve_mcp(&config_path, &final_config)?;
+
+    Ok(vec![
+        preview_config_file(&auth_path, &auth_content),
+        preview_config_file(&config_path, &config_content),
+    ])
 }
 
 /// Append common TOML config to provider config (common is appended after provider)
@@ -582,7 +615,7 @@ fn write_codex_config_files(auth: &serde_json::Value, config_toml: &str) -> Resu
     let auth_path = config_dir.join("auth.json");
     let auth_content = serde_json::to_string_pretty(auth)
         .map_err(|e| format!("Failed to serialize auth: {}", e))?;
-    fs::write(&auth_path, auth_content)
+    write_config_file(&auth_path, auth_content)
         .map_err(|e| format!("Failed to write auth.json: {}", e))?;
 
     // Write config.toml with partial update (preserve mcp_servers)
@@ -594,6 +627,15 @@ fn write_codex_config_files(auth: &serde_json::Value, config_toml: &str) -> Resu
 
 /// Write config.toml while preserving mcp_servers and other unrelated fields
 fn write_codex_config_toml_preserve_mcp(config_path: &std::path::Path, new_config: &str) -> Result<(), String> {
+    let final_content = build_codex_config_toml_preserve_mcp(config_path, new_config)?;
+    write_config_file(config_path, final_content)
+        .map_err(|e| format!("Failed to write config.toml: {}", e))?;
+
+    Ok(())
+}
+
+/// Build the config.toml content that `write_codex_config_toml_preserve_mcp` would write
+fn build_codex_config_toml_preserve_mcp(config_path: &std::path::Path, new_config: &str) -> Result<String, String> {
     use toml_edit::DocumentMut;
 
     // Parse new config
@@ -640,10 +682,8 @@ fn write_codex_config_toml_preserve_mcp(config_path: &std::path::Path, new_confi
     } else {
         format!("#:schema none\n{}", doc_content)
     };
-    fs::write(config_path, final_content)
-        .map_err(|e| format!("Failed to write config.toml: {}", e))?;
 
-    Ok(())
+    Ok(final_content)
 }
 
 /// Apply Codex config to files
@@ -723,6 +763,8 @@ pub async fn apply_config_internal<R: tauri::Runtime>(
     // Trigger WSL sync via event (Windows only)
     #[cfg(target_os = "windows")]
     let _ = app.emit("wsl-sync-request-codex", ());
+    // Trigger SSH sync via event (debounced by the SSH module)
+    let _ = app.emit("ssh-sync-request-codex", ());
 
     Ok(())
 }
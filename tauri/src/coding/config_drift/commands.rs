@@ -0,0 +1,34 @@
+use std::path::Path;
+use tauri::Emitter;
+
+use super::registry;
+use super::types::ConfigDrift;
+use crate::coding::config_writer::write_config_file;
+
+/// List unresolved config drifts
+#[tauri::command]
+pub async fn config_drift_list() -> Result<Vec<ConfigDrift>, String> {
+    Ok(registry::list_drifts())
+}
+
+/// Ignore a drift: keep the edited file and use it as the new baseline.
+/// Also used after importing the file into the current provider.
+#[tauri::command]
+pub async fn config_drift_ignore(file_path: String) -> Result<(), String> {
+    registry::accept_current(Path::new(&file_path))
+}
+
+/// Re-apply: overwrite the edited file with the content last written by the app
+#[tauri::command]
+pub async fn config_drift_reapply(app: tauri::AppHandle, file_path: String) -> Result<(), String> {
+    let path = Path::new(&file_path);
+    let content = registry::baseline_content(path)
+        .ok_or_else(|| format!("No applied content recorded for: {}", file_path))?;
+
+    write_config_file(path, content)?;
+
+    // Notify frontend and tray to refresh
+    let _ = app.emit("config-changed", "window");
+
+    Ok(())
+}
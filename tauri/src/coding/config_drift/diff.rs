@@ -0,0 +1,133 @@
+//! Structured (key-level) diff between the applied config and the live file
+
+use serde_json::Value;
+use std::path::Path;
+
+use super::types::{ConfigDriftChange, ConfigDriftKind};
+
+/// Parse a config file by extension: TOML for `.toml`, JSON/JSONC otherwise
+pub fn parse_config(path: &Path, content: &str) -> Option<Value> {
+    let is_toml = path
+        .extension()
+        .map(|ext| ext.eq_ignore_ascii_case("toml"))
+        .unwrap_or(false);
+
+    if is_toml {
+        let value: toml::Value = toml::from_str(content).ok()?;
+        serde_json::to_value(value).ok()
+    } else {
+        json5::from_str(content).ok()
+    }
+}
+
+/// Diff two versions of a config file.
+/// Falls back to a single whole-file change when either side cannot be parsed.
+pub fn diff_config_contents(path: &Path, expected: &str, actual: &str) -> Vec<ConfigDriftChange> {
+    match (parse_config(path, expected), parse_config(path, actual)) {
+        (Some(expected_value), Some(actual_value)) => diff_values(&expected_value, &actual_value),
+        _ if expected == actual => Vec::new(),
+        _ => vec![ConfigDriftChange {
+            path: String::new(),
+            kind: ConfigDriftKind::Modified,
+            expected: Some(Value::String(expected.to_string())),
+            actual: Some(Value::String(actual.to_string())),
+        }],
+    }
+}
+
+/// Key-level diff of two JSON values. Objects are compared recursively, arrays as a whole.
+pub fn diff_values(expected: &Value, actual: &Value) -> Vec<ConfigDriftChange> {
+    let mut changes = Vec::new();
+    collect_changes("", expected, actual, &mut changes);
+    changes
+}
+
+fn join_path(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+fn collect_changes(prefix: &str, expected: &Value, actual: &Value, changes: &mut Vec<ConfigDriftChange>) {
+    match (expected, actual) {
+        (Value::Object(expected_map), Value::Object(actual_map)) => {
+            for (key, expected_value) in expected_map {
+                let path = join_path(prefix, key);
+                match actual_map.get(key) {
+                    Some(actual_value) => collect_changes(&path, expected_value, actual_value, changes),
+                    None => changes.push(ConfigDriftChange {
+                        path,
+                        kind: ConfigDriftKind::Removed,
+                        expected: Some(expected_value.clone()),
+                        actual: None,
+                    }),
+                }
+            }
+            for (key, actual_value) in actual_map {
+                if !expected_map.contains_key(key) {
+                    changes.push(ConfigDriftChange {
+                        path: join_path(prefix, key),
+                        kind: ConfigDriftKind::Added,
+                        expected: None,
+                        actual: Some(actual_value.clone()),
+                    });
+                }
+            }
+        }
+        _ if expected != actual => changes.push(ConfigDriftChange {
+            path: prefix.to_string(),
+            kind: ConfigDriftKind::Modified,
+            expected: Some(expected.clone()),
+            actual: Some(actual.clone()),
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_values_nested() {
+        let expected = json!({ "model": "a", "env": { "KEY": "1", "OLD": "x" } });
+        let actual = json!({ "model": "b", "env": { "KEY": "1", "NEW": "y" } });
+
+        let mut changes: Vec<(String, ConfigDriftKind)> = diff_values(&expected, &actual)
+            .into_iter()
+            .map(|c| (c.path, c.kind))
+            .collect();
+        changes.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(
+            changes,
+            vec![
+                ("env.NEW".to_string(), ConfigDriftKind::Added),
+                ("env.OLD".to_string(), ConfigDriftKind::Removed),
+                ("model".to_string(), ConfigDriftKind::Modified),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_formatting_only_changes_are_ignored() {
+        let path = Path::new("config.toml");
+        let changes = diff_config_contents(path, "model = \"a\"\n", "# comment\nmodel   = \"a\"\n");
+        assert!(changes.is_empty());
+
+        let path = Path::new("opencode.jsonc");
+        let changes = diff_config_contents(path, "{\"a\": 1}", "{\n  // note\n  \"a\": 1,\n}");
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn test_unparsable_file_is_whole_file_change() {
+        let changes = diff_config_contents(Path::new("settings.json"), "{}", "{");
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].path, "");
+        assert_eq!(changes[0].kind, ConfigDriftKind::Modified);
+    }
+}
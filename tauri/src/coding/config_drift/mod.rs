@@ -0,0 +1,19 @@
+//! Config Drift Module
+//!
+//! Detects edits made outside the app to the CLI config files whose content is derived
+//! from the database (`~/.claude/settings.json`, `~/.codex/config.toml`, `opencode.jsonc`).
+//!
+//! The content written by the app is recorded as a baseline, the files are polled in the
+//! background, and a `config-drift` event carrying a key-level diff is emitted when a file
+//! no longer matches. The UI can then import the file, re-apply the baseline or ignore it.
+
+pub mod commands;
+pub mod diff;
+pub mod registry;
+pub mod types;
+pub mod watcher;
+
+pub use commands::*;
+pub use registry::record_write;
+pub use types::*;
+pub use watcher::start_config_drift_watcher;
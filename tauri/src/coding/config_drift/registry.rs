@@ -0,0 +1,305 @@
+//! Applied-output baselines for watched config files
+//!
+//! The config writer reports every write here; writes to watched files become the new
+//! baseline. Baseline hashes are persisted to `<app data>/config-drift.json` so edits made
+//! while the app was closed are detected on the next start. The applied content itself
+//! holds credentials, so it is kept out of that file: it is written to
+//! `<app data>/config-drift/<file key>.baseline`, readable by the current user only.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use super::diff::diff_config_contents;
+use super::types::ConfigDrift;
+use crate::coding::config_writer::writer::{atomic_write, atomic_write_private, resolve_write_target};
+
+static REGISTRY: OnceLock<Mutex<DriftRegistry>> = OnceLock::new();
+
+/// Content written by the app for one file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Baseline {
+    tool: String,
+    hash: String,
+    recorded_at: String,
+    /// Applied content; held in memory and in the baseline file, never persisted here.
+    /// Older versions did persist it, such entries are moved out on load.
+    #[serde(default, skip_serializing)]
+    content: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PersistedState {
+    #[serde(default)]
+    baselines: HashMap<String, Baseline>,
+}
+
+struct DriftRegistry {
+    state_path: PathBuf,
+    /// Directory of the applied content files
+    content_dir: PathBuf,
+    /// Watched file path -> tool
+    watched: HashMap<String, String>,
+    baselines: HashMap<String, Baseline>,
+    /// Unresolved drift per file path
+    drifts: HashMap<String, ConfigDrift>,
+}
+
+/// Get the app data dir holding the drift state
+fn get_state_dir() -> Result<PathBuf, String> {
+    dirs::data_dir()
+        .map(|p| p.join("com.ai-toolbox"))
+        .or_else(|| dirs::home_dir().map(|p| p.join(".ai-toolbox")))
+        .ok_or_else(|| "Failed to get app data directory".to_string())
+}
+
+pub fn hash_content(content: &[u8]) -> String {
+    hex::encode(Sha256::digest(content))
+}
+
+fn path_key(path: &Path) -> String {
+    resolve_write_target(path).to_string_lossy().to_string()
+}
+
+/// Load persisted baselines. Called once when the watcher starts.
+pub(crate) fn init() -> Result<(), String> {
+    let state_dir = get_state_dir()?;
+    let state_path = state_dir.join("config-drift.json");
+    let persisted: PersistedState = fs::read_to_string(&state_path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+
+    let registry = DriftRegistry {
+        state_path,
+        content_dir: state_dir.join("config-drift"),
+        watched: HashMap::new(),
+        baselines: persisted.baselines,
+        drifts: HashMap::new(),
+    };
+    registry.migrate_legacy_content();
+
+    let _ = REGISTRY.set(Mutex::new(registry));
+    Ok(())
+}
+
+fn with_registry<T>(f: impl FnOnce(&mut DriftRegistry) -> T) -> Option<T> {
+    let registry = REGISTRY.get()?;
+    let mut guard = registry.lock().ok()?;
+    Some(f(&mut guard))
+}
+
+impl DriftRegistry {
+    fn content_path(&self, key: &str) -> PathBuf {
+        let file_key = hex::encode(&Sha256::digest(key.as_bytes())[..8]);
+        self.content_dir.join(format!("{}.baseline", file_key))
+    }
+
+    fn store_content(&self, key: &str, content: &[u8]) {
+        let result = fs::create_dir_all(&self.content_dir)
+            .map_err(|e| format!("Failed to create {}: {}", self.content_dir.display(), e))
+            .and_then(|_| atomic_write_private(&self.content_path(key), content));
+        if let Err(e) = result {
+            log::warn!("Failed to store config drift baseline: {}", e);
+        }
+    }
+
+    /// Applied content of a baseline; None when it was lost or no longer matches the hash
+    fn load_content(&mut self, key: &str) -> Option<String> {
+        let baseline = self.baselines.get(key)?;
+        if let Some(content) = &baseline.content {
+            return Some(content.clone());
+        }
+        let content = fs::read(self.content_path(key)).ok()?;
+        if hash_content(&content) != baseline.hash {
+            return None;
+        }
+        let content = String::from_utf8_lossy(&content).to_string();
+        if let Some(baseline) = self.baselines.get_mut(key) {
+            baseline.content = Some(content.clone());
+        }
+        Some(content)
+    }
+
+    /// Move content persisted by older versions into the baseline files
+    fn migrate_legacy_content(&self) {
+        let legacy: Vec<(&String, &String)> = self
+            .baselines
+            .iter()
+            .filter_map(|(key, baseline)| baseline.content.as_ref().map(|c| (key, c)))
+            .collect();
+        if legacy.is_empty() {
+            return;
+        }
+        for (key, content) in legacy {
+            self.store_content(key, content.as_bytes());
+        }
+        self.persist();
+    }
+
+    fn persist(&self) {
+        let state = PersistedState {
+            baselines: self.baselines.clone(),
+        };
+        let result = serde_json::to_string(&state)
+            .map_err(|e| format!("Failed to serialize drift state: {}", e))
+            .and_then(|json| atomic_write(&self.state_path, json.as_bytes()));
+        if let Err(e) = result {
+            log::warn!("Failed to persist config drift state: {}", e);
+        }
+    }
+
+    fn set_baseline(&mut self, key: &str, tool: &str, content: &[u8]) {
+        self.store_content(key, content);
+        self.baselines.insert(
+            key.to_string(),
+            Baseline {
+                tool: tool.to_string(),
+                hash: hash_content(content),
+                recorded_at: Local::now().to_rfc3339(),
+                content: Some(String::from_utf8_lossy(content).to_string()),
+            },
+        );
+        self.drifts.remove(key);
+        self.persist();
+    }
+}
+
+/// Record a write made by the app. Only watched files are tracked.
+pub fn record_write(path: &Path, contents: &[u8]) {
+    let key = path_key(path);
+    with_registry(|registry| {
+        if let Some(tool) = registry.watched.get(&key).cloned() {
+            registry.set_baseline(&key, &tool, contents);
+        }
+    });
+}
+
+/// Replace the set of watched files
+pub(crate) fn set_watched(targets: Vec<(String, PathBuf)>) {
+    with_registry(|registry| {
+        registry.watched = targets
+            .into_iter()
+            .map(|(tool, path)| (path_key(&path), tool))
+            .collect();
+        let watched = &registry.watched;
+        registry.drifts.retain(|key, _| watched.contains_key(key));
+    });
+}
+
+/// Compare a watched file with its baseline.
+/// Returns the drift only when it is new or changed since it was last reported.
+pub(crate) fn check_file(tool: &str, path: &Path) -> Option<ConfigDrift> {
+    let key = path_key(path);
+    let current = fs::read(&key).ok();
+
+    with_registry(|registry| {
+        let Some(current) = current else {
+            registry.drifts.remove(&key);
+            return None;
+        };
+        let current_hash = hash_content(&current);
+
+        let Some(baseline) = registry.baselines.get(&key).cloned() else {
+            // First time this file is seen: adopt it as the baseline
+            registry.set_baseline(&key, tool, &current);
+            return None;
+        };
+        if baseline.hash == current_hash {
+            registry.drifts.remove(&key);
+            return None;
+        }
+        if registry
+            .drifts
+            .get(&key)
+            .map(|d| d.actual_hash == current_hash)
+            .unwrap_or(false)
+        {
+            return None;
+        }
+
+        let Some(expected) = registry.load_content(&key) else {
+            // Applied content is gone, nothing to compare against
+            log::warn!("Config drift baseline content missing for {}, adopting the current file", key);
+            registry.set_baseline(&key, tool, &current);
+            return None;
+        };
+        let changes = diff_config_contents(
+            Path::new(&key),
+            &expected,
+            &String::from_utf8_lossy(&current),
+        );
+        if changes.is_empty() {
+            // Formatting or comment-only edit, nothing to report
+            registry.set_baseline(&key, tool, &current);
+            return None;
+        }
+
+        let drift = ConfigDrift {
+            tool: tool.to_string(),
+            file_path: key.clone(),
+            expected_hash: baseline.hash,
+            actual_hash: current_hash,
+            changes,
+            detected_at: Local::now().to_rfc3339(),
+        };
+        registry.drifts.insert(key, drift.clone());
+        Some(drift)
+    })
+    .flatten()
+}
+
+/// Unresolved drifts
+pub(crate) fn list_drifts() -> Vec<ConfigDrift> {
+    with_registry(|registry| {
+        let mut drifts: Vec<ConfigDrift> = registry.drifts.values().cloned().collect();
+        drifts.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        drifts
+    })
+    .unwrap_or_default()
+}
+
+/// Accept the live file content as the new baseline
+pub(crate) fn accept_current(path: &Path) -> Result<(), String> {
+    let key = path_key(path);
+    let current = fs::read(&key).map_err(|e| format!("Failed to read {}: {}", key, e))?;
+
+    with_registry(|registry| {
+        let tool = registry
+            .watched
+            .get(&key)
+            .or_else(|| registry.baselines.get(&key).map(|b| &b.tool))
+            .cloned()
+            .ok_or_else(|| format!("File is not watched: {}", key))?;
+        registry.set_baseline(&key, &tool, &current);
+        Ok(())
+    })
+    .unwrap_or_else(|| Err("Config drift watcher is not running".to_string()))
+}
+
+/// Content last written by the app for a file
+pub(crate) fn baseline_content(path: &Path) -> Option<String> {
+    let key = path_key(path);
+    with_registry(|registry| registry.load_content(&key)).flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_persisted_state_omits_content() {
+        let legacy = r#"{"baselines":{"/x/settings.json":{"tool":"claude","hash":"abc","content":"{\"token\":\"sk\"}","recorded_at":"t"}}}"#;
+        let state: PersistedState = serde_json::from_str(legacy).unwrap();
+        let baseline = &state.baselines["/x/settings.json"];
+        assert_eq!(baseline.content.as_deref(), Some("{\"token\":\"sk\"}"));
+
+        let json = serde_json::to_string(&state).unwrap();
+        assert!(!json.contains("token"));
+        assert!(json.contains("\"hash\":\"abc\""));
+    }
+}
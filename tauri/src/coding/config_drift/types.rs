@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+/// Kind of a single drifted key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConfigDriftKind {
+    /// Key exists in the file but was not written by the app
+    Added,
+    /// Key written by the app is missing from the file
+    Removed,
+    /// Key value differs from what the app wrote
+    Modified,
+}
+
+/// One key-level difference between the applied output and the live file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigDriftChange {
+    /// Dotted key path (empty when the whole file could not be parsed)
+    pub path: String,
+    pub kind: ConfigDriftKind,
+    /// Value written by the app
+    pub expected: Option<serde_json::Value>,
+    /// Value found in the live file
+    pub actual: Option<serde_json::Value>,
+}
+
+/// Payload of the `config-drift` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigDrift {
+    /// Tool that owns the file: `claude`, `codex` or `opencode`
+    pub tool: String,
+    pub file_path: String,
+    /// SHA-256 of the content written by the app
+    pub expected_hash: String,
+    /// SHA-256 of the live file content
+    pub actual_hash: String,
+    pub changes: Vec<ConfigDriftChange>,
+    pub detected_at: String,
+}
@@ -0,0 +1,63 @@
+use log::{info, warn};
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+
+use super::registry;
+use crate::db::DbState;
+
+/// Poll interval for watched config files
+const CHECK_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Start the config drift watcher as a background task
+pub fn start_config_drift_watcher(app_handle: tauri::AppHandle) {
+    if let Err(e) = registry::init() {
+        warn!("Config drift watcher not started: {}", e);
+        return;
+    }
+
+    tauri::async_runtime::spawn(async move {
+        info!("Config drift watcher started");
+
+        loop {
+            let targets = collect_watch_targets(&app_handle).await;
+            registry::set_watched(targets.clone());
+
+            for (tool, path) in targets {
+                if let Some(drift) = registry::check_file(&tool, &path) {
+                    info!(
+                        "Config drift detected in {} ({} changes)",
+                        drift.file_path,
+                        drift.changes.len()
+                    );
+                    let _ = app_handle.emit("config-drift", &drift);
+                }
+            }
+
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+/// Config files whose content is derived from the database
+async fn collect_watch_targets(app_handle: &tauri::AppHandle) -> Vec<(String, PathBuf)> {
+    let mut targets = Vec::new();
+
+    if let Ok(path) = crate::coding::claude_code::get_claude_config_path() {
+        targets.push(("claude".to_string(), PathBuf::from(path)));
+    }
+
+    if let Ok(path) = crate::coding::codex::get_codex_config_file_path() {
+        targets.push(("codex".to_string(), PathBuf::from(path)));
+    }
+
+    let db_state = app_handle.state::<DbState>();
+    if let Ok(path) = crate::coding::open_code::get_opencode_config_path(db_state).await {
+        match crate::coding::expand_local_path(&path) {
+            Ok(expanded) => targets.push(("opencode".to_string(), PathBuf::from(expanded))),
+            Err(e) => warn!("Failed to expand OpenCode config path: {}", e),
+        }
+    }
+
+    targets
+}
@@ -0,0 +1,43 @@
+use std::path::Path;
+use tauri::Emitter;
+
+use super::snapshot;
+use super::types::ConfigSnapshot;
+use super::writer::{resolve_write_target, write_config_file_with_root};
+
+/// List config file snapshots (newest first), optionally for a single file
+#[tauri::command]
+pub async fn config_list_snapshots(file_path: Option<String>) -> Result<Vec<ConfigSnapshot>, String> {
+    let root = snapshot::get_snapshot_root()?;
+
+    let target = match file_path {
+        Some(path) if !path.trim().is_empty() => {
+            let expanded = crate::coding::expand_local_path(&path)?;
+            Some(resolve_write_target(Path::new(&expanded)))
+        }
+        _ => None,
+    };
+
+    Ok(snapshot::list_snapshots(&root, target.as_deref()))
+}
+
+/// Roll a config file back to a snapshot.
+/// The current content is snapshotted first, so the rollback itself can be undone.
+#[tauri::command]
+pub async fn config_restore_snapshot(
+    app: tauri::AppHandle,
+    snapshot_id: String,
+) -> Result<String, String> {
+    let root = snapshot::get_snapshot_root()?;
+    let (file_path, content) = snapshot::read_snapshot(&root, &snapshot_id)?;
+
+    write_config_file_with_root(&root, &file_path, &content)
+        .map_err(|e| format!("Failed to restore snapshot: {}", e))?;
+
+    log::info!("Restored {} from snapshot {}", file_path.display(), snapshot_id);
+
+    // Notify frontend and tray to refresh
+    let _ = app.emit("config-changed", "window");
+
+    Ok(file_path.to_string_lossy().to_string())
+}
@@ -0,0 +1,20 @@
+//! Config Writer Module
+//!
+//! Shared writer for the external CLI config files managed by the app
+//! (`~/.claude/settings.json`, `~/.codex/auth.json`, `config.toml`, `opencode.jsonc`, ...).
+//!
+//! Every write goes to a temp file in the same directory, is fsynced and then atomically
+//! renamed over the target, so a crash or full disk never leaves a truncated file behind.
+//! The previous content of the file is kept as a timestamped snapshot and can be rolled back.
+//! `preview_config_file` renders the same write as a unified diff without touching the file.
+
+pub mod commands;
+pub mod preview;
+pub mod snapshot;
+pub mod types;
+pub mod writer;
+
+pub use commands::*;
+pub use preview::preview_config_file;
+pub use types::*;
+pub use writer::write_config_file;
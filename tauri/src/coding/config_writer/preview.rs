@@ -0,0 +1,63 @@
+use similar::TextDiff;
+use std::fs;
+use std::path::Path;
+
+use super::types::ConfigFilePreview;
+use super::writer::resolve_write_target;
+
+/// Build a dry-run preview of writing `new_content` to `path`
+pub fn preview_config_file(path: &Path, new_content: &str) -> ConfigFilePreview {
+    let target = resolve_write_target(path);
+    let current_content = fs::read_to_string(&target).ok();
+    let current = current_content.as_deref().unwrap_or("");
+    let file_name = target.to_string_lossy().to_string();
+
+    let diff = if current == new_content {
+        String::new()
+    } else {
+        TextDiff::from_lines(current, new_content)
+            .unified_diff()
+            .context_radius(3)
+            .header(&format!("a/{}", file_name), &format!("b/{}", file_name))
+            .to_string()
+    };
+
+    ConfigFilePreview {
+        file_path: file_name,
+        changed: current_content.as_deref() != Some(new_content),
+        current_content,
+        new_content: new_content.to_string(),
+        diff,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_diff() {
+        let dir = std::env::temp_dir().join(format!(
+            "ai-toolbox-preview-{}",
+            uuid::Uuid::new_v4().simple()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+
+        let missing = preview_config_file(&path, "a = 1\n");
+        assert!(missing.changed);
+        assert!(missing.current_content.is_none());
+        assert!(missing.diff.contains("+a = 1"));
+
+        fs::write(&path, "a = 1\nb = 2\n").unwrap();
+        let preview = preview_config_file(&path, "a = 1\nb = 3\n");
+        assert!(preview.changed);
+        assert!(preview.diff.contains("-b = 2\n+b = 3"));
+
+        let unchanged = preview_config_file(&path, "a = 1\nb = 2\n");
+        assert!(!unchanged.changed);
+        assert!(unchanged.diff.is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,290 @@
+//! Rolling pre-write snapshots of external config files
+//!
+//! Layout: `<app data>/config-snapshots/<file key>/`
+//! - `target.json`: `{ "path": "<absolute path of the config file>" }`
+//! - `<%Y%m%d-%H%M%S-%3f>.snap`: file content before a write
+//!
+//! The file key is derived from the config file path, so every file has its own ring
+//! of at most `MAX_SNAPSHOTS_PER_FILE` snapshots.
+
+use chrono::{Local, NaiveDateTime, TimeZone};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::types::ConfigSnapshot;
+use super::writer::atomic_write_private;
+
+/// Number of snapshots kept per config file
+pub const MAX_SNAPSHOTS_PER_FILE: usize = 20;
+
+const TARGET_META_FILE: &str = "target.json";
+const SNAPSHOT_EXT: &str = "snap";
+const SNAPSHOT_TIME_FORMAT: &str = "%Y%m%d-%H%M%S-%3f";
+
+/// Get the snapshot root directory (inside the app data dir)
+pub fn get_snapshot_root() -> Result<PathBuf, String> {
+    dirs::data_dir()
+        .map(|p| p.join("com.ai-toolbox"))
+        .or_else(|| dirs::home_dir().map(|p| p.join(".ai-toolbox")))
+        .map(|p| p.join("config-snapshots"))
+        .ok_or_else(|| "Failed to get app data directory".to_string())
+}
+
+/// Stable directory key for a config file path
+fn file_key(path: &Path) -> String {
+    let digest = Sha256::digest(path.to_string_lossy().as_bytes());
+    hex::encode(&digest[..8])
+}
+
+/// Snapshot files of one config file, oldest first
+fn list_snapshot_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().map(|ext| ext == SNAPSHOT_EXT).unwrap_or(false))
+            .collect(),
+        Err(_) => return Vec::new(),
+    };
+    // Timestamped names sort chronologically
+    files.sort();
+    files
+}
+
+fn read_target_path(dir: &Path) -> Option<String> {
+    let content = fs::read_to_string(dir.join(TARGET_META_FILE)).ok()?;
+    let value: serde_json::Value = serde_json::from_str(&content).ok()?;
+    value.get("path").and_then(|v| v.as_str()).map(String::from)
+}
+
+fn to_snapshot(key: &str, file_path: &str, snapshot_path: &Path) -> Option<ConfigSnapshot> {
+    let name = snapshot_path.file_name()?.to_string_lossy().to_string();
+    let stem = name.strip_suffix(&format!(".{}", SNAPSHOT_EXT))?;
+    let metadata = fs::metadata(snapshot_path).ok()?;
+
+    let created_at = NaiveDateTime::parse_from_str(stem, SNAPSHOT_TIME_FORMAT)
+        .ok()
+        .and_then(|dt| Local.from_local_datetime(&dt).single())
+        .map(|dt| dt.to_rfc3339())
+        .unwrap_or_default();
+
+    Some(ConfigSnapshot {
+        id: format!("{}/{}", key, name),
+        file_path: file_path.to_string(),
+        created_at,
+        size: metadata.len(),
+    })
+}
+
+/// Copy the current content of `target` into its snapshot ring.
+/// Returns `None` when the content is identical to the latest snapshot.
+pub(crate) fn take_snapshot(root: &Path, target: &Path) -> Result<Option<ConfigSnapshot>, String> {
+    let current = fs::read(target)
+        .map_err(|e| format!("Failed to read {} for snapshot: {}", target.display(), e))?;
+
+    let key = file_key(target);
+    let dir = root.join(&key);
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create snapshot directory: {}", e))?;
+
+    let target_str = target.to_string_lossy().to_string();
+    if read_target_path(&dir).is_none() {
+        let meta = serde_json::json!({ "path": target_str });
+        atomic_write_private(&dir.join(TARGET_META_FILE), meta.to_string().as_bytes())?;
+    }
+
+    let existing = list_snapshot_files(&dir);
+    if let Some(latest) = existing.last() {
+        if fs::read(latest).map(|c| c == current).unwrap_or(false) {
+            return Ok(None);
+        }
+    }
+
+    let stamp = Local::now().format(SNAPSHOT_TIME_FORMAT).to_string();
+    let mut snapshot_path = dir.join(format!("{}.{}", stamp, SNAPSHOT_EXT));
+    let mut suffix = 1;
+    while snapshot_path.exists() {
+        snapshot_path = dir.join(format!("{}{}.{}", stamp, suffix, SNAPSHOT_EXT));
+        suffix += 1;
+    }
+    // Snapshots of auth files hold API keys
+    atomic_write_private(&snapshot_path, &current)?;
+
+    prune_snapshots(&dir, MAX_SNAPSHOTS_PER_FILE);
+
+    Ok(to_snapshot(&key, &target_str, &snapshot_path))
+}
+
+/// Remove the oldest snapshots beyond `keep`
+fn prune_snapshots(dir: &Path, keep: usize) {
+    let files = list_snapshot_files(dir);
+    if files.len() <= keep {
+        return;
+    }
+    for old in files.iter().take(files.len() - keep) {
+        if let Err(e) = fs::remove_file(old) {
+            log::warn!("Failed to remove old config snapshot {}: {}", old.display(), e);
+        }
+    }
+}
+
+/// List snapshots, newest first. When `target` is given, only that file's snapshots are returned.
+pub(crate) fn list_snapshots(root: &Path, target: Option<&Path>) -> Vec<ConfigSnapshot> {
+    let dirs: Vec<PathBuf> = match target {
+        Some(path) => vec![root.join(file_key(path))],
+        None => match fs::read_dir(root) {
+            Ok(entries) => entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.is_dir())
+                .collect(),
+            Err(_) => Vec::new(),
+        },
+    };
+
+    let mut snapshots: Vec<ConfigSnapshot> = Vec::new();
+    for dir in dirs {
+        let Some(file_path) = read_target_path(&dir) else {
+            continue;
+        };
+        let key = dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        snapshots.extend(
+            list_snapshot_files(&dir)
+                .iter()
+                .filter_map(|p| to_snapshot(&key, &file_path, p)),
+        );
+    }
+
+    snapshots.sort_by(|a, b| b.created_at.cmp(&a.created_at).then_with(|| b.id.cmp(&a.id)));
+    snapshots
+}
+
+/// Resolve a snapshot id into (config file path, snapshot content)
+pub(crate) fn read_snapshot(root: &Path, snapshot_id: &str) -> Result<(PathBuf, Vec<u8>), String> {
+    let (key, name) = snapshot_id
+        .split_once('/')
+        .ok_or_else(|| format!("Invalid snapshot id: {}", snapshot_id))?;
+
+    let is_safe = |s: &str| !s.is_empty() && !s.contains("..") && !s.contains(['/', '\\']);
+    if !is_safe(key) || !is_safe(name) || !name.ends_with(&format!(".{}", SNAPSHOT_EXT)) {
+        return Err(format!("Invalid snapshot id: {}", snapshot_id));
+    }
+
+    let dir = root.join(key);
+    let file_path = read_target_path(&dir)
+        .ok_or_else(|| format!("Snapshot target not found for: {}", snapshot_id))?;
+    let content = fs::read(dir.join(name))
+        .map_err(|e| format!("Failed to read snapshot {}: {}", snapshot_id, e))?;
+
+    Ok((PathBuf::from(file_path), content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coding::config_writer::writer::write_config_file_with_root;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "ai-toolbox-config-writer-{}-{}",
+            name,
+            uuid::Uuid::new_v4().simple()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_write_creates_file_without_snapshot() {
+        let dir = temp_dir("create");
+        let root = dir.join("snapshots");
+        let target = dir.join("nested").join("settings.json");
+
+        write_config_file_with_root(&root, &target, b"{}").unwrap();
+
+        assert_eq!(fs::read_to_string(&target).unwrap(), "{}");
+        assert!(list_snapshots(&root, Some(&target)).is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_overwrite_keeps_previous_content() {
+        let dir = temp_dir("overwrite");
+        let root = dir.join("snapshots");
+        let target = dir.join("config.toml");
+
+        write_config_file_with_root(&root, &target, b"a = 1").unwrap();
+        write_config_file_with_root(&root, &target, b"a = 2").unwrap();
+        // Same content as latest snapshot is not duplicated
+        write_config_file_with_root(&root, &target, b"a = 2").unwrap();
+
+        let snapshots = list_snapshots(&root, Some(&target));
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].file_path, target.to_string_lossy());
+
+        let (path, content) = read_snapshot(&root, &snapshots[1].id).unwrap();
+        assert_eq!(path, target);
+        assert_eq!(content, b"a = 1");
+
+        // No temp files left behind
+        let leftovers = fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().ends_with(".tmp"))
+            .count();
+        assert_eq!(leftovers, 0);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_snapshots_are_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = temp_dir("private");
+        let root = dir.join("snapshots");
+        let target = dir.join("auth.json");
+        fs::write(&target, r#"{"OPENAI_API_KEY":"sk-old"}"#).unwrap();
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let snapshot = take_snapshot(&root, &target).unwrap().unwrap();
+        let snapshot_dir = root.join(file_key(&target));
+        let snapshot_path = snapshot_dir.join(snapshot.id.split_once('/').unwrap().1);
+        for path in [snapshot_path, snapshot_dir.join(TARGET_META_FILE)] {
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600, "{}", path.display());
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_prune_keeps_latest() {
+        let dir = temp_dir("prune");
+        let snap_dir = dir.join("key");
+        fs::create_dir_all(&snap_dir).unwrap();
+        for i in 0..5 {
+            fs::write(snap_dir.join(format!("2026010{}-000000-000.snap", i)), "x").unwrap();
+        }
+
+        prune_snapshots(&snap_dir, 2);
+
+        let names: Vec<String> = list_snapshot_files(&snap_dir)
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, vec!["20260103-000000-000.snap", "20260104-000000-000.snap"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_read_snapshot_rejects_traversal() {
+        let root = PathBuf::from("/nonexistent");
+        assert!(read_snapshot(&root, "../etc/passwd.snap").is_err());
+        assert!(read_snapshot(&root, "abc/../../x.snap").is_err());
+        assert!(read_snapshot(&root, "no-separator").is_err());
+    }
+}
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+/// A pre-write snapshot of an external config file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigSnapshot {
+    /// Snapshot identifier (`<file key>/<snapshot file name>`), used for rollback
+    pub id: String,
+    /// Absolute path of the config file this snapshot belongs to
+    pub file_path: String,
+    /// Time the snapshot was taken (RFC 3339)
+    pub created_at: String,
+    /// Snapshot size in bytes
+    pub size: u64,
+}
+
+/// Dry-run result for one config file: what an apply would write
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigFilePreview {
+    /// Absolute path of the config file
+    pub file_path: String,
+    /// Current file content (`None` when the file does not exist yet)
+    pub current_content: Option<String>,
+    /// Content that would be written
+    pub new_content: String,
+    /// Unified diff from the current content to the new content (empty when unchanged)
+    pub diff: String,
+    pub changed: bool,
+}
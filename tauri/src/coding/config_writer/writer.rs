@@ -0,0 +1,136 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::snapshot;
+
+/// Atomically write an external config file, keeping a snapshot of the previous content.
+///
+/// Parent directories are created when missing. If the path is a symlink, the file it
+/// points to is written instead so that dotfile managers keep working.
+pub fn write_config_file(path: &Path, contents: impl AsRef<[u8]>) -> Result<(), String> {
+    let snapshot_root = snapshot::get_snapshot_root()?;
+    write_config_file_with_root(&snapshot_root, path, contents.as_ref())
+}
+
+/// Same as `write_config_file` with an explicit snapshot root directory
+pub(crate) fn write_config_file_with_root(
+    snapshot_root: &Path,
+    path: &Path,
+    contents: &[u8],
+) -> Result<(), String> {
+    let target = resolve_write_target(path);
+
+    if let Some(parent) = target.parent() {
+        if !parent.as_os_str().is_empty() && !parent.exists() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create directory {}: {}", parent.display(), e))?;
+        }
+    }
+
+    if target.is_file() {
+        snapshot::take_snapshot(snapshot_root, &target)?;
+    }
+
+    atomic_write(&target, contents)?;
+
+    // The written content becomes the drift baseline for watched files
+    crate::coding::config_drift::record_write(&target, contents);
+    Ok(())
+}
+
+/// Resolve the path that should actually be replaced (follows symlinks)
+pub(crate) fn resolve_write_target(path: &Path) -> PathBuf {
+    let is_symlink = fs::symlink_metadata(path)
+        .map(|m| m.file_type().is_symlink())
+        .unwrap_or(false);
+
+    if is_symlink {
+        if let Ok(resolved) = fs::canonicalize(path) {
+            return resolved;
+        }
+    }
+
+    path.to_path_buf()
+}
+
+/// Write to a temp file in the target directory, fsync it, then rename it over the target
+pub(crate) fn atomic_write(target: &Path, contents: &[u8]) -> Result<(), String> {
+    atomic_write_with_mode(target, contents, false)
+}
+
+/// Same as `atomic_write` for files only the current user may read (0600 on Unix).
+/// The temp file is created with these permissions, so the content is never readable
+/// by other users, not even before the rename.
+pub(crate) fn atomic_write_private(target: &Path, contents: &[u8]) -> Result<(), String> {
+    atomic_write_with_mode(target, contents, true)
+}
+
+fn atomic_write_with_mode(target: &Path, contents: &[u8], private: bool) -> Result<(), String> {
+    let parent = match target.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let file_name = target
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| format!("Invalid config file path: {}", target.display()))?;
+    let tmp_path = parent.join(format!(
+        ".{}.{}.tmp",
+        file_name,
+        uuid::Uuid::new_v4().simple()
+    ));
+
+    let result = write_and_rename(&tmp_path, target, contents, private);
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result?;
+
+    sync_dir(&parent);
+    Ok(())
+}
+
+fn write_and_rename(tmp_path: &Path, target: &Path, contents: &[u8], private: bool) -> Result<(), String> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    if private {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(tmp_path)
+        .map_err(|e| format!("Failed to create temp file {}: {}", tmp_path.display(), e))?;
+
+    file.write_all(contents)
+        .map_err(|e| format!("Failed to write temp file {}: {}", tmp_path.display(), e))?;
+
+    // Keep the permissions of the file being replaced (auth files are often 0600)
+    if !private {
+        if let Ok(metadata) = fs::metadata(target) {
+            let _ = file.set_permissions(metadata.permissions());
+        }
+    }
+
+    file.sync_all()
+        .map_err(|e| format!("Failed to sync temp file {}: {}", tmp_path.display(), e))?;
+    drop(file);
+
+    fs::rename(tmp_path, target)
+        .map_err(|e| format!("Failed to replace {}: {}", target.display(), e))
+}
+
+/// Persist the rename itself (best effort, directories cannot be opened on Windows)
+fn sync_dir(dir: &Path) {
+    #[cfg(unix)]
+    {
+        if let Ok(d) = fs::File::open(dir) {
+            let _ = d.sync_all();
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = dir;
+    }
+}
@@ -5,7 +5,11 @@
 use serde_json::Value;
 
 use crate::coding::db_extract_id;
-use super::types::{McpPreferences, McpServer, McpSyncDetail, McpSyncDetailDto, FavoriteMcp};
+use crate::secrets;
+use super::types::{
+    McpEnvVar, McpPreferences, McpProject, McpProjectDto, McpProjectSyncEntry, McpServer, McpSyncDetail, McpSyncDetailDto,
+    FavoriteMcp,
+};
 
 /// Convert database record to McpServer struct
 pub fn from_db_mcp_server(value: Value) -> McpServer {
@@ -55,6 +59,18 @@ pub fn from_db_mcp_server(value: Value) -> McpServer {
             .map(|s| s.to_string()),
         tags,
         sort_index: value.get("sort_index").and_then(|v| v.as_i64()).unwrap_or(0) as i32,
+        health: value
+            .get("health")
+            .filter(|v| !v.is_null())
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        tool_catalog: value
+            .get("tool_catalog")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default(),
+        tool_filters: value
+            .get("tool_filters")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default(),
         created_at: value.get("created_at").and_then(|v| v.as_i64()).unwrap_or(0),
         updated_at: value.get("updated_at").and_then(|v| v.as_i64()).unwrap_or(0),
     }
@@ -71,6 +87,9 @@ pub fn to_clean_mcp_server_payload(server: &McpServer) -> Value {
         "description": server.description,
         "tags": server.tags,
         "sort_index": server.sort_index,
+        "health": server.health,
+        "tool_catalog": server.tool_catalog,
+        "tool_filters": server.tool_filters,
         "created_at": server.created_at,
         "updated_at": server.updated_at,
     })
@@ -166,6 +185,15 @@ pub fn from_db_mcp_preferences(value: Value) -> McpPreferences {
             .get("favorites_initialized")
             .and_then(|v| v.as_bool())
             .unwrap_or(false),
+        gateway_enabled: value
+            .get("gateway_enabled")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+        gateway_port: value
+            .get("gateway_port")
+            .and_then(|v| v.as_u64())
+            .and_then(|v| u16::try_from(v).ok())
+            .unwrap_or(crate::coding::mcp::gateway::DEFAULT_GATEWAY_PORT),
         updated_at: value.get("updated_at").and_then(|v| v.as_i64()).unwrap_or(0),
     }
 }
@@ -176,6 +204,8 @@ pub fn to_mcp_preferences_payload(prefs: &McpPreferences) -> Value {
         "show_in_tray": prefs.show_in_tray,
         "preferred_tools": prefs.preferred_tools,
         "favorites_initialized": prefs.favorites_initialized,
+        "gateway_enabled": prefs.gateway_enabled,
+        "gateway_port": prefs.gateway_port,
         "updated_at": prefs.updated_at,
     })
 }
@@ -219,3 +249,122 @@ pub fn from_db_favorite_mcp(value: Value) -> FavoriteMcp {
         updated_at: value.get("updated_at").and_then(|v| v.as_i64()).unwrap_or(0),
     }
 }
+
+/// Convert database record to McpEnvVar struct, revealing secret values
+pub fn from_db_mcp_env_var(value: Value) -> McpEnvVar {
+    let text = |key: &str| value.get(key).and_then(|v| v.as_str()).unwrap_or("").to_string();
+    McpEnvVar {
+        id: db_extract_id(&value),
+        name: text("name"),
+        value: secrets::reveal(&text("value")),
+        secret: value.get("secret").and_then(|v| v.as_bool()).unwrap_or(false),
+        overrides: value
+            .get("overrides")
+            .and_then(|v| v.as_object())
+            .map(|obj| {
+                obj.iter()
+                    .filter_map(|(target, v)| v.as_str().map(|s| (target.clone(), secrets::reveal(s))))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        description: value
+            .get("description")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string()),
+        created_at: value.get("created_at").and_then(|v| v.as_i64()).unwrap_or(0),
+        updated_at: value.get("updated_at").and_then(|v| v.as_i64()).unwrap_or(0),
+    }
+}
+
+/// Convert McpEnvVar to database payload; values of secret variables are stored as
+/// vault references
+pub fn to_mcp_env_var_payload(var: &McpEnvVar) -> Value {
+    let store = |value: &str| {
+        if var.secret {
+            secrets::seal(value)
+        } else {
+            value.to_string()
+        }
+    };
+    let overrides: serde_json::Map<String, Value> = var
+        .overrides
+        .iter()
+        .map(|(target, value)| (target.clone(), Value::String(store(value))))
+        .collect();
+    serde_json::json!({
+        "name": var.name,
+        "value": store(&var.value),
+        "secret": var.secret,
+        "overrides": overrides,
+        "description": var.description,
+        "created_at": var.created_at,
+        "updated_at": var.updated_at,
+    })
+}
+
+/// Convert database record to McpProject struct
+pub fn from_db_mcp_project(value: Value) -> McpProject {
+    let string_list = |key: &str| -> Vec<String> {
+        value
+            .get(key)
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|item| item.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let sync_entries: Vec<McpProjectSyncEntry> = value
+        .get("sync_entries")
+        .cloned()
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+
+    McpProject {
+        id: db_extract_id(&value),
+        name: value
+            .get("name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+        path: value
+            .get("path")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+        tools: string_list("tools"),
+        server_ids: string_list("server_ids"),
+        sync_entries,
+        created_at: value.get("created_at").and_then(|v| v.as_i64()).unwrap_or(0),
+        updated_at: value.get("updated_at").and_then(|v| v.as_i64()).unwrap_or(0),
+    }
+}
+
+/// Convert McpProject to clean database payload (without id)
+pub fn to_mcp_project_payload(project: &McpProject) -> Value {
+    serde_json::json!({
+        "name": project.name,
+        "path": project.path,
+        "tools": project.tools,
+        "server_ids": project.server_ids,
+        "sync_entries": project.sync_entries,
+        "created_at": project.created_at,
+        "updated_at": project.updated_at,
+    })
+}
+
+/// Convert McpProject to DTO format
+pub fn to_mcp_project_dto(project: McpProject) -> McpProjectDto {
+    McpProjectDto {
+        id: project.id,
+        name: project.name,
+        path: project.path,
+        tools: project.tools,
+        server_ids: project.server_ids,
+        sync_entries: project.sync_entries,
+        created_at: project.created_at,
+        updated_at: project.updated_at,
+    }
+}
@@ -0,0 +1,676 @@
+//! MCP JSON-RPC client
+//!
+//! Speaks to a configured MCP server over its transport: stdio servers are launched
+//! from `server_config.command/args/env`, `http` servers use the Streamable HTTP
+//! transport and `sse` servers the legacy HTTP+SSE transport. Used by the health
+//! check and the gateway.
+
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, CONTENT_TYPE};
+use reqwest::{Client, Response, Url};
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+
+use super::command_normalize;
+use super::env_vars::EnvResolver;
+use super::types::{HttpConfig, McpServer, McpServerType, StdioConfig};
+use crate::db::DbState;
+use crate::http_client;
+
+/// Protocol version offered in `initialize`
+pub(super) const PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// Timeout of the HTTP clients used for http/sse servers
+const HTTP_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Number of stderr lines kept from a stdio server
+const STDERR_TAIL_LINES: usize = 20;
+
+/// Safety limit when following `nextCursor` of list results
+const MAX_LIST_PAGES: usize = 50;
+
+enum Transport {
+    Stdio(StdioTransport),
+    Http(HttpTransport),
+    Sse(SseTransport),
+}
+
+/// JSON-RPC client of one MCP server
+pub(super) struct McpClient {
+    transport: Transport,
+    next_id: u64,
+}
+
+impl McpClient {
+    /// Launch or connect to a configured server, resolving its `${NAME}` placeholders
+    pub(super) async fn connect(state: &DbState, server: &McpServer) -> Result<Self, String> {
+        let server = &EnvResolver::load(state, None).await?.resolve_server(server)?;
+        let transport = match McpServerType::from_str(&server.server_type) {
+            McpServerType::Stdio => return Self::spawn_stdio(&server.server_config),
+            McpServerType::Http => {
+                let (url, headers) = http_target(&server.server_config)?;
+                let client = http_client_for(state, &url).await?;
+                Transport::Http(HttpTransport {
+                    client,
+                    url,
+                    headers,
+                    session_id: None,
+                    protocol_version: None,
+                })
+            }
+            McpServerType::Sse => {
+                let (url, headers) = http_target(&server.server_config)?;
+                let client = http_client_for(state, &url).await?;
+                Transport::Sse(SseTransport::connect(client, url, headers).await?)
+            }
+        };
+        Ok(Self { transport, next_id: 0 })
+    }
+
+    /// Launch a stdio server from its config
+    pub(super) fn spawn_stdio(server_config: &Value) -> Result<Self, String> {
+        Ok(Self {
+            transport: Transport::Stdio(StdioTransport::spawn(server_config)?),
+            next_id: 0,
+        })
+    }
+
+    /// Perform the `initialize` request and `notifications/initialized`; returns the
+    /// server's initialize result (server info, protocol version and capabilities)
+    pub(super) async fn initialize(&mut self) -> Result<Value, String> {
+        let init = self
+            .request(
+                "initialize",
+                json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": {
+                        "name": "ai-toolbox",
+                        "version": env!("CARGO_PKG_VERSION"),
+                    },
+                }),
+            )
+            .await?;
+
+        if let Transport::Http(http) = &mut self.transport {
+            http.protocol_version = init
+                .get("protocolVersion")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+        }
+        self.notify("notifications/initialized").await?;
+        Ok(init)
+    }
+
+    /// Collect the items of a paginated list method
+    pub(super) async fn list_all(&mut self, method: &str, key: &str) -> Result<Vec<Value>, String> {
+        let mut items = Vec::new();
+        let mut cursor: Option<String> = None;
+        for _ in 0..MAX_LIST_PAGES {
+            let params = match &cursor {
+                Some(cursor) => json!({ "cursor": cursor }),
+                None => json!({}),
+            };
+            let result = self.request(method, params).await?;
+            if let Some(page) = result.get(key).and_then(|v| v.as_array()) {
+                items.extend(page.iter().cloned());
+            }
+            cursor = result.get("nextCursor").and_then(|v| v.as_str()).map(String::from);
+            if cursor.is_none() {
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    /// Send a request and return its `result`; JSON-RPC errors become Err
+    pub(super) async fn request(&mut self, method: &str, params: Value) -> Result<Value, String> {
+        let response = self.request_raw(method, params).await?;
+        rpc_result(response, method)
+    }
+
+    /// Send a request and return the whole JSON-RPC response (with `result` or `error`);
+    /// Err only when the transport fails
+    pub(super) async fn request_raw(&mut self, method: &str, params: Value) -> Result<Value, String> {
+        self.next_id += 1;
+        let id = self.next_id;
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+
+        match &mut self.transport {
+            Transport::Stdio(t) => t.request(&message, id).await,
+            Transport::Http(t) => t.request(&message, id).await,
+            Transport::Sse(t) => t.request(&message, id).await,
+        }
+    }
+
+    async fn notify(&mut self, method: &str) -> Result<(), String> {
+        let message = json!({ "jsonrpc": "2.0", "method": method });
+        match &mut self.transport {
+            Transport::Stdio(t) => t.send(&message),
+            Transport::Http(t) => t.post(&message).await.map(|_| ()),
+            Transport::Sse(t) => t.send(&message).await,
+        }
+    }
+
+    /// Last stderr lines of a stdio server
+    pub(super) fn stderr_tail(&self) -> Option<String> {
+        match &self.transport {
+            Transport::Stdio(t) => t.stderr_tail(),
+            _ => None,
+        }
+    }
+
+    /// End the session of an HTTP server; stdio servers are stopped on drop
+    pub(super) async fn close(&mut self) {
+        if let Transport::Http(http) = &mut self.transport {
+            http.terminate_session().await;
+        }
+    }
+}
+
+/// Extract the `result` of a JSON-RPC response, turning a JSON-RPC error into Err
+fn rpc_result(response: Value, method: &str) -> Result<Value, String> {
+    if let Some(error) = response.get("error") {
+        let message = error
+            .get("message")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown error");
+        let code = error.get("code").and_then(|v| v.as_i64()).unwrap_or(0);
+        return Err(format!("{} failed ({}): {}", method, code, message));
+    }
+    response
+        .get("result")
+        .cloned()
+        .ok_or_else(|| format!("{} returned no result", method))
+}
+
+/// Whether an incoming message is the response to the request with `id`
+fn is_response_to(message: &Value, id: u64) -> bool {
+    message.get("method").is_none()
+        && message.get("id").and_then(|v| v.as_u64()) == Some(id)
+        && (message.get("result").is_some() || message.get("error").is_some())
+}
+
+/// Reply to a request sent by the server (e.g. `ping`); None for anything else
+fn reply_to_server_request(message: &Value) -> Option<Value> {
+    let method = message.get("method")?.as_str()?;
+    let id = message.get("id")?.clone();
+    Some(if method == "ping" {
+        json!({ "jsonrpc": "2.0", "id": id, "result": {} })
+    } else {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": -32601, "message": format!("Method not supported: {}", method) },
+        })
+    })
+}
+
+// ==================== stdio Transport ====================
+
+struct StdioTransport {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stdout: mpsc::UnboundedReceiver<String>,
+    stderr: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl StdioTransport {
+    fn spawn(server_config: &Value) -> Result<Self, String> {
+        // Windows needs cmd /c for npx/npm/... which are .cmd batch files
+        let config: StdioConfig = serde_json::from_value(command_normalize::wrap_cmd_c(server_config))
+            .map_err(|e| format!("Invalid stdio server config: {}", e))?;
+        if config.command.trim().is_empty() {
+            return Err("MCP server command is empty".to_string());
+        }
+
+        let mut command = Command::new(&config.command);
+        command
+            .args(&config.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if let Some(env) = config.env.as_ref().and_then(|v| v.as_object()) {
+            for (key, value) in env {
+                match value {
+                    Value::String(s) => command.env(key, s),
+                    Value::Null => continue,
+                    other => command.env(key, other.to_string()),
+                };
+            }
+        }
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            const CREATE_NO_WINDOW: u32 = 0x08000000;
+            command.creation_flags(CREATE_NO_WINDOW);
+        }
+
+        let mut child = command
+            .spawn()
+            .map_err(|e| format!("Failed to start MCP server '{}': {}", config.command, e))?;
+        let stdin = child.stdin.take();
+        let stdout = child.stdout.take().ok_or("Failed to capture MCP server stdout")?;
+        let stderr = child.stderr.take().ok_or("Failed to capture MCP server stderr")?;
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let tail = Arc::new(Mutex::new(VecDeque::new()));
+        let tail_writer = tail.clone();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stderr).lines() {
+                let Ok(line) = line else { break };
+                let Ok(mut tail) = tail_writer.lock() else { break };
+                if tail.len() == STDERR_TAIL_LINES {
+                    tail.pop_front();
+                }
+                tail.push_back(line);
+            }
+        });
+
+        Ok(Self {
+            child,
+            stdin,
+            stdout: rx,
+            stderr: tail,
+        })
+    }
+
+    fn send(&mut self, message: &Value) -> Result<(), String> {
+        let stdin = self.stdin.as_mut().ok_or("MCP server stdin is closed")?;
+        writeln!(stdin, "{}", message)
+            .and_then(|_| stdin.flush())
+            .map_err(|e| format!("Failed to write to MCP server: {}", e))
+    }
+
+    async fn request(&mut self, message: &Value, id: u64) -> Result<Value, String> {
+        self.send(message)?;
+        loop {
+            let Some(line) = self.stdout.recv().await else {
+                return Err(self.exit_message());
+            };
+            // Some servers log plain text to stdout; skip anything that is not JSON
+            let Ok(incoming) = serde_json::from_str::<Value>(&line) else {
+                continue;
+            };
+            if let Some(reply) = reply_to_server_request(&incoming) {
+                self.send(&reply)?;
+            } else if is_response_to(&incoming, id) {
+                return Ok(incoming);
+            }
+        }
+    }
+
+    fn exit_message(&mut self) -> String {
+        match self.child.try_wait() {
+            Ok(Some(status)) => format!("MCP server exited ({})", status),
+            _ => "MCP server closed stdout".to_string(),
+        }
+    }
+
+    fn stderr_tail(&self) -> Option<String> {
+        let tail = self.stderr.lock().ok()?;
+        if tail.is_empty() {
+            return None;
+        }
+        Some(tail.iter().cloned().collect::<Vec<_>>().join("\n"))
+    }
+}
+
+impl Drop for StdioTransport {
+    fn drop(&mut self) {
+        // Closing stdin lets well-behaved servers exit on their own
+        drop(self.stdin.take());
+
+        // Kill the whole tree: cmd /c and npx leave the actual server as a child process
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            const CREATE_NO_WINDOW: u32 = 0x08000000;
+            let _ = Command::new("taskkill")
+                .args(["/PID", &self.child.id().to_string(), "/T", "/F"])
+                .creation_flags(CREATE_NO_WINDOW)
+                .output();
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+// ==================== HTTP Transports ====================
+
+/// URL and headers of an http/sse server config
+fn http_target(server_config: &Value) -> Result<(Url, HeaderMap), String> {
+    let config: HttpConfig = serde_json::from_value(server_config.clone())
+        .map_err(|e| format!("Invalid HTTP server config: {}", e))?;
+    let url = Url::parse(config.url.trim()).map_err(|e| format!("Invalid MCP server URL: {}", e))?;
+
+    let mut headers = HeaderMap::new();
+    if let Some(map) = config.headers.as_ref().and_then(|v| v.as_object()) {
+        for (name, value) in map {
+            let Some(value) = value.as_str() else {
+                continue;
+            };
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| format!("Invalid header name '{}': {}", name, e))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|e| format!("Invalid value for header '{}': {}", name, e))?;
+            headers.insert(name, value);
+        }
+    }
+    Ok((url, headers))
+}
+
+/// HTTP client for a server URL; local servers bypass the configured proxy
+async fn http_client_for(state: &DbState, url: &Url) -> Result<Client, String> {
+    let timeout_secs = HTTP_TIMEOUT.as_secs();
+    match url.host_str() {
+        Some("localhost") | Some("127.0.0.1") | Some("[::1]") => {
+            http_client::create_client_no_proxy(timeout_secs)
+        }
+        _ => http_client::client_with_timeout(state, timeout_secs).await,
+    }
+}
+
+/// Turn a non-success HTTP response into an error message
+async fn check_status(response: Response, url: &Url) -> Result<Response, String> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    let body: String = body.trim().chars().take(200).collect();
+    if body.is_empty() {
+        Err(format!("HTTP {} from {}", status, url))
+    } else {
+        Err(format!("HTTP {} from {}: {}", status, url, body))
+    }
+}
+
+/// Streamable HTTP transport: every message is a POST, responses come back as JSON or an SSE stream
+struct HttpTransport {
+    client: Client,
+    url: Url,
+    headers: HeaderMap,
+    session_id: Option<String>,
+    protocol_version: Option<String>,
+}
+
+impl HttpTransport {
+    async fn post(&mut self, message: &Value) -> Result<Response, String> {
+        let mut request = self
+            .client
+            .post(self.url.clone())
+            .headers(self.headers.clone())
+            .header(ACCEPT, "application/json, text/event-stream")
+            .json(message);
+        if let Some(session_id) = &self.session_id {
+            request = request.header("Mcp-Session-Id", session_id);
+        }
+        if let Some(version) = &self.protocol_version {
+            request = request.header("MCP-Protocol-Version", version);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("Failed to connect to {}: {}", self.url, e))?;
+        let response = check_status(response, &self.url).await?;
+        if let Some(session_id) = response
+            .headers()
+            .get("mcp-session-id")
+            .and_then(|v| v.to_str().ok())
+        {
+            self.session_id = Some(session_id.to_string());
+        }
+        Ok(response)
+    }
+
+    async fn request(&mut self, message: &Value, id: u64) -> Result<Value, String> {
+        let response = self.post(message).await?;
+        let is_stream = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("text/event-stream"));
+
+        if !is_stream {
+            let body = response
+                .text()
+                .await
+                .map_err(|e| format!("Failed to read response from {}: {}", self.url, e))?;
+            let incoming: Value = serde_json::from_str(&body)
+                .map_err(|e| format!("Invalid JSON-RPC response from {}: {}", self.url, e))?;
+            let messages = match incoming {
+                Value::Array(batch) => batch,
+                single => vec![single],
+            };
+            return messages
+                .into_iter()
+                .find(|m| is_response_to(m, id))
+                .ok_or_else(|| format!("No response to request {} from {}", id, self.url));
+        }
+
+        let mut events = SseReader::new(response);
+        while let Some(event) = events.next_event().await? {
+            let Ok(incoming) = serde_json::from_str::<Value>(&event.data) else {
+                continue;
+            };
+            if let Some(reply) = reply_to_server_request(&incoming) {
+                self.post(&reply).await?;
+            } else if is_response_to(&incoming, id) {
+                return Ok(incoming);
+            }
+        }
+        Err(format!("Response stream from {} ended without a result", self.url))
+    }
+
+    /// Explicitly end the session so the server can release it
+    async fn terminate_session(&mut self) {
+        let Some(session_id) = self.session_id.take() else {
+            return;
+        };
+        let _ = self
+            .client
+            .delete(self.url.clone())
+            .headers(self.headers.clone())
+            .header("Mcp-Session-Id", session_id)
+            .send()
+            .await;
+    }
+}
+
+/// Legacy HTTP+SSE transport: responses arrive on a GET event stream, messages are
+/// POSTed to the endpoint announced by its first `endpoint` event
+struct SseTransport {
+    client: Client,
+    headers: HeaderMap,
+    endpoint: Url,
+    events: SseReader,
+}
+
+impl SseTransport {
+    async fn connect(client: Client, url: Url, headers: HeaderMap) -> Result<Self, String> {
+        let response = client
+            .get(url.clone())
+            .headers(headers.clone())
+            .header(ACCEPT, "text/event-stream")
+            .send()
+            .await
+            .map_err(|e| format!("Failed to connect to {}: {}", url, e))?;
+        let mut events = SseReader::new(check_status(response, &url).await?);
+
+        loop {
+            let event = events
+                .next_event()
+                .await?
+                .ok_or_else(|| format!("SSE stream from {} closed before the endpoint event", url))?;
+            if event.event == "endpoint" {
+                let endpoint = url
+                    .join(event.data.trim())
+                    .map_err(|e| format!("Invalid SSE endpoint '{}': {}", event.data, e))?;
+                return Ok(Self {
+                    client,
+                    headers,
+                    endpoint,
+                    events,
+                });
+            }
+        }
+    }
+
+    async fn send(&mut self, message: &Value) -> Result<(), String> {
+        let response = self
+            .client
+            .post(self.endpoint.clone())
+            .headers(self.headers.clone())
+            .json(message)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to post to {}: {}", self.endpoint, e))?;
+        check_status(response, &self.endpoint).await.map(|_| ())
+    }
+
+    async fn request(&mut self, message: &Value, id: u64) -> Result<Value, String> {
+        self.send(message).await?;
+        loop {
+            let event = self
+                .events
+                .next_event()
+                .await?
+                .ok_or("SSE stream closed before the response arrived")?;
+            if event.event != "message" {
+                continue;
+            }
+            let Ok(incoming) = serde_json::from_str::<Value>(&event.data) else {
+                continue;
+            };
+            if let Some(reply) = reply_to_server_request(&incoming) {
+                self.send(&reply).await?;
+            } else if is_response_to(&incoming, id) {
+                return Ok(incoming);
+            }
+        }
+    }
+}
+
+// ==================== Server-Sent Events ====================
+
+#[derive(Debug, PartialEq)]
+struct SseEvent {
+    event: String,
+    data: String,
+}
+
+/// Incremental reader of an SSE response body
+struct SseReader {
+    response: Response,
+    buffer: Vec<u8>,
+}
+
+impl SseReader {
+    fn new(response: Response) -> Self {
+        Self {
+            response,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Next event with data, None once the stream has ended
+    async fn next_event(&mut self) -> Result<Option<SseEvent>, String> {
+        loop {
+            while let Some(block) = take_event_block(&mut self.buffer) {
+                if let Some(event) = parse_event_block(&block) {
+                    return Ok(Some(event));
+                }
+            }
+            match self.response.chunk().await {
+                // JSON payloads never contain a raw \r, so dropping it normalizes \r\n line ends
+                Ok(Some(chunk)) => self.buffer.extend(chunk.iter().filter(|b| **b != b'\r')),
+                Ok(None) => return Ok(None),
+                Err(e) => return Err(format!("Failed to read SSE stream: {}", e)),
+            }
+        }
+    }
+}
+
+/// Remove the first complete event block (terminated by a blank line) from the buffer
+fn take_event_block(buffer: &mut Vec<u8>) -> Option<String> {
+    let end = buffer.windows(2).position(|w| w == b"\n\n")?;
+    let block: Vec<u8> = buffer.drain(..end + 2).collect();
+    Some(String::from_utf8_lossy(&block[..end]).to_string())
+}
+
+/// Parse an event block; comments and blocks without data yield None
+fn parse_event_block(block: &str) -> Option<SseEvent> {
+    let mut event = String::from("message");
+    let mut data: Vec<&str> = Vec::new();
+    for line in block.lines() {
+        if line.starts_with(':') {
+            continue;
+        }
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => event = value.to_string(),
+            "data" => data.push(value),
+            _ => {}
+        }
+    }
+    if data.is_empty() {
+        return None;
+    }
+    Some(SseEvent {
+        event,
+        data: data.join("\n"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sse_event_parsing() {
+        let mut buffer = b": keep-alive\n\nevent: endpoint\ndata: /messages?session=1\n\ndata: {\"id\":1,\ndata: \"result\":{}}\n\nevent: mes".to_vec();
+
+        let events: Vec<SseEvent> = std::iter::from_fn(|| take_event_block(&mut buffer))
+            .filter_map(|block| parse_event_block(&block))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                SseEvent { event: "endpoint".to_string(), data: "/messages?session=1".to_string() },
+                SseEvent { event: "message".to_string(), data: "{\"id\":1,\n\"result\":{}}".to_string() },
+            ]
+        );
+        assert_eq!(buffer, b"event: mes");
+    }
+
+    #[test]
+    fn test_message_matching() {
+        assert!(is_response_to(&json!({"jsonrpc": "2.0", "id": 2, "result": {}}), 2));
+        assert!(!is_response_to(&json!({"jsonrpc": "2.0", "id": 1, "result": {}}), 2));
+        assert!(!is_response_to(&json!({"jsonrpc": "2.0", "id": 2, "method": "ping"}), 2));
+
+        let reply = reply_to_server_request(&json!({"jsonrpc": "2.0", "id": 7, "method": "ping"})).unwrap();
+        assert_eq!(reply["result"], json!({}));
+        assert!(reply_to_server_request(&json!({"jsonrpc": "2.0", "method": "notifications/progress"})).is_none());
+
+        let err = rpc_result(json!({"id": 1, "error": {"code": -32601, "message": "nope"}}), "tools/list");
+        assert_eq!(err.unwrap_err(), "tools/list failed (-32601): nope");
+    }
+}
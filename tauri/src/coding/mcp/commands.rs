@@ -4,16 +4,20 @@
 
 use tauri::{AppHandle, Emitter, Runtime, State};
 
-use super::adapter::parse_sync_details_dto;
+use super::adapter::{parse_sync_details_dto, to_mcp_project_dto};
 use super::config_sync::{import_servers_from_tool, remove_server_from_tool, sync_server_to_tool};
+use super::env_vars::{self, EnvResolver};
+use super::health_check::check_server;
 use super::mcp_store;
+use super::project_sync::{project_target_by_key, remove_project_entries, sync_project, PROJECT_MCP_TARGETS};
 use super::types::{
-    CreateMcpServerInput, McpDiscoveredServerDto, McpImportResultDto, McpScanResultDto, McpServer, McpServerDto,
-    McpSyncDetail, McpSyncResultDto, UpdateMcpServerInput, FavoriteMcp, FavoriteMcpDto, FavoriteMcpInput, now_ms,
+    CreateMcpServerInput, McpDiscoveredServerDto, McpEnvVar, McpEnvVarInput, McpHealthCheck, McpImportResultDto, McpProject, McpProjectDto, McpProjectInput,
+    McpProjectTargetDto, McpScanResultDto, McpServer, McpServerDto, McpSyncDetail, McpSyncResultDto,
+    UpdateMcpServerInput, FavoriteMcp, FavoriteMcpDto, FavoriteMcpInput, now_ms,
 };
 use crate::coding::tools::{
-    custom_store, get_mcp_runtime_tools, runtime_tool_by_key, RuntimeToolDto, is_tool_installed,
-    to_runtime_tool_dto, resolve_mcp_config_path,
+    builtin_tool_by_key, custom_store, get_mcp_runtime_tools, runtime_tool_by_key, RuntimeToolDto,
+    is_tool_installed, to_runtime_tool_dto, resolve_mcp_config_path,
 };
 use crate::DbState;
 
@@ -36,6 +40,9 @@ pub async fn mcp_list_servers(state: State<'_, DbState>) -> Result<Vec<McpServer
             description: s.description.clone(),
             tags: s.tags.clone(),
             sort_index: s.sort_index,
+            health: s.health.clone(),
+            tool_catalog: s.tool_catalog.clone(),
+            tool_filters: s.tool_filters.clone(),
             created_at: s.created_at,
             updated_at: s.updated_at,
         })
@@ -61,6 +68,9 @@ pub async fn mcp_create_server<R: Runtime>(
         description: input.description,
         tags: input.tags,
         sort_index: 0, // Will be assigned by upsert
+        health: None,
+        tool_catalog: vec![],
+        tool_filters: Default::default(),
         created_at: now,
         updated_at: now,
     };
@@ -69,10 +79,11 @@ pub async fn mcp_create_server<R: Runtime>(
 
     // Sync to all enabled tools
     let custom_tools = custom_store::get_custom_tools(&state).await.unwrap_or_default();
+    let env = EnvResolver::load(&state, None).await?;
     for tool_key in &input.enabled_tools {
         if let Some(tool) = runtime_tool_by_key(tool_key, &custom_tools) {
             if is_tool_installed(&tool) {
-                match sync_server_to_tool(&server, &tool) {
+                match sync_server_to_tool(&server, &tool, &env) {
                     Ok(detail) => {
                         let _ = mcp_store::update_sync_detail(&state, &id, &detail).await;
                     }
@@ -110,6 +121,9 @@ pub async fn mcp_create_server<R: Runtime>(
         description: created.description,
         tags: created.tags,
         sort_index: created.sort_index,
+        health: created.health,
+        tool_catalog: created.tool_catalog,
+        tool_filters: created.tool_filters,
         created_at: created.created_at,
         updated_at: created.updated_at,
     })
@@ -148,16 +162,20 @@ pub async fn mcp_update_server<R: Runtime>(
     if let Some(tags) = input.tags {
         server.tags = tags;
     }
+    if let Some(tool_filters) = input.tool_filters {
+        server.tool_filters = tool_filters;
+    }
     server.updated_at = now_ms();
 
     mcp_store::upsert_mcp_server(&state, &server).await?;
 
     // Re-sync to all enabled tools
     let custom_tools = custom_store::get_custom_tools(&state).await.unwrap_or_default();
+    let env = EnvResolver::load(&state, None).await?;
     for tool_key in &server.enabled_tools {
         if let Some(tool) = runtime_tool_by_key(tool_key, &custom_tools) {
             if is_tool_installed(&tool) {
-                match sync_server_to_tool(&server, &tool) {
+                match sync_server_to_tool(&server, &tool, &env) {
                     Ok(detail) => {
                         let _ = mcp_store::update_sync_detail(&state, &serverId, &detail).await;
                     }
@@ -175,6 +193,9 @@ pub async fn mcp_update_server<R: Runtime>(
         }
     }
 
+    // Re-sync projects that include this server
+    resync_projects_for_server(&state, &serverId).await?;
+
     // Get the updated server with sync details
     let updated = mcp_store::get_mcp_server_by_id(&state, &serverId)
         .await?
@@ -195,11 +216,30 @@ pub async fn mcp_update_server<R: Runtime>(
         description: updated.description,
         tags: updated.tags,
         sort_index: updated.sort_index,
+        health: updated.health,
+        tool_catalog: updated.tool_catalog,
+        tool_filters: updated.tool_filters,
         created_at: updated.created_at,
         updated_at: updated.updated_at,
     })
 }
 
+/// Check an MCP server with a real protocol handshake and store the result on the record
+#[tauri::command]
+#[allow(non_snake_case)]
+pub async fn mcp_check_server(state: State<'_, DbState>, serverId: String) -> Result<McpHealthCheck, String> {
+    let server = mcp_store::get_mcp_server_by_id(&state, &serverId)
+        .await?
+        .ok_or_else(|| format!("MCP server not found: {}", serverId))?;
+
+    let (health, catalog) = check_server(&state, &server).await;
+    mcp_store::update_health(&state, &serverId, &health).await?;
+    if let Some(catalog) = catalog {
+        mcp_store::update_tool_catalog(&state, &serverId, &catalog).await?;
+    }
+    Ok(health)
+}
+
 /// Delete an MCP server
 #[tauri::command]
 #[allow(non_snake_case)]
@@ -220,6 +260,7 @@ pub async fn mcp_delete_server<R: Runtime>(
     }
 
     mcp_store::delete_mcp_server(&state, &serverId).await?;
+    resync_projects_for_server(&state, &serverId).await?;
 
     // Emit mcp-changed for WSL sync
     let _ = app.emit("config-changed", "window");
@@ -246,13 +287,14 @@ pub async fn mcp_toggle_tool<R: Runtime>(
 
     // Get the tool
     let custom_tools = custom_store::get_custom_tools(&state).await.unwrap_or_default();
+    let env = EnvResolver::load(&state, None).await?;
     let tool = runtime_tool_by_key(&toolKey, &custom_tools)
         .ok_or_else(|| format!("Tool not found: {}", toolKey))?;
 
     // Sync or remove based on new state
     if is_enabled {
         // Sync to tool config
-        match sync_server_to_tool(&server, &tool) {
+        match sync_server_to_tool(&server, &tool, &env) {
             Ok(detail) => {
                 mcp_store::update_sync_detail(&state, &serverId, &detail).await?;
             }
@@ -300,6 +342,7 @@ pub async fn mcp_sync_to_tool<R: Runtime>(
     toolKey: String,
 ) -> Result<Vec<McpSyncResultDto>, String> {
     let custom_tools = custom_store::get_custom_tools(&state).await.unwrap_or_default();
+    let env = EnvResolver::load(&state, None).await?;
     let tool = runtime_tool_by_key(&toolKey, &custom_tools)
         .ok_or_else(|| format!("Tool not found: {}", toolKey))?;
 
@@ -315,7 +358,7 @@ pub async fn mcp_sync_to_tool<R: Runtime>(
             continue;
         }
 
-        match sync_server_to_tool(&server, &tool) {
+        match sync_server_to_tool(&server, &tool, &env) {
             Ok(detail) => {
                 mcp_store::update_sync_detail(&state, &server.id, &detail).await?;
                 results.push(McpSyncResultDto {
@@ -355,6 +398,7 @@ pub async fn mcp_sync_all<R: Runtime>(
     state: State<'_, DbState>,
 ) -> Result<Vec<McpSyncResultDto>, String> {
     let custom_tools = custom_store::get_custom_tools(&state).await.unwrap_or_default();
+    let env = EnvResolver::load(&state, None).await?;
     let servers = mcp_store::get_mcp_servers(&state).await?;
     let mut results = Vec::new();
 
@@ -368,7 +412,7 @@ pub async fn mcp_sync_all<R: Runtime>(
                 continue;
             }
 
-            match sync_server_to_tool(&server, &tool) {
+            match sync_server_to_tool(&server, &tool, &env) {
                 Ok(detail) => {
                     mcp_store::update_sync_detail(&state, &server.id, &detail).await?;
                     results.push(McpSyncResultDto {
@@ -413,6 +457,7 @@ pub async fn mcp_import_from_tool(
     enabledTools: Option<Vec<String>>,
 ) -> Result<McpImportResultDto, String> {
     let custom_tools = custom_store::get_custom_tools(&state).await.unwrap_or_default();
+    let env = EnvResolver::load(&state, None).await?;
     let tool = runtime_tool_by_key(&toolKey, &custom_tools)
         .ok_or_else(|| format!("Tool not found: {}", toolKey))?;
 
@@ -483,7 +528,7 @@ pub async fn mcp_import_from_tool(
                 // Sync to each enabled tool
                 for tool_key in &target_tools {
                     if let Some(target_tool) = runtime_tool_by_key(tool_key, &custom_tools) {
-                        match sync_server_to_tool(&server, &target_tool) {
+                        match sync_server_to_tool(&server, &target_tool, &env) {
                             Ok(detail) => {
                                 let _ = mcp_store::update_sync_detail(&state, &server_id, &detail).await;
                             }
@@ -692,8 +737,8 @@ pub async fn mcp_add_custom_tool(
     }
 
     // Validate mcp_format
-    if mcp_format != "json" && mcp_format != "toml" && mcp_format != "jsonc" {
-        return Err("MCP config format must be 'json', 'jsonc' or 'toml'".to_string());
+    if !["json", "jsonc", "toml", "yaml"].contains(&mcp_format.as_str()) {
+        return Err("MCP config format must be 'json', 'jsonc', 'toml' or 'yaml'".to_string());
     }
 
     // Check for duplicate with built-in tools
@@ -876,3 +921,249 @@ pub async fn mcp_init_default_favorites(state: State<'_, DbState>) -> Result<usi
 
     Ok(presets.len())
 }
+
+// ==================== Variables ====================
+
+/// List the variables available as `${NAME}` placeholders in server configs
+#[tauri::command]
+pub async fn mcp_list_env_vars(state: State<'_, DbState>) -> Result<Vec<McpEnvVar>, String> {
+    mcp_store::get_mcp_env_vars(&state).await
+}
+
+/// Create or update a variable (upsert by name), then re-sync the servers using it
+#[tauri::command]
+pub async fn mcp_upsert_env_var<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, DbState>,
+    input: McpEnvVarInput,
+) -> Result<McpEnvVar, String> {
+    let name = input.name.trim().to_string();
+    if !env_vars::is_valid_name(&name) {
+        return Err(format!("Invalid variable name: {}", input.name));
+    }
+
+    let now = now_ms();
+    let existing = mcp_store::get_mcp_env_var_by_name(&state, &name).await?;
+    let mut var = McpEnvVar {
+        id: existing.as_ref().map(|v| v.id.clone()).unwrap_or_default(),
+        name,
+        value: input.value,
+        secret: input.secret,
+        overrides: input.overrides,
+        description: input.description,
+        created_at: existing.map(|v| v.created_at).unwrap_or(now),
+        updated_at: now,
+    };
+    var.id = mcp_store::upsert_mcp_env_var(&state, &var).await?;
+
+    resync_servers_using_var(&state, &var.name).await?;
+    let _ = app.emit("config-changed", "window");
+    let _ = app.emit("mcp-changed", "window");
+    Ok(var)
+}
+
+/// Delete a variable; servers using it get the placeholder written as-is
+#[tauri::command]
+#[allow(non_snake_case)]
+pub async fn mcp_delete_env_var<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, DbState>,
+    varId: String,
+) -> Result<(), String> {
+    let name = mcp_store::get_mcp_env_vars(&state)
+        .await?
+        .into_iter()
+        .find(|v| v.id == varId)
+        .map(|v| v.name);
+    mcp_store::delete_mcp_env_var(&state, &varId).await?;
+
+    if let Some(name) = name {
+        resync_servers_using_var(&state, &name).await?;
+        let _ = app.emit("config-changed", "window");
+        let _ = app.emit("mcp-changed", "window");
+    }
+    Ok(())
+}
+
+/// Re-sync the servers whose config references a variable to their tools and projects
+async fn resync_servers_using_var(state: &DbState, name: &str) -> Result<(), String> {
+    let custom_tools = custom_store::get_custom_tools(state).await.unwrap_or_default();
+    let env = EnvResolver::load(state, None).await?;
+    let servers = mcp_store::get_mcp_servers(state).await?;
+
+    for server in servers.iter().filter(|s| env_vars::references(s, name)) {
+        for tool_key in &server.enabled_tools {
+            let Some(tool) = runtime_tool_by_key(tool_key, &custom_tools) else {
+                continue;
+            };
+            if !is_tool_installed(&tool) {
+                continue;
+            }
+            let detail = sync_server_to_tool(server, &tool, &env).unwrap_or_else(|e| McpSyncDetail {
+                tool: tool_key.clone(),
+                status: "error".to_string(),
+                synced_at: Some(now_ms()),
+                error_message: Some(e),
+            });
+            mcp_store::update_sync_detail(state, &server.id, &detail).await?;
+        }
+        resync_projects_for_server(state, &server.id).await?;
+    }
+    Ok(())
+}
+
+// ==================== Project Sync ====================
+
+/// List tools that support a project-level MCP config file
+#[tauri::command]
+pub async fn mcp_list_project_targets() -> Result<Vec<McpProjectTargetDto>, String> {
+    Ok(PROJECT_MCP_TARGETS
+        .iter()
+        .map(|t| McpProjectTargetDto {
+            tool_key: t.tool_key.to_string(),
+            display_name: builtin_tool_by_key(t.tool_key)
+                .map(|b| b.display_name.to_string())
+                .unwrap_or_else(|| t.tool_key.to_string()),
+            relative_path: t.relative_path.to_string(),
+        })
+        .collect())
+}
+
+/// List all MCP projects
+#[tauri::command]
+pub async fn mcp_list_projects(state: State<'_, DbState>) -> Result<Vec<McpProjectDto>, String> {
+    let projects = mcp_store::get_mcp_projects(&state).await?;
+    Ok(projects.into_iter().map(to_mcp_project_dto).collect())
+}
+
+/// Create an MCP project and sync its servers into the project-level config files
+#[tauri::command]
+pub async fn mcp_create_project(
+    state: State<'_, DbState>,
+    input: McpProjectInput,
+) -> Result<McpProjectDto, String> {
+    let path = normalize_project_path(&input.path)?;
+    validate_project_tools(&input.tools)?;
+
+    let now = now_ms();
+    let mut project = McpProject {
+        id: String::new(), // Will be assigned by upsert
+        name: input.name,
+        path,
+        tools: input.tools,
+        server_ids: input.server_ids,
+        sync_entries: Vec::new(),
+        created_at: now,
+        updated_at: now,
+    };
+    project.id = mcp_store::upsert_mcp_project(&state, &project).await?;
+
+    let project = sync_and_save_project(&state, project).await?;
+    Ok(to_mcp_project_dto(project))
+}
+
+/// Update an MCP project and re-sync it.
+/// Servers or tools that were deselected are removed from the project files.
+#[tauri::command]
+#[allow(non_snake_case)]
+pub async fn mcp_update_project(
+    state: State<'_, DbState>,
+    projectId: String,
+    input: McpProjectInput,
+) -> Result<McpProjectDto, String> {
+    let mut project = mcp_store::get_mcp_project_by_id(&state, &projectId)
+        .await?
+        .ok_or_else(|| format!("MCP project not found: {}", projectId))?;
+
+    let path = normalize_project_path(&input.path)?;
+    validate_project_tools(&input.tools)?;
+
+    if path != project.path {
+        // Moved to another directory: clean up the files in the old one
+        remove_project_entries(&project.path, &project.sync_entries);
+        project.sync_entries.clear();
+    }
+
+    project.name = input.name;
+    project.path = path;
+    project.tools = input.tools;
+    project.server_ids = input.server_ids;
+    project.updated_at = now_ms();
+
+    let project = sync_and_save_project(&state, project).await?;
+    Ok(to_mcp_project_dto(project))
+}
+
+/// Delete an MCP project and remove its servers from the project-level config files
+#[tauri::command]
+#[allow(non_snake_case)]
+pub async fn mcp_delete_project(state: State<'_, DbState>, projectId: String) -> Result<(), String> {
+    if let Some(project) = mcp_store::get_mcp_project_by_id(&state, &projectId).await? {
+        remove_project_entries(&project.path, &project.sync_entries);
+    }
+    mcp_store::delete_mcp_project(&state, &projectId).await
+}
+
+/// Sync an MCP project's servers into its project-level config files
+#[tauri::command]
+#[allow(non_snake_case)]
+pub async fn mcp_sync_project(
+    state: State<'_, DbState>,
+    projectId: String,
+) -> Result<Vec<McpSyncResultDto>, String> {
+    let project = mcp_store::get_mcp_project_by_id(&state, &projectId)
+        .await?
+        .ok_or_else(|| format!("MCP project not found: {}", projectId))?;
+
+    let project = sync_and_save_project(&state, project).await?;
+    Ok(project
+        .sync_entries
+        .into_iter()
+        .map(|entry| McpSyncResultDto {
+            tool: entry.tool,
+            success: entry.status == "ok",
+            error_message: entry.error_message,
+        })
+        .collect())
+}
+
+/// Sync a project and store the resulting sync entries
+async fn sync_and_save_project(state: &DbState, mut project: McpProject) -> Result<McpProject, String> {
+    let servers = mcp_store::get_mcp_servers(state).await?;
+    project.sync_entries = sync_project(&project, &servers);
+    mcp_store::upsert_mcp_project(state, &project).await?;
+    Ok(project)
+}
+
+/// Re-sync projects that include a server after it was updated or deleted
+async fn resync_projects_for_server(state: &DbState, server_id: &str) -> Result<(), String> {
+    let server_exists = mcp_store::get_mcp_server_by_id(state, server_id).await?.is_some();
+    let projects = mcp_store::get_mcp_projects(state).await?;
+    for mut project in projects {
+        if !project.server_ids.iter().any(|id| id == server_id) {
+            continue;
+        }
+        if !server_exists {
+            project.server_ids.retain(|id| id != server_id);
+        }
+        sync_and_save_project(state, project).await?;
+    }
+    Ok(())
+}
+
+fn normalize_project_path(path: &str) -> Result<String, String> {
+    let expanded = crate::coding::expand_local_path(path.trim())?;
+    let dir = std::path::Path::new(&expanded);
+    if !dir.is_dir() {
+        return Err(format!("Project directory does not exist: {}", expanded));
+    }
+    let canonical = std::fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+    Ok(canonical.to_string_lossy().to_string())
+}
+
+fn validate_project_tools(tools: &[String]) -> Result<(), String> {
+    match tools.iter().find(|key| project_target_by_key(key).is_none()) {
+        Some(key) => Err(format!("Tool {} does not support project-level MCP config", key)),
+        None => Ok(()),
+    }
+}
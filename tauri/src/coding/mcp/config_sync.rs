@@ -1,7 +1,7 @@
 //! MCP Configuration File Synchronization
 //!
 //! Handles reading/writing MCP server configurations to various tool config files.
-//! Supports JSON/JSONC (unified with json5) and TOML formats.
+//! Supports JSON/JSONC (unified with json5), TOML and YAML formats.
 //! Also handles format conversion for tools like OpenCode that use different schemas.
 
 use std::path::PathBuf;
@@ -9,35 +9,70 @@ use std::path::PathBuf;
 use serde_json::Value;
 
 use super::command_normalize;
+use super::env_vars::{has_placeholders, EnvResolver};
 use super::format_configs::get_format_config;
+use super::gateway;
 use super::types::{McpServer, McpSyncDetail, now_ms};
-use crate::coding::tools::{resolve_mcp_config_path, McpFormatConfig, RuntimeTool};
-
-/// Sync an MCP server to a specific tool's config file
+use super::yaml_config;
+use crate::coding::claude_code::commands::get_claude_config_path;
+use crate::coding::config_writer::write_config_file;
+use crate::coding::tools::{builtin_tool_by_key, resolve_mcp_config_path, McpFormatConfig, RuntimeTool};
+
+/// Per-server tool filter fields of clients that support them natively:
+/// (tool key, allow-list field, deny-list field)
+const NATIVE_TOOL_FILTER_FIELDS: &[(&str, &str, &str)] = &[
+    ("codex", "enabled_tools", "disabled_tools"),
+    ("gemini_cli", "includeTools", "excludeTools"),
+];
+
+/// Sync an MCP server to a specific tool's config file, resolving `${NAME}` placeholders
+/// with `env`
 pub fn sync_server_to_tool(
     server: &McpServer,
     tool: &RuntimeTool,
+    env: &EnvResolver,
 ) -> Result<McpSyncDetail, String> {
     let config_path = resolve_mcp_config_path(tool)
         .ok_or_else(|| format!("Tool {} does not support MCP", tool.key))?;
 
     let format = tool.mcp_config_format.as_deref().unwrap_or("json");
     let field = tool.mcp_field.as_deref().unwrap_or("mcpServers");
-    let format_config = get_format_config(&tool.key);
 
-    match format {
-        // json5 handles both standard JSON and JSONC (with comments, trailing commas)
-        "json" | "jsonc" => sync_server_to_json(&config_path, server, field, format_config),
-        "toml" => sync_server_to_toml(&config_path, server, field),
-        _ => Err(format!("Unsupported config format: {}", format)),
+    // While the gateway is enabled, the client only gets the gateway entry; servers and
+    // tool filters are resolved by the gateway per client
+    if let Some(entry) = gateway::client_entry(&tool.key)? {
+        sync_server_to_file(&entry, &tool.key, &config_path, format, field)?;
+        if server.name != gateway::GATEWAY_ENTRY_NAME {
+            remove_server_from_file(&server.name, &config_path, format, field)?;
+            if tool.key == "claude_code" {
+                sync_claude_tool_permissions(&server.name, &[])?;
+            }
+        }
+        return Ok(McpSyncDetail {
+            tool: tool.key.clone(),
+            status: "ok".to_string(),
+            synced_at: Some(now_ms()),
+            error_message: None,
+        });
     }
-    .map(|_| McpSyncDetail {
+
+    let server = &env.resolve_server(server)?;
+    sync_server_to_file(server, &tool.key, &config_path, format, field)?;
+
+    if tool.key == "claude_code" {
+        let hidden_tools = server
+            .tool_filter(&tool.key)
+            .map(|filter| filter.hidden_tools(&server.tool_catalog))
+            .unwrap_or_default();
+        sync_claude_tool_permissions(&server.name, &hidden_tools)?;
+    }
+
+    Ok(McpSyncDetail {
         tool: tool.key.clone(),
         status: "ok".to_string(),
         synced_at: Some(now_ms()),
         error_message: None,
     })
-    .map_err(|e| e.to_string())
 }
 
 /// Remove an MCP server from a specific tool's config file
@@ -51,10 +86,115 @@ pub fn remove_server_from_tool(
     let format = tool.mcp_config_format.as_deref().unwrap_or("json");
     let field = tool.mcp_field.as_deref().unwrap_or("mcpServers");
 
+    remove_server_from_file(server_name, &config_path, format, field)?;
+
+    if tool.key == "claude_code" {
+        sync_claude_tool_permissions(server_name, &[])?;
+    }
+    Ok(())
+}
+
+/// Sync an MCP server to an explicit config file, using the tool's format conversion.
+/// Used for both the global tool configs and project-level config files.
+pub fn sync_server_to_file(
+    server: &McpServer,
+    tool_key: &str,
+    config_path: &PathBuf,
+    format: &str,
+    field: &str,
+) -> Result<(), String> {
+    let format_config = get_format_config(tool_key);
+
+    match format {
+        // json5 handles both standard JSON and JSONC (with comments, trailing commas)
+        "json" | "jsonc" => sync_server_to_json(config_path, server, tool_key, field, format_config),
+        "toml" => sync_server_to_toml(config_path, server, tool_key, field),
+        "yaml" => yaml_config::sync_server_to_yaml(config_path, server, tool_key, field, format_config),
+        _ => Err(format!("Unsupported config format: {}", format)),
+    }
+}
+
+/// Remove an MCP server from an explicit config file
+pub fn remove_server_from_file(
+    server_name: &str,
+    config_path: &PathBuf,
+    format: &str,
+    field: &str,
+) -> Result<(), String> {
     match format {
         // json5 handles both standard JSON and JSONC (with comments, trailing commas)
-        "json" | "jsonc" => remove_server_from_json(&config_path, server_name, field),
-        "toml" => remove_server_from_toml(&config_path, server_name, field),
+        "json" | "jsonc" => remove_server_from_json(config_path, server_name, field),
+        "toml" => remove_server_from_toml(config_path, server_name, field),
+        "yaml" => yaml_config::remove_server_from_yaml(config_path, server_name, field),
+        _ => Err(format!("Unsupported config format: {}", format)),
+    }
+}
+
+/// Re-render the entries of servers with `${NAME}` placeholders in a tool's MCP config
+/// content copied to another machine, so they get that target's variable values.
+/// Only entries already present in the content are rewritten.
+pub fn apply_target_env_to_content(
+    content: &str,
+    tool_key: &str,
+    servers: &[McpServer],
+    env: &EnvResolver,
+) -> Result<String, String> {
+    let Some(tool) = builtin_tool_by_key(tool_key) else {
+        return Ok(content.to_string());
+    };
+    let format = tool.mcp_config_format.unwrap_or("json");
+    let field = tool.mcp_field.unwrap_or("mcpServers");
+
+    let servers: Vec<McpServer> = servers
+        .iter()
+        .filter(|s| s.enabled_tools.iter().any(|t| t == tool_key) && has_placeholders(s))
+        .map(|s| env.resolve_server(s))
+        .collect::<Result<_, _>>()?;
+    if servers.is_empty() || content.trim().is_empty() {
+        return Ok(content.to_string());
+    }
+
+    match format {
+        "json" | "jsonc" => {
+            let mut config: Value = json5::from_str(content)
+                .map_err(|e| format!("Failed to parse config file: {}", e))?;
+            let Some(entries) = config.get_mut(field).and_then(|v| v.as_object_mut()) else {
+                return Ok(content.to_string());
+            };
+            for server in &servers {
+                if entries.contains_key(&server.name) {
+                    let server_config = build_json_server_config(server, tool_key, get_format_config(tool_key))?;
+                    entries.insert(server.name.clone(), server_config);
+                }
+            }
+            serde_json::to_string_pretty(&config).map_err(|e| format!("Failed to serialize config: {}", e))
+        }
+        "toml" => {
+            let mut doc = content
+                .parse::<toml_edit::DocumentMut>()
+                .map_err(|e| format!("Failed to parse TOML config: {}", e))?;
+            let Some(entries) = doc.get_mut(field).and_then(|s| s.as_table_mut()) else {
+                return Ok(content.to_string());
+            };
+            for server in &servers {
+                if entries.contains_key(&server.name) {
+                    let server_table = build_toml_edit_server_config(server, tool_key)?;
+                    entries.insert(&server.name, toml_edit::Item::Table(server_table));
+                }
+            }
+            Ok(doc.to_string())
+        }
+        "yaml" => {
+            let format_config = get_format_config(tool_key);
+            let existing = yaml_config::import_servers_from_yaml(content, field, format_config)?;
+            let mut content = content.to_string();
+            for server in &servers {
+                if existing.iter().any(|s| s.name == server.name) {
+                    content = yaml_config::sync_server_to_content(&content, server, tool_key, field, format_config)?;
+                }
+            }
+            Ok(content)
+        }
         _ => Err(format!("Unsupported config format: {}", format)),
     }
 }
@@ -64,6 +204,7 @@ pub fn remove_server_from_tool(
 fn sync_server_to_json(
     config_path: &PathBuf,
     server: &McpServer,
+    tool_key: &str,
     field: &str,
     format_config: Option<&McpFormatConfig>,
 ) -> Result<(), String> {
@@ -96,7 +237,7 @@ fn sync_server_to_json(
         .or_insert(serde_json::json!({}));
 
     // Build server config based on type and format config
-    let server_config = build_json_server_config(server, format_config)?;
+    let server_config = build_json_server_config(server, tool_key, format_config)?;
 
     // Add/update server
     mcp_servers
@@ -109,7 +250,7 @@ fn sync_server_to_json(
     // which is valid JSON5 (JSON is a subset of JSON5)
     let content = serde_json::to_string_pretty(&config)
         .map_err(|e| format!("Failed to serialize config: {}", e))?;
-    std::fs::write(config_path, content)
+    write_config_file(config_path, content)
         .map_err(|e| format!("Failed to write config file: {}", e))?;
 
     Ok(())
@@ -144,7 +285,7 @@ fn remove_server_from_json(
     // Write back to file
     let content = serde_json::to_string_pretty(&config)
         .map_err(|e| format!("Failed to serialize config: {}", e))?;
-    std::fs::write(config_path, content)
+    write_config_file(config_path, content)
         .map_err(|e| format!("Failed to write config file: {}", e))?;
 
     Ok(())
@@ -154,6 +295,7 @@ fn remove_server_from_json(
 fn sync_server_to_toml(
     config_path: &PathBuf,
     server: &McpServer,
+    tool_key: &str,
     field: &str,
 ) -> Result<(), String> {
     use toml_edit::Item;
@@ -184,14 +326,14 @@ fn sync_server_to_toml(
     }
 
     // Build server config using toml_edit
-    let server_table = build_toml_edit_server_config(server)?;
+    let server_table = build_toml_edit_server_config(server, tool_key)?;
 
     // Add/update server
     doc[field][&server.name] = Item::Table(server_table);
 
     // Write back to file
     let content = doc.to_string();
-    std::fs::write(config_path, content)
+    write_config_file(config_path, content)
         .map_err(|e| format!("Failed to write config file: {}", e))?;
 
     Ok(())
@@ -222,14 +364,14 @@ fn remove_server_from_toml(
 
     // Write back to file
     let content = doc.to_string();
-    std::fs::write(config_path, content)
+    write_config_file(config_path, content)
         .map_err(|e| format!("Failed to write config file: {}", e))?;
 
     Ok(())
 }
 
 /// Build TOML server configuration using toml_edit (matches cc-switch format)
-fn build_toml_edit_server_config(server: &McpServer) -> Result<toml_edit::Table, String> {
+fn build_toml_edit_server_config(server: &McpServer, tool_key: &str) -> Result<toml_edit::Table, String> {
     use toml_edit::{Array, Item, Table};
 
     let mut t = Table::new();
@@ -320,17 +462,116 @@ fn build_toml_edit_server_config(server: &McpServer) -> Result<toml_edit::Table,
         _ => return Err(format!("Unknown server type: {}", server.server_type)),
     }
 
+    // Tool filter (e.g. Codex enabled_tools / disabled_tools)
+    for (field, tools) in native_tool_filter(server, tool_key) {
+        let mut arr = Array::default();
+        for tool in tools {
+            arr.push(tool.as_str());
+        }
+        t[field] = Item::Value(toml_edit::Value::Array(arr));
+    }
+
     Ok(t)
 }
 
 /// Build JSON server configuration from McpServer
 /// Applies format conversion if format_config is provided
-fn build_json_server_config(server: &McpServer, format_config: Option<&McpFormatConfig>) -> Result<Value, String> {
-    match server.server_type.as_str() {
-        "stdio" => build_stdio_config(server, format_config),
-        "http" | "sse" => build_http_config(server, format_config),
-        _ => Err(format!("Unknown server type: {}", server.server_type)),
+pub(super) fn build_json_server_config(
+    server: &McpServer,
+    tool_key: &str,
+    format_config: Option<&McpFormatConfig>,
+) -> Result<Value, String> {
+    let mut result = match server.server_type.as_str() {
+        "stdio" => build_stdio_config(server, format_config)?,
+        "http" | "sse" => build_http_config(server, format_config)?,
+        _ => return Err(format!("Unknown server type: {}", server.server_type)),
+    };
+
+    // Tool filter (e.g. Gemini CLI includeTools / excludeTools)
+    if let Some(obj) = result.as_object_mut() {
+        for (field, tools) in native_tool_filter(server, tool_key) {
+            obj.insert(field.to_string(), serde_json::json!(tools));
+        }
     }
+
+    Ok(result)
+}
+
+/// Native per-server tool filter fields for a tool, empty when the tool has no
+/// native filter or no filter is configured
+fn native_tool_filter<'a>(server: &'a McpServer, tool_key: &str) -> Vec<(&'static str, &'a Vec<String>)> {
+    let Some(filter) = server.tool_filter(tool_key) else {
+        return Vec::new();
+    };
+    let Some((_, allow_field, deny_field)) = NATIVE_TOOL_FILTER_FIELDS
+        .iter()
+        .find(|(key, _, _)| *key == tool_key)
+    else {
+        return Vec::new();
+    };
+
+    [(*allow_field, &filter.allowed), (*deny_field, &filter.denied)]
+        .into_iter()
+        .filter(|(_, tools)| !tools.is_empty())
+        .collect()
+}
+
+/// Claude Code has no per-server tool filter; hidden tools become `permissions.deny`
+/// rules (`mcp__<server>__<tool>`) in ~/.claude/settings.json. Rules with the server's
+/// prefix are owned by the MCP sync and replaced on every sync.
+fn sync_claude_tool_permissions(server_name: &str, hidden_tools: &[String]) -> Result<(), String> {
+    let config_path = PathBuf::from(get_claude_config_path()?);
+    let mut settings: Value = match std::fs::read_to_string(&config_path) {
+        Ok(content) if !content.trim().is_empty() => json5::from_str(&content)
+            .map_err(|e| format!("Failed to parse Claude settings: {}", e))?,
+        _ => serde_json::json!({}),
+    };
+
+    if !apply_claude_deny_rules(&mut settings, server_name, hidden_tools)? {
+        return Ok(());
+    }
+
+    let content = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize Claude settings: {}", e))?;
+    write_config_file(&config_path, content)
+        .map_err(|e| format!("Failed to write Claude settings: {}", e))
+}
+
+/// Replace the deny rules of a server in Claude settings; returns whether anything changed
+fn apply_claude_deny_rules(settings: &mut Value, server_name: &str, hidden_tools: &[String]) -> Result<bool, String> {
+    // Claude Code replaces characters outside [A-Za-z0-9_-] in server names with '_'
+    let normalized: String = server_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .collect();
+    let prefix = format!("mcp__{}__", normalized);
+
+    let existing: Vec<Value> = settings
+        .pointer("/permissions/deny")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let mut deny: Vec<Value> = existing
+        .iter()
+        .filter(|rule| !rule.as_str().is_some_and(|r| r.starts_with(&prefix)))
+        .cloned()
+        .collect();
+    deny.extend(hidden_tools.iter().map(|tool| Value::String(format!("{}{}", prefix, tool))));
+
+    if deny == existing {
+        return Ok(false);
+    }
+
+    let permissions = settings
+        .as_object_mut()
+        .ok_or("Claude settings is not a JSON object")?
+        .entry("permissions")
+        .or_insert(serde_json::json!({}));
+    permissions
+        .as_object_mut()
+        .ok_or("Claude settings permissions is not a JSON object")?
+        .insert("deny".to_string(), Value::Array(deny));
+    Ok(true)
 }
 
 /// Build stdio server configuration
@@ -363,7 +604,7 @@ fn build_stdio_config(server: &McpServer, format_config: Option<&McpFormatConfig
 
             // Windows: wrap cmd /c for OpenCode array format
             let command_array = command_normalize::wrap_cmd_c_opencode_array(&command_array);
-            result.insert("command".to_string(), Value::Array(command_array));
+            result.insert(config.command_field.to_string(), Value::Array(command_array));
         } else {
             // Standard command + args format with format_config
             // Build result first, then wrap for Windows
@@ -378,7 +619,7 @@ fn build_stdio_config(server: &McpServer, format_config: Option<&McpFormatConfig
             let final_command = temp_result.get("command").and_then(|v| v.as_str()).unwrap_or(command);
             let final_args = temp_result.get("args").cloned().unwrap_or(Value::Array(vec![]));
 
-            result.insert("command".to_string(), Value::String(final_command.to_string()));
+            result.insert(config.command_field.to_string(), Value::String(final_command.to_string()));
             result.insert("args".to_string(), final_args);
         }
 
@@ -389,11 +630,7 @@ fn build_stdio_config(server: &McpServer, format_config: Option<&McpFormatConfig
             }
         }
 
-        // Add enabled field if required
-        if config.requires_enabled {
-            result.insert("enabled".to_string(), Value::Bool(true));
-        }
-
+        insert_format_fields(&mut result, server, config);
         Ok(Value::Object(result))
     } else {
         // Standard format (Claude Code, Gemini CLI, etc.)
@@ -416,6 +653,20 @@ fn build_stdio_config(server: &McpServer, format_config: Option<&McpFormatConfig
     }
 }
 
+/// Add the fields a format requires besides the server config itself
+/// (`enabled`, the server name, a timeout)
+fn insert_format_fields(result: &mut serde_json::Map<String, Value>, server: &McpServer, config: &McpFormatConfig) {
+    if config.requires_enabled {
+        result.insert("enabled".to_string(), Value::Bool(true));
+    }
+    if let Some(name_field) = config.name_field {
+        result.insert(name_field.to_string(), Value::String(server.name.clone()));
+    }
+    if let Some(timeout) = config.timeout_secs {
+        result.insert("timeout".to_string(), Value::from(timeout));
+    }
+}
+
 /// Build HTTP/SSE server configuration
 fn build_http_config(server: &McpServer, format_config: Option<&McpFormatConfig>) -> Result<Value, String> {
     let url = server.server_config
@@ -432,7 +683,7 @@ fn build_http_config(server: &McpServer, format_config: Option<&McpFormatConfig>
         // Map server type
         let mapped_type = config.map_type_to_tool(&server.server_type);
         result.insert("type".to_string(), Value::String(mapped_type.to_string()));
-        result.insert("url".to_string(), Value::String(url.to_string()));
+        result.insert(config.url_field.to_string(), Value::String(url.to_string()));
 
         if let Some(headers_val) = headers {
             if headers_val.is_object() && !headers_val.as_object().map(|o| o.is_empty()).unwrap_or(true) {
@@ -440,11 +691,7 @@ fn build_http_config(server: &McpServer, format_config: Option<&McpFormatConfig>
             }
         }
 
-        // Add enabled field if required
-        if config.requires_enabled {
-            result.insert("enabled".to_string(), Value::Bool(true));
-        }
-
+        insert_format_fields(&mut result, server, config);
         Ok(Value::Object(result))
     } else {
         // Standard format (Claude Code, Gemini CLI, etc.)
@@ -472,26 +719,33 @@ pub fn import_servers_from_tool(tool: &RuntimeTool) -> Result<Vec<McpServer>, St
         return Ok(vec![]);
     }
 
+    let content = std::fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read config file: {}", e))?;
+    import_servers_from_content(tool, &content)
+}
+
+/// Import MCP servers from the content of a tool's config file
+/// (e.g. a config file read from a remote machine)
+pub fn import_servers_from_content(tool: &RuntimeTool, content: &str) -> Result<Vec<McpServer>, String> {
     let format = tool.mcp_config_format.as_deref().unwrap_or("json");
     let field = tool.mcp_field.as_deref().unwrap_or("mcpServers");
     let format_config = get_format_config(&tool.key);
 
     match format {
         // json5 handles both standard JSON and JSONC (with comments, trailing commas)
-        "json" | "jsonc" => import_servers_from_json(&config_path, field, format_config),
-        "toml" => import_servers_from_toml(&config_path, field),
+        "json" | "jsonc" => import_servers_from_json(content, field, format_config),
+        "toml" => import_servers_from_toml(content, field),
+        "yaml" => yaml_config::import_servers_from_yaml(content, field, format_config),
         _ => Err(format!("Unsupported config format: {}", format)),
     }
 }
 
-/// Import servers from JSON/JSONC config file (using json5 for parsing)
+/// Import servers from JSON/JSONC config content (using json5 for parsing)
 fn import_servers_from_json(
-    config_path: &PathBuf,
+    content: &str,
     field: &str,
     format_config: Option<&McpFormatConfig>,
 ) -> Result<Vec<McpServer>, String> {
-    let content = std::fs::read_to_string(config_path)
-        .map_err(|e| format!("Failed to read config file: {}", e))?;
     let content = content.trim();
     if content.is_empty() {
         return Ok(vec![]);
@@ -530,7 +784,7 @@ fn parse_mcp_servers_from_value(
 }
 
 /// Parse a single server config, applying format conversion if needed
-fn parse_server_config(
+pub(super) fn parse_server_config(
     name: &str,
     server_config: &Value,
     format_config: Option<&McpFormatConfig>,
@@ -563,7 +817,7 @@ fn parse_server_with_format_config(
     // Build unified server_config
     let unified_config = if server_type == "stdio" {
         // Handle command array -> command + args conversion
-        let command_val = server_config.get("command")?;
+        let command_val = server_config.get(format_config.command_field)?;
 
         let (command, args) = if format_config.merge_command_args {
             // Command is an array: ["npx", "-y", "pkg"] or ["cmd", "/c", "npx", "-y", "pkg"]
@@ -609,7 +863,7 @@ fn parse_server_with_format_config(
         command_normalize::unwrap_cmd_c(&result)
     } else {
         // HTTP/SSE type
-        let url = server_config.get("url").and_then(|v| v.as_str())?;
+        let url = server_config.get(format_config.url_field).and_then(|v| v.as_str())?;
         let headers = server_config.get("headers").cloned();
 
         let mut result = serde_json::json!({
@@ -633,6 +887,9 @@ fn parse_server_with_format_config(
         description: None,
         tags: vec![],
         sort_index: 0,
+        health: None,
+        tool_catalog: vec![],
+        tool_filters: Default::default(),
         created_at: now,
         updated_at: now,
     })
@@ -676,15 +933,16 @@ fn parse_standard_server_config(
         description: None,
         tags: vec![],
         sort_index: 0,
+        health: None,
+        tool_catalog: vec![],
+        tool_filters: Default::default(),
         created_at: now,
         updated_at: now,
     })
 }
 
-/// Import servers from TOML config file
-fn import_servers_from_toml(config_path: &PathBuf, field: &str) -> Result<Vec<McpServer>, String> {
-    let content = std::fs::read_to_string(config_path)
-        .map_err(|e| format!("Failed to read config file: {}", e))?;
+/// Import servers from TOML config content
+fn import_servers_from_toml(content: &str, field: &str) -> Result<Vec<McpServer>, String> {
     let content_trimmed = content.trim();
     if content_trimmed.is_empty() {
         return Ok(vec![]);
@@ -786,6 +1044,9 @@ fn import_servers_from_toml(config_path: &PathBuf, field: &str) -> Result<Vec<Mc
             description: None,
             tags: vec![],
             sort_index: 0,
+            health: None,
+            tool_catalog: vec![],
+            tool_filters: Default::default(),
             created_at: now,
             updated_at: now,
         });
@@ -793,3 +1054,104 @@ fn import_servers_from_toml(config_path: &PathBuf, field: &str) -> Result<Vec<Mc
 
     Ok(servers)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coding::mcp::types::{McpToolFilter, McpToolInfo};
+
+    fn server_with_filter(tool_key: &str, filter: McpToolFilter) -> McpServer {
+        McpServer {
+            id: String::new(),
+            name: "my server".to_string(),
+            server_type: "stdio".to_string(),
+            server_config: serde_json::json!({ "command": "npx", "args": ["-y", "srv"] }),
+            enabled_tools: vec![],
+            sync_details: None,
+            description: None,
+            tags: vec![],
+            sort_index: 0,
+            health: None,
+            tool_catalog: ["read", "write", "delete"]
+                .iter()
+                .map(|name| McpToolInfo { name: name.to_string(), description: None })
+                .collect(),
+            tool_filters: [(tool_key.to_string(), filter)].into_iter().collect(),
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_native_tool_filter() {
+        let filter = McpToolFilter { allowed: vec!["read".to_string()], denied: vec![] };
+
+        let codex = server_with_filter("codex", filter.clone());
+        let table = build_toml_edit_server_config(&codex, "codex").unwrap();
+        assert_eq!(table["enabled_tools"].to_string().trim(), "[\"read\"]");
+        assert!(!table.contains_key("disabled_tools"));
+
+        let gemini = server_with_filter("gemini_cli", filter.clone());
+        let config = build_json_server_config(&gemini, "gemini_cli", None).unwrap();
+        assert_eq!(config["includeTools"], serde_json::json!(["read"]));
+
+        // Filters of other clients and clients without native support are not written
+        let config = build_json_server_config(&gemini, "cursor", None).unwrap();
+        assert!(config.get("includeTools").is_none());
+    }
+
+    #[test]
+    fn test_apply_target_env_to_content() {
+        use crate::coding::mcp::types::McpEnvVar;
+
+        let mut server = server_with_filter("codex", McpToolFilter::default());
+        server.enabled_tools = vec!["codex".to_string()];
+        server.server_config = serde_json::json!({
+            "command": "npx",
+            "env": { "GITHUB_TOKEN": "${GITHUB_TOKEN}" },
+        });
+        let var = McpEnvVar {
+            id: String::new(),
+            name: "GITHUB_TOKEN".to_string(),
+            value: "local-token".to_string(),
+            secret: false,
+            overrides: [("ssh:box".to_string(), "remote-token".to_string())].into_iter().collect(),
+            description: None,
+            created_at: 0,
+            updated_at: 0,
+        };
+        let content = "model = \"o3\"\n\n[mcp_servers.\"my server\"]\ncommand = \"npx\"\n\n[mcp_servers.\"my server\".env]\nGITHUB_TOKEN = \"local-token\"\n\n[mcp_servers.other]\ncommand = \"uvx\"\n";
+
+        let env = EnvResolver::new(std::slice::from_ref(&var), Some("ssh:box"));
+        let updated = apply_target_env_to_content(content, "codex", std::slice::from_ref(&server), &env).unwrap();
+        assert!(updated.contains("GITHUB_TOKEN = \"remote-token\""));
+        assert!(!updated.contains("local-token"));
+        assert!(updated.contains("model = \"o3\""));
+        assert!(updated.contains("command = \"uvx\""));
+
+        // Entries missing from the content are not added
+        let without_entry = "[mcp_servers.other]\ncommand = \"uvx\"\n";
+        let unchanged = apply_target_env_to_content(without_entry, "codex", &[server], &env).unwrap();
+        assert_eq!(unchanged, without_entry);
+    }
+
+    #[test]
+    fn test_claude_deny_rules() {
+        let mut settings = serde_json::json!({
+            "permissions": { "deny": ["Bash(rm:*)", "mcp__my_server__old"] }
+        });
+        let filter = McpToolFilter { allowed: vec!["read".to_string()], denied: vec![] };
+        let hidden = filter.hidden_tools(&server_with_filter("claude_code", filter.clone()).tool_catalog);
+        assert_eq!(hidden, vec!["delete", "write"]);
+
+        assert!(apply_claude_deny_rules(&mut settings, "my server", &hidden).unwrap());
+        assert_eq!(
+            settings["permissions"]["deny"],
+            serde_json::json!(["Bash(rm:*)", "mcp__my_server__delete", "mcp__my_server__write"])
+        );
+        assert!(!apply_claude_deny_rules(&mut settings, "my server", &hidden).unwrap());
+
+        assert!(apply_claude_deny_rules(&mut settings, "my server", &[]).unwrap());
+        assert_eq!(settings["permissions"]["deny"], serde_json::json!(["Bash(rm:*)"]));
+    }
+}
@@ -0,0 +1,226 @@
+//! `${NAME}` placeholders in MCP server configs
+//!
+//! Server configs may reference managed variables as `${NAME}` in any string (env
+//! values, headers, args, URLs). Placeholders are resolved when a server is written to a
+//! client config or started by the app, using the variable's override for the sync
+//! target (`ssh:<connection id>`, `wsl:<distro>`) when one is set. Names without a
+//! managed variable are left as they are, since several clients expand `${VAR}` from
+//! their own environment. Project-level config files are never resolved, see
+//! [`super::project_sync`].
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde_json::Value;
+
+use super::mcp_store;
+use super::types::{McpEnvVar, McpServer};
+use crate::db::DbState;
+use crate::secrets;
+
+fn placeholder_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)\}").expect("valid regex"))
+}
+
+/// Whether a variable name can be used in a placeholder
+pub fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Sync target id of an SSH connection, used as override key
+pub fn ssh_target(connection_id: &str) -> String {
+    format!("ssh:{}", connection_id)
+}
+
+/// Sync target id of a WSL distro, used as override key
+pub fn wsl_target(distro: &str) -> String {
+    format!("wsl:{}", distro)
+}
+
+/// Whether a server config contains placeholders
+pub fn has_placeholders(server: &McpServer) -> bool {
+    placeholder_regex().is_match(&server.server_config.to_string())
+}
+
+/// Whether a server config references a variable
+pub fn references(server: &McpServer, name: &str) -> bool {
+    placeholder_regex()
+        .captures_iter(&server.server_config.to_string())
+        .any(|caps| &caps[1] == name)
+}
+
+/// Variable values for one sync target
+#[derive(Debug, Default)]
+pub struct EnvResolver {
+    values: HashMap<String, String>,
+}
+
+impl EnvResolver {
+    /// Load the managed variables; `target` is None for the local machine
+    pub async fn load(state: &DbState, target: Option<&str>) -> Result<Self, String> {
+        let vars = mcp_store::get_mcp_env_vars(state).await?;
+        Ok(Self::new(&vars, target))
+    }
+
+    pub fn new(vars: &[McpEnvVar], target: Option<&str>) -> Self {
+        let values = vars
+            .iter()
+            .map(|var| {
+                let value = target
+                    .and_then(|target| var.overrides.get(target))
+                    .unwrap_or(&var.value);
+                (var.name.clone(), value.clone())
+            })
+            .collect();
+        Self { values }
+    }
+
+    /// Replace the placeholders of managed variables in a string
+    pub fn resolve_str(&self, text: &str) -> Result<String, String> {
+        let mut error = None;
+        let resolved = placeholder_regex().replace_all(text, |caps: &regex::Captures| {
+            let Some(value) = self.values.get(&caps[1]) else {
+                return caps[0].to_string();
+            };
+            if secrets::check_resolved(value).is_err() {
+                error = Some(format!(
+                    "Variable {} is stored in the locked secrets vault, unlock the vault and retry",
+                    &caps[1]
+                ));
+            }
+            value.clone()
+        });
+        match error {
+            Some(e) => Err(e),
+            None => Ok(resolved.into_owned()),
+        }
+    }
+
+    /// Copy of a server with the placeholders in its config resolved
+    pub fn resolve_server(&self, server: &McpServer) -> Result<McpServer, String> {
+        let mut resolved = server.clone();
+        if !self.values.is_empty() {
+            resolve_strings(&mut resolved.server_config, self)?;
+        }
+        Ok(resolved)
+    }
+}
+
+/// Copy of a server with its placeholders rewritten to `<prefix>NAME<suffix>`, for
+/// clients that expand environment variables with another syntax
+pub fn rewrite_placeholders(server: &McpServer, prefix: &str, suffix: &str) -> McpServer {
+    let mut rewritten = server.clone();
+    rewrite_strings(&mut rewritten.server_config, prefix, suffix);
+    rewritten
+}
+
+fn rewrite_strings(value: &mut Value, prefix: &str, suffix: &str) {
+    match value {
+        Value::String(s) => {
+            *s = placeholder_regex()
+                .replace_all(s, |caps: &regex::Captures| format!("{}{}{}", prefix, &caps[1], suffix))
+                .into_owned()
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| rewrite_strings(item, prefix, suffix)),
+        Value::Object(map) => map.values_mut().for_each(|item| rewrite_strings(item, prefix, suffix)),
+        _ => {}
+    }
+}
+
+fn resolve_strings(value: &mut Value, env: &EnvResolver) -> Result<(), String> {
+    match value {
+        Value::String(s) => *s = env.resolve_str(s)?,
+        Value::Array(items) => {
+            for item in items {
+                resolve_strings(item, env)?;
+            }
+        }
+        Value::Object(map) => {
+            for item in map.values_mut() {
+                resolve_strings(item, env)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn var(name: &str, value: &str, overrides: &[(&str, &str)]) -> McpEnvVar {
+        McpEnvVar {
+            id: String::new(),
+            name: name.to_string(),
+            value: value.to_string(),
+            secret: false,
+            overrides: overrides
+                .iter()
+                .map(|(target, value)| (target.to_string(), value.to_string()))
+                .collect(),
+            description: None,
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_resolve_placeholders() {
+        let vars = vec![
+            var("GITHUB_TOKEN", "local-token", &[("ssh:box", "remote-token")]),
+            var("API_HOST", "api.example.com", &[]),
+        ];
+        let server: McpServer = serde_json::from_value(json!({
+            "id": "1",
+            "name": "github",
+            "server_type": "stdio",
+            "server_config": {
+                "command": "npx",
+                "args": ["--host", "https://${API_HOST}/v1"],
+                "env": { "GITHUB_TOKEN": "${GITHUB_TOKEN}", "HOME_DIR": "${HOME}" },
+            },
+            "enabled_tools": [],
+            "created_at": 0,
+            "updated_at": 0,
+        }))
+        .unwrap();
+
+        let local = EnvResolver::new(&vars, None).resolve_server(&server).unwrap();
+        assert_eq!(local.server_config["env"]["GITHUB_TOKEN"], "local-token");
+        assert_eq!(local.server_config["args"][1], "https://api.example.com/v1");
+        // Unmanaged names are left for the client to expand
+        assert_eq!(local.server_config["env"]["HOME_DIR"], "${HOME}");
+
+        let remote = EnvResolver::new(&vars, Some(&ssh_target("box")))
+            .resolve_server(&server)
+            .unwrap();
+        assert_eq!(remote.server_config["env"]["GITHUB_TOKEN"], "remote-token");
+        let other = EnvResolver::new(&vars, Some(&wsl_target("Ubuntu")))
+            .resolve_server(&server)
+            .unwrap();
+        assert_eq!(other.server_config["env"]["GITHUB_TOKEN"], "local-token");
+
+        let vscode = rewrite_placeholders(&server, "${env:", "}");
+        assert_eq!(vscode.server_config["env"]["GITHUB_TOKEN"], "${env:GITHUB_TOKEN}");
+        assert_eq!(vscode.server_config["args"][1], "https://${env:API_HOST}/v1");
+
+        assert!(has_placeholders(&server));
+        assert!(references(&server, "API_HOST"));
+        assert!(!references(&server, "API"));
+    }
+
+    #[test]
+    fn test_valid_names() {
+        assert!(is_valid_name("GITHUB_TOKEN"));
+        assert!(is_valid_name("_x1"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name("1ABC"));
+        assert!(!is_valid_name("A-B"));
+    }
+}
@@ -24,12 +24,63 @@ pub const OPENCODE_FORMAT: McpFormatConfig = McpFormatConfig {
     env_field: "environment",
     requires_enabled: true,
     default_tool_type: "local",
+    command_field: "command",
+    url_field: "url",
+    name_field: None,
+    timeout_secs: None,
+    list_entries: false,
+};
+
+/// Goose format configuration (`extensions` in `~/.config/goose/config.yaml`)
+///
+/// - Entries are keyed by name and repeat it in `name`
+/// - `command` -> `cmd`, `env` -> `envs`, `url` -> `uri`
+/// - `http` -> `streamable_http`
+/// - Requires `enabled: true` and a `timeout`
+pub const GOOSE_FORMAT: McpFormatConfig = McpFormatConfig {
+    type_mappings: &[
+        ("stdio", "stdio"),
+        ("http", "streamable_http"),
+        ("sse", "sse"),
+    ],
+    merge_command_args: false,
+    env_field: "envs",
+    requires_enabled: true,
+    default_tool_type: "stdio",
+    command_field: "cmd",
+    url_field: "uri",
+    name_field: Some("name"),
+    timeout_secs: Some(300),
+    list_entries: false,
+};
+
+/// Continue format configuration (`mcpServers` in `~/.continue/config.yaml`)
+///
+/// - Servers are a list of blocks identified by `name`
+/// - `http` -> `streamable-http`
+pub const CONTINUE_FORMAT: McpFormatConfig = McpFormatConfig {
+    type_mappings: &[
+        ("stdio", "stdio"),
+        ("http", "streamable-http"),
+        ("sse", "sse"),
+    ],
+    merge_command_args: false,
+    env_field: "env",
+    requires_enabled: false,
+    default_tool_type: "stdio",
+    command_field: "command",
+    url_field: "url",
+    name_field: Some("name"),
+    timeout_secs: None,
+    list_entries: true,
 };
 
 /// Get the format config for a tool by key
 pub fn get_format_config(tool_key: &str) -> Option<&'static McpFormatConfig> {
     match tool_key {
         "opencode" => Some(&OPENCODE_FORMAT),
+        "goose" => Some(&GOOSE_FORMAT),
+        "continue" => Some(&CONTINUE_FORMAT),
         _ => None,
     }
 }
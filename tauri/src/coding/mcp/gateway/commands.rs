@@ -0,0 +1,104 @@
+//! Tauri commands for the MCP gateway
+
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+use super::super::commands::mcp_sync_all;
+use super::super::config_sync::remove_server_from_tool;
+use super::super::mcp_store;
+use super::super::types::now_ms;
+use super::upstream::McpGatewayUpstream;
+use super::{last_error, set_enabled, start_gateway, stop_gateway, McpGatewayCall, McpGatewayState, GATEWAY_ENTRY_NAME};
+use crate::coding::tools::{custom_store, is_tool_installed, runtime_tool_by_key};
+use crate::DbState;
+
+/// Gateway status for the frontend
+#[derive(Debug, Serialize)]
+pub struct McpGatewayStatusDto {
+    pub enabled: bool,
+    pub running: bool,
+    pub port: u16,
+    /// Unscoped HTTP endpoint; clients use `<url>/<tool key>`
+    pub url: Option<String>,
+    pub last_error: Option<String>,
+    pub upstreams: Vec<McpGatewayUpstream>,
+    pub recent_calls: Vec<McpGatewayCall>,
+}
+
+async fn gateway_status(state: &DbState, gateway: &McpGatewayState) -> Result<McpGatewayStatusDto, String> {
+    let prefs = mcp_store::get_mcp_preferences(state).await?;
+    let running_port = gateway.running.lock().await.as_ref().map(|running| running.port);
+    Ok(McpGatewayStatusDto {
+        enabled: prefs.gateway_enabled,
+        running: running_port.is_some(),
+        port: running_port.unwrap_or(prefs.gateway_port),
+        url: running_port.map(|port| format!("http://127.0.0.1:{}/mcp", port)),
+        last_error: last_error(),
+        upstreams: gateway.upstreams.status(),
+        recent_calls: gateway.recent_calls(),
+    })
+}
+
+/// Get the gateway status, its upstream servers and recent tool calls
+#[tauri::command]
+pub async fn mcp_gateway_status(
+    state: State<'_, DbState>,
+    gateway: State<'_, McpGatewayState>,
+) -> Result<McpGatewayStatusDto, String> {
+    gateway_status(&state, &gateway).await
+}
+
+/// Enable or disable the gateway (optionally changing its port), then re-sync clients:
+/// with the gateway they get the single gateway entry, without it every server again
+#[tauri::command]
+pub async fn mcp_set_gateway_enabled(
+    app: AppHandle,
+    state: State<'_, DbState>,
+    gateway: State<'_, McpGatewayState>,
+    enabled: bool,
+    port: Option<u16>,
+) -> Result<McpGatewayStatusDto, String> {
+    let mut prefs = mcp_store::get_mcp_preferences(&state).await?;
+    prefs.gateway_enabled = enabled;
+    if let Some(port) = port {
+        prefs.gateway_port = port;
+    }
+    prefs.updated_at = now_ms();
+    mcp_store::save_mcp_preferences(&state, &prefs).await?;
+    set_enabled(enabled);
+
+    if enabled {
+        start_gateway(&app, prefs.gateway_port).await?;
+    } else {
+        stop_gateway(&gateway).await;
+        remove_gateway_entries(&state).await;
+    }
+
+    mcp_sync_all(app.clone(), state.clone()).await?;
+    gateway_status(&state, &gateway).await
+}
+
+/// Remove the gateway entry from every client that has managed servers
+async fn remove_gateway_entries(state: &DbState) {
+    let custom_tools = custom_store::get_custom_tools(state).await.unwrap_or_default();
+    let servers = mcp_store::get_mcp_servers(state).await.unwrap_or_default();
+    let tool_keys: BTreeSet<&String> = servers
+        .iter()
+        .filter(|server| server.name != GATEWAY_ENTRY_NAME)
+        .flat_map(|server| &server.enabled_tools)
+        .collect();
+
+    for tool_key in tool_keys {
+        let Some(tool) = runtime_tool_by_key(tool_key, &custom_tools) else {
+            continue;
+        };
+        if !is_tool_installed(&tool) {
+            continue;
+        }
+        if let Err(e) = remove_server_from_tool(GATEWAY_ENTRY_NAME, &tool) {
+            log::warn!("Failed to remove MCP gateway entry from {}: {}", tool_key, e);
+        }
+    }
+}
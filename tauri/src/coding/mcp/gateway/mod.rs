@@ -0,0 +1,220 @@
+//! MCP Gateway Module
+//!
+//! Runs the managed MCP servers once inside the app and exposes them to every client
+//! through one entry: a localhost Streamable HTTP endpoint (`/mcp/<client>`) that
+//! aggregates `tools/list` under `<server>__<tool>` names and routes `tools/call` to the
+//! owning server, plus a stdio shim (the app binary started with `--mcp-gateway`) that
+//! clients launch like any other stdio server.
+//!
+//! While the gateway is enabled, clients only get the gateway entry, so enabling or disabling
+//! a server (or one of its tools) for a client takes effect without rewriting its config.
+
+pub mod commands;
+mod server;
+mod shim;
+mod upstream;
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU16, Ordering};
+
+use serde::Serialize;
+use tauri::Manager;
+use tokio::sync::Mutex;
+
+use super::types::McpServer;
+use upstream::UpstreamPool;
+
+pub use commands::*;
+pub use shim::{run_shim, shim_args};
+
+/// Name of the single entry written to client configs
+pub const GATEWAY_ENTRY_NAME: &str = "ai-toolbox";
+
+/// Default localhost port of the HTTP endpoint
+pub const DEFAULT_GATEWAY_PORT: u16 = 39517;
+
+/// Number of tool calls kept in the call log
+const CALL_LOG_SIZE: usize = 200;
+
+/// Port of the running gateway, 0 when it is not running. Read by the config sync,
+/// which has no access to app state.
+static ACTIVE_PORT: AtomicU16 = AtomicU16::new(0);
+
+/// `gateway_enabled` of the MCP preferences, mirrored for the config sync
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Why the gateway failed to start, cleared when it starts
+static LAST_ERROR: std::sync::Mutex<Option<String>> = std::sync::Mutex::new(None);
+
+/// Mirror the `gateway_enabled` preference for the config sync
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::SeqCst);
+}
+
+pub fn last_error() -> Option<String> {
+    LAST_ERROR.lock().ok().and_then(|e| e.clone())
+}
+
+fn set_last_error(error: Option<String>) {
+    if let Ok(mut last_error) = LAST_ERROR.lock() {
+        *last_error = error;
+    }
+}
+
+/// Port of the running gateway
+pub fn active_port() -> Option<u16> {
+    match ACTIVE_PORT.load(Ordering::SeqCst) {
+        0 => None,
+        port => Some(port),
+    }
+}
+
+/// Config entry pointing a client at the gateway, None when the gateway is disabled.
+/// An enabled gateway that is not running is an error, so the sync neither writes the
+/// servers directly nor leaves a gateway entry that points nowhere.
+pub fn client_entry(tool_key: &str) -> Result<Option<McpServer>, String> {
+    if !ENABLED.load(Ordering::SeqCst) {
+        return Ok(None);
+    }
+    let Some(port) = active_port() else {
+        return Err(match last_error() {
+            Some(e) => format!("MCP gateway is enabled but not running: {}", e),
+            None => "MCP gateway is enabled but not running".to_string(),
+        });
+    };
+    let exe = std::env::current_exe()
+        .map_err(|e| format!("Failed to get executable path: {}", e))?;
+    Ok(Some(McpServer {
+        id: String::new(),
+        name: GATEWAY_ENTRY_NAME.to_string(),
+        server_type: "stdio".to_string(),
+        server_config: serde_json::json!({
+            "command": exe.to_string_lossy(),
+            "args": ["--mcp-gateway", tool_key, "--port", port.to_string()],
+        }),
+        enabled_tools: vec![tool_key.to_string()],
+        sync_details: None,
+        description: None,
+        tags: vec![],
+        sort_index: 0,
+        health: None,
+        tool_catalog: vec![],
+        tool_filters: Default::default(),
+        created_at: 0,
+        updated_at: 0,
+    }))
+}
+
+/// Prefix of a server's tools in the gateway (`<key>__<tool>`)
+fn server_key(server_name: &str) -> String {
+    server_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .collect()
+}
+
+/// One `tools/call` routed through the gateway
+#[derive(Clone, Debug, Serialize)]
+pub struct McpGatewayCall {
+    pub called_at: i64,
+    /// Client tool key from the endpoint path, None for the unscoped `/mcp` endpoint
+    pub client: Option<String>,
+    pub server: String,
+    pub tool: String,
+    pub duration_ms: u64,
+    pub status: String, // "ok" | "error"
+    pub error_message: Option<String>,
+}
+
+struct RunningGateway {
+    port: u16,
+    task: tauri::async_runtime::JoinHandle<()>,
+}
+
+/// Gateway state, managed by Tauri
+#[derive(Default)]
+pub struct McpGatewayState {
+    running: Mutex<Option<RunningGateway>>,
+    upstreams: UpstreamPool,
+    calls: std::sync::Mutex<VecDeque<McpGatewayCall>>,
+}
+
+impl McpGatewayState {
+    fn record_call(&self, call: McpGatewayCall) {
+        match &call.error_message {
+            Some(e) => log::warn!(
+                "MCP gateway call {}__{} failed after {} ms: {}",
+                call.server, call.tool, call.duration_ms, e
+            ),
+            None => log::info!(
+                "MCP gateway call {}__{} ({} ms)",
+                call.server, call.tool, call.duration_ms
+            ),
+        }
+        if let Ok(mut calls) = self.calls.lock() {
+            if calls.len() == CALL_LOG_SIZE {
+                calls.pop_front();
+            }
+            calls.push_back(call);
+        }
+    }
+
+    fn recent_calls(&self) -> Vec<McpGatewayCall> {
+        self.calls
+            .lock()
+            .map(|calls| calls.iter().rev().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Start (or restart) the gateway on a localhost port
+pub async fn start_gateway(app: &tauri::AppHandle, port: u16) -> Result<(), String> {
+    let gateway = app.state::<McpGatewayState>();
+    stop_gateway(&gateway).await;
+
+    let listener = match tokio::net::TcpListener::bind(("127.0.0.1", port)).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            let error = format!("Failed to listen on 127.0.0.1:{}: {}", port, e);
+            set_last_error(Some(error.clone()));
+            return Err(error);
+        }
+    };
+
+    let task = tauri::async_runtime::spawn(server::serve(app.clone(), listener, port));
+    *gateway.running.lock().await = Some(RunningGateway { port, task });
+    set_last_error(None);
+    ACTIVE_PORT.store(port, Ordering::SeqCst);
+    log::info!("MCP gateway listening on 127.0.0.1:{}", port);
+    Ok(())
+}
+
+/// Stop the gateway and every upstream server it started
+pub async fn stop_gateway(gateway: &McpGatewayState) {
+    ACTIVE_PORT.store(0, Ordering::SeqCst);
+    if let Some(running) = gateway.running.lock().await.take() {
+        running.task.abort();
+        log::info!("MCP gateway on port {} stopped", running.port);
+    }
+    gateway.upstreams.stop_all().await;
+}
+
+/// Start the gateway on app startup when it is enabled
+pub fn start_gateway_if_enabled(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let db_state = app.state::<crate::DbState>();
+        let prefs = match super::mcp_store::get_mcp_preferences(&db_state).await {
+            Ok(prefs) => prefs,
+            Err(e) => {
+                log::warn!("Failed to load MCP preferences: {}", e);
+                return;
+            }
+        };
+        set_enabled(prefs.gateway_enabled);
+        if prefs.gateway_enabled {
+            if let Err(e) = start_gateway(&app, prefs.gateway_port).await {
+                log::warn!("MCP gateway not started: {}", e);
+            }
+        }
+    });
+}
@@ -0,0 +1,437 @@
+//! Streamable HTTP endpoint of the gateway
+//!
+//! A minimal HTTP/1.1 server on 127.0.0.1: every request is a JSON-RPC message POSTed to
+//! `/mcp` (all managed servers) or `/mcp/<tool key>` (servers enabled for that client,
+//! with its tool filters applied), answered with a JSON body and `Connection: close`.
+//! Requests whose `Host` or `Origin` is not local are rejected to prevent DNS rebinding.
+
+use std::collections::HashSet;
+use std::time::Instant;
+
+use futures_util::future::join_all;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use super::super::client::PROTOCOL_VERSION;
+use super::super::env_vars::EnvResolver;
+use super::super::mcp_store;
+use super::super::types::{now_ms, McpServer};
+use super::{server_key, McpGatewayCall, McpGatewayState};
+use crate::db::DbState;
+
+/// Protocol versions the gateway can answer `initialize` with
+const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &[PROTOCOL_VERSION, "2025-03-26", "2024-11-05"];
+
+/// Separator between server key and tool name in gateway tool names
+const TOOL_SEPARATOR: &str = "__";
+
+const MAX_HEADER_BYTES: usize = 64 * 1024;
+const MAX_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+/// Accept connections until the task is aborted
+pub(super) async fn serve(app: AppHandle, listener: TcpListener, port: u16) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = handle_connection(&app, stream, port).await {
+                        log::debug!("MCP gateway connection error: {}", e);
+                    }
+                });
+            }
+            Err(e) => log::warn!("MCP gateway failed to accept a connection: {}", e),
+        }
+    }
+}
+
+struct HttpRequest {
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl HttpRequest {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+async fn handle_connection(app: &AppHandle, mut stream: TcpStream, port: u16) -> Result<(), String> {
+    let request = match read_request(&mut stream).await? {
+        Some(request) => request,
+        None => return Ok(()),
+    };
+
+    let (status, body) = route(app, &request, port).await;
+    write_response(&mut stream, status, body.as_deref()).await
+}
+
+async fn route(app: &AppHandle, request: &HttpRequest, port: u16) -> (u16, Option<String>) {
+    if !request.header("host").is_some_and(|host| is_local_host(host, port))
+        || !request.header("origin").is_none_or(is_local_origin)
+    {
+        return (403, None);
+    }
+
+    let path = request.path.split('?').next().unwrap_or_default();
+    let client = match path.trim_end_matches('/') {
+        "/mcp" => None,
+        path => match path.strip_prefix("/mcp/") {
+            Some(key) if !key.is_empty() && !key.contains('/') => Some(key.to_string()),
+            _ => return (404, None),
+        },
+    };
+    if request.method != "POST" {
+        return (405, None);
+    }
+
+    let message: Value = match serde_json::from_slice(&request.body) {
+        Ok(message) => message,
+        Err(e) => {
+            let error = rpc_error(Value::Null, -32700, &format!("Parse error: {}", e));
+            return (400, Some(error.to_string()));
+        }
+    };
+
+    // Notifications and responses are accepted without a body
+    let (Some(id), Some(method)) = (message.get("id"), message.get("method").and_then(|m| m.as_str())) else {
+        if !message.is_object() {
+            return (400, Some(rpc_error(Value::Null, -32600, "Invalid request").to_string()));
+        }
+        return (202, None);
+    };
+
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+    let response = dispatch(app, client.as_deref(), id.clone(), method, &params).await;
+    (200, Some(response.to_string()))
+}
+
+/// Answer a JSON-RPC request
+async fn dispatch(app: &AppHandle, client: Option<&str>, id: Value, method: &str, params: &Value) -> Value {
+    match method {
+        "initialize" => {
+            let requested = params.get("protocolVersion").and_then(|v| v.as_str());
+            let version = requested
+                .filter(|v| SUPPORTED_PROTOCOL_VERSIONS.contains(v))
+                .unwrap_or(PROTOCOL_VERSION);
+            rpc_result(
+                id,
+                json!({
+                    "protocolVersion": version,
+                    "capabilities": { "tools": { "listChanged": false } },
+                    "serverInfo": {
+                        "name": "ai-toolbox-gateway",
+                        "version": env!("CARGO_PKG_VERSION"),
+                    },
+                }),
+            )
+        }
+        "ping" => rpc_result(id, json!({})),
+        "tools/list" => match list_tools(app, client).await {
+            Ok(tools) => rpc_result(id, json!({ "tools": tools })),
+            Err(e) => rpc_error(id, -32603, &e),
+        },
+        "tools/call" => call_tool(app, client, id, params).await,
+        _ => rpc_error(id, -32601, &format!("Method not found: {}", method)),
+    }
+}
+
+/// Servers served on an endpoint, with their placeholders resolved so that a changed
+/// variable restarts the server; also stops the upstreams of deleted servers
+async fn servers_for(app: &AppHandle, client: Option<&str>) -> Result<Vec<McpServer>, String> {
+    let db_state = app.state::<DbState>();
+    let gateway = app.state::<McpGatewayState>();
+    let servers = mcp_store::get_mcp_servers(&db_state).await?;
+    let env = EnvResolver::load(&db_state, None).await?;
+
+    let ids: HashSet<&str> = servers.iter().map(|s| s.id.as_str()).collect();
+    gateway.upstreams.retain(&ids);
+
+    Ok(servers
+        .into_iter()
+        .filter(|server| client.is_none_or(|key| server.enabled_tools.iter().any(|t| t == key)))
+        .map(|server| env.resolve_server(&server).unwrap_or(server))
+        .collect())
+}
+
+fn allows_tool(server: &McpServer, client: Option<&str>, tool: &str) -> bool {
+    client
+        .and_then(|key| server.tool_filter(key))
+        .is_none_or(|filter| filter.allows(tool))
+}
+
+/// Aggregated tools of the endpoint, named `<server key>__<tool>`; servers that fail to
+/// start or list are skipped
+async fn list_tools(app: &AppHandle, client: Option<&str>) -> Result<Vec<Value>, String> {
+    let db_state = app.state::<DbState>();
+    let gateway = app.state::<McpGatewayState>();
+    let servers = servers_for(app, client).await?;
+
+    let listed = join_all(servers.iter().map(|server| {
+        let upstream = gateway.upstreams.get(server);
+        let db_state = db_state.inner();
+        async move { (server, upstream.list_tools(db_state).await) }
+    }))
+    .await;
+
+    let mut tools = Vec::new();
+    for (server, result) in listed {
+        let server_tools = match result {
+            Ok(server_tools) => server_tools,
+            Err(e) => {
+                log::warn!("MCP gateway skipped {}: {}", server.name, e);
+                continue;
+            }
+        };
+        let key = server_key(&server.name);
+        for mut tool in server_tools {
+            let Some(name) = tool.get("name").and_then(|v| v.as_str()).map(String::from) else {
+                continue;
+            };
+            if !allows_tool(server, client, &name) {
+                continue;
+            }
+            tool["name"] = Value::String(format!("{}{}{}", key, TOOL_SEPARATOR, name));
+            tools.push(tool);
+        }
+    }
+    Ok(tools)
+}
+
+/// Server owning a gateway tool name and the upstream tool name; the longest matching
+/// server key wins so that keys containing the separator still resolve
+fn resolve_tool<'a>(servers: &'a [McpServer], name: &str) -> Option<(&'a McpServer, String)> {
+    servers
+        .iter()
+        .filter_map(|server| {
+            let prefix = format!("{}{}", server_key(&server.name), TOOL_SEPARATOR);
+            let tool = name.strip_prefix(&prefix)?;
+            (!tool.is_empty()).then(|| (server, prefix.len(), tool.to_string()))
+        })
+        .max_by_key(|(_, prefix_len, _)| *prefix_len)
+        .map(|(server, _, tool)| (server, tool))
+}
+
+async fn call_tool(app: &AppHandle, client: Option<&str>, id: Value, params: &Value) -> Value {
+    let Some(name) = params.get("name").and_then(|v| v.as_str()) else {
+        return rpc_error(id, -32602, "Missing tool name");
+    };
+    let servers = match servers_for(app, client).await {
+        Ok(servers) => servers,
+        Err(e) => return rpc_error(id, -32603, &e),
+    };
+    let Some((server, tool)) = resolve_tool(&servers, name)
+        .filter(|(server, tool)| allows_tool(server, client, tool))
+    else {
+        return rpc_error(id, -32602, &format!("Unknown tool: {}", name));
+    };
+
+    let db_state = app.state::<DbState>();
+    let gateway = app.state::<McpGatewayState>();
+    let arguments = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+    let started = Instant::now();
+    let outcome = gateway.upstreams.get(server).call_tool(&db_state, &tool, arguments).await;
+
+    let error_message = match &outcome {
+        Ok(response) => match response.get("error") {
+            Some(error) => Some(
+                error
+                    .get("message")
+                    .and_then(|m| m.as_str())
+                    .unwrap_or("Unknown error")
+                    .to_string(),
+            ),
+            None if response.pointer("/result/isError") == Some(&Value::Bool(true)) => {
+                Some("Tool returned an error".to_string())
+            }
+            None => None,
+        },
+        Err(e) => Some(e.clone()),
+    };
+    gateway.record_call(McpGatewayCall {
+        called_at: now_ms(),
+        client: client.map(String::from),
+        server: server.name.clone(),
+        tool: tool.clone(),
+        duration_ms: started.elapsed().as_millis() as u64,
+        status: if error_message.is_some() { "error" } else { "ok" }.to_string(),
+        error_message,
+    });
+
+    match outcome {
+        Ok(mut response) => {
+            response["id"] = id;
+            response
+        }
+        Err(e) => rpc_error(id, -32603, &e),
+    }
+}
+
+fn rpc_result(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+fn rpc_error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// Split `host[:port]`, keeping IPv6 literals such as `[::1]` intact
+fn split_host(host: &str) -> (&str, Option<&str>) {
+    match host.rsplit_once(':') {
+        Some((name, port)) if !name.ends_with(':') => (name, Some(port)),
+        _ => (host, None),
+    }
+}
+
+fn is_loopback_name(name: &str) -> bool {
+    matches!(name, "127.0.0.1" | "localhost" | "[::1]")
+}
+
+/// Whether a `Host` header names this endpoint
+fn is_local_host(host: &str, port: u16) -> bool {
+    let (name, host_port) = split_host(host);
+    is_loopback_name(name) && host_port.is_none_or(|p| p == port.to_string())
+}
+
+/// Whether an `Origin` header is a page served from this machine (any port)
+fn is_local_origin(origin: &str) -> bool {
+    origin
+        .strip_prefix("http://")
+        .or_else(|| origin.strip_prefix("https://"))
+        .is_some_and(|host| is_loopback_name(split_host(host).0))
+}
+
+/// Read one request; None when the peer closed the connection without sending one
+async fn read_request(stream: &mut TcpStream) -> Result<Option<HttpRequest>, String> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 8192];
+    let header_end = loop {
+        if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        if buffer.len() > MAX_HEADER_BYTES {
+            return Err("Request headers too large".to_string());
+        }
+        let n = stream
+            .read(&mut chunk)
+            .await
+            .map_err(|e| format!("Failed to read request: {}", e))?;
+        if n == 0 {
+            return Ok(None);
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let path = request_line.next().unwrap_or_default().to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect();
+
+    let content_length = headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    if content_length > MAX_BODY_BYTES {
+        return Err("Request body too large".to_string());
+    }
+
+    let mut body = buffer.split_off(header_end + 4);
+    while body.len() < content_length {
+        let n = stream
+            .read(&mut chunk)
+            .await
+            .map_err(|e| format!("Failed to read request body: {}", e))?;
+        if n == 0 {
+            return Err("Connection closed before the request body was complete".to_string());
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+    body.truncate(content_length);
+
+    Ok(Some(HttpRequest { method, path, headers, body }))
+}
+
+async fn write_response(stream: &mut TcpStream, status: u16, body: Option<&str>) -> Result<(), String> {
+    let reason = match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    };
+    let body = body.unwrap_or_default();
+    let mut response = format!("HTTP/1.1 {} {}\r\nConnection: close\r\n", status, reason);
+    if status == 405 {
+        response.push_str("Allow: POST\r\n");
+    }
+    if !body.is_empty() {
+        response.push_str("Content-Type: application/json\r\n");
+    }
+    response.push_str(&format!("Content-Length: {}\r\n\r\n{}", body.len(), body));
+
+    stream
+        .write_all(response.as_bytes())
+        .await
+        .map_err(|e| format!("Failed to write response: {}", e))?;
+    let _ = stream.shutdown().await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server(name: &str) -> McpServer {
+        serde_json::from_value(json!({
+            "id": name,
+            "name": name,
+            "server_type": "stdio",
+            "server_config": {},
+            "enabled_tools": [],
+            "created_at": 0,
+            "updated_at": 0,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resolve_tool() {
+        let servers = vec![server("git"), server("git__lab"), server("my server")];
+
+        let (owner, tool) = resolve_tool(&servers, "git__status").unwrap();
+        assert_eq!((owner.name.as_str(), tool.as_str()), ("git", "status"));
+        let (owner, tool) = resolve_tool(&servers, "git__lab__issues").unwrap();
+        assert_eq!((owner.name.as_str(), tool.as_str()), ("git__lab", "issues"));
+        let (owner, tool) = resolve_tool(&servers, "my_server__read__file").unwrap();
+        assert_eq!((owner.name.as_str(), tool.as_str()), ("my server", "read__file"));
+        assert!(resolve_tool(&servers, "git__").is_none());
+        assert!(resolve_tool(&servers, "unknown__tool").is_none());
+    }
+
+    #[test]
+    fn test_local_host_check() {
+        assert!(is_local_host("127.0.0.1:39517", 39517));
+        assert!(is_local_host("localhost", 39517));
+        assert!(is_local_host("[::1]:39517", 39517));
+        assert!(!is_local_host("127.0.0.1:8080", 39517));
+        assert!(!is_local_host("evil.example.com:39517", 39517));
+        assert!(is_local_origin("http://localhost:6274"));
+        assert!(!is_local_origin("http://evil.example.com"));
+        assert!(!is_local_origin("null"));
+    }
+}
@@ -0,0 +1,160 @@
+//! Stdio shim of the gateway
+//!
+//! Clients launch the app binary as `ai-toolbox --mcp-gateway <tool key> --port <port>`.
+//! The shim forwards each JSON-RPC line from stdin to the running app's HTTP endpoint
+//! and writes the responses to stdout, so it needs no window, database or logging.
+
+use std::io::{BufRead, Write};
+
+use serde_json::{json, Value};
+
+use super::DEFAULT_GATEWAY_PORT;
+use crate::http_client;
+
+/// Timeout of one forwarded request; tool calls may run for minutes
+const REQUEST_TIMEOUT_SECS: u64 = 600;
+
+/// Arguments of a shim invocation
+pub struct ShimArgs {
+    pub client: String,
+    pub port: u16,
+}
+
+/// Parse the shim arguments, None when the app was not started as the shim
+pub fn shim_args() -> Option<ShimArgs> {
+    parse_shim_args(std::env::args().skip(1))
+}
+
+fn parse_shim_args(args: impl Iterator<Item = String>) -> Option<ShimArgs> {
+    let mut args = args.peekable();
+    if args.next().as_deref() != Some("--mcp-gateway") {
+        return None;
+    }
+    let client = args.next_if(|arg| !arg.starts_with("--")).unwrap_or_default();
+    let mut port = DEFAULT_GATEWAY_PORT;
+    while let Some(arg) = args.next() {
+        if arg == "--port" {
+            port = args.next().and_then(|p| p.parse().ok()).unwrap_or(port);
+        }
+    }
+    Some(ShimArgs { client, port })
+}
+
+/// Run the shim until stdin closes; returns the process exit code
+pub fn run_shim(args: ShimArgs) -> i32 {
+    let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("Failed to start runtime: {}", e);
+            return 1;
+        }
+    };
+    runtime.block_on(forward_stdio(args))
+}
+
+async fn forward_stdio(args: ShimArgs) -> i32 {
+    let client = match http_client::create_client_no_proxy(REQUEST_TIMEOUT_SECS) {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+    let url = match args.client.as_str() {
+        "" => format!("http://127.0.0.1:{}/mcp", args.port),
+        key => format!("http://127.0.0.1:{}/mcp/{}", args.port, key),
+    };
+
+    // Blocking stdin reader; requests are forwarded concurrently so that a long tool
+    // call does not hold up pings or cancellations
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut in_flight = tokio::task::JoinSet::new();
+    while let Some(line) = rx.recv().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let client = client.clone();
+        let url = url.clone();
+        let port = args.port;
+        in_flight.spawn(async move {
+            if let Some(response) = forward(&client, &url, port, &line).await {
+                write_line(&response);
+            }
+        });
+    }
+    while in_flight.join_next().await.is_some() {}
+    0
+}
+
+/// Forward one message; returns the line to write back, if any
+async fn forward(client: &reqwest::Client, url: &str, port: u16, line: &str) -> Option<String> {
+    let id = serde_json::from_str::<Value>(line)
+        .ok()
+        .and_then(|message| message.get("method").and(message.get("id")).cloned());
+
+    let result = client
+        .post(url)
+        .header("Content-Type", "application/json")
+        .header("Accept", "application/json, text/event-stream")
+        .body(line.to_string())
+        .send()
+        .await;
+
+    let error = match result {
+        Ok(response) if response.status().as_u16() == 202 => return None,
+        Ok(response) => match response.text().await {
+            Ok(body) if !body.trim().is_empty() => return Some(body.trim().to_string()),
+            Ok(_) => return None,
+            Err(e) => format!("Failed to read gateway response: {}", e),
+        },
+        Err(e) if e.is_connect() => format!(
+            "AI Toolbox MCP gateway is not running on port {}; open AI Toolbox and enable the gateway",
+            port
+        ),
+        Err(e) => format!("MCP gateway request failed: {}", e),
+    };
+
+    // Only requests get an error response; notifications are dropped
+    let id = id?;
+    let response = json!({ "jsonrpc": "2.0", "id": id, "error": { "code": -32603, "message": error } });
+    Some(response.to_string())
+}
+
+fn write_line(line: &str) {
+    let mut stdout = std::io::stdout().lock();
+    let _ = writeln!(stdout, "{}", line);
+    let _ = stdout.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Option<ShimArgs> {
+        parse_shim_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_shim_args() {
+        let args = parse(&["--mcp-gateway", "codex", "--port", "4000"]).unwrap();
+        assert_eq!((args.client.as_str(), args.port), ("codex", 4000));
+
+        let args = parse(&["--mcp-gateway", "--port", "4000"]).unwrap();
+        assert_eq!((args.client.as_str(), args.port), ("", 4000));
+
+        let args = parse(&["--mcp-gateway", "claude_code"]).unwrap();
+        assert_eq!(args.port, DEFAULT_GATEWAY_PORT);
+
+        assert!(parse(&[]).is_none());
+        assert!(parse(&["--minimized"]).is_none());
+    }
+}
@@ -0,0 +1,230 @@
+//! Upstream MCP servers run by the gateway
+//!
+//! Each managed server is started (or connected to) on first use and kept running for
+//! later requests. A server is restarted when its config changes or after a transport
+//! failure, and stopped once it is deleted.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+
+use super::super::client::McpClient;
+use super::super::types::McpServer;
+use crate::db::DbState;
+
+/// Timeout for starting a server and completing `initialize`
+const START_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Timeout for `tools/list`
+const LIST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Timeout for `tools/call`
+const CALL_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// State of an upstream server, reported in the gateway status
+#[derive(Clone, Debug, Serialize)]
+pub struct McpGatewayUpstream {
+    pub server_id: String,
+    pub server_name: String,
+    pub running: bool,
+    pub last_error: Option<String>,
+}
+
+enum Operation {
+    ListTools,
+    CallTool(Value),
+}
+
+/// One managed server as seen by the gateway
+pub(super) struct Upstream {
+    server: McpServer,
+    fingerprint: String,
+    client: Mutex<Option<McpClient>>,
+    running: AtomicBool,
+    last_error: std::sync::Mutex<Option<String>>,
+}
+
+/// Servers whose config differs in these fields need a restart
+fn fingerprint(server: &McpServer) -> String {
+    format!("{}:{}", server.server_type, server.server_config)
+}
+
+impl Upstream {
+    fn new(server: &McpServer) -> Self {
+        Self {
+            server: server.clone(),
+            fingerprint: fingerprint(server),
+            client: Mutex::new(None),
+            running: AtomicBool::new(false),
+            last_error: std::sync::Mutex::new(None),
+        }
+    }
+
+    /// Tools advertised by the server
+    pub(super) async fn list_tools(&self, state: &DbState) -> Result<Vec<Value>, String> {
+        match self.run(state, Operation::ListTools).await? {
+            Value::Array(tools) => Ok(tools),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Call a tool; returns the upstream JSON-RPC response (with `result` or `error`)
+    pub(super) async fn call_tool(
+        &self,
+        state: &DbState,
+        tool: &str,
+        arguments: Value,
+    ) -> Result<Value, String> {
+        let params = json!({ "name": tool, "arguments": arguments });
+        self.run(state, Operation::CallTool(params)).await
+    }
+
+    /// Run an operation on the started server; the server is started when needed and
+    /// dropped (stopped) when the operation fails or times out
+    async fn run(&self, state: &DbState, operation: Operation) -> Result<Value, String> {
+        let mut guard = self.client.lock().await;
+
+        if guard.is_none() {
+            let started = tokio::time::timeout(START_TIMEOUT, async {
+                let mut client = McpClient::connect(state, &self.server).await?;
+                client.initialize().await?;
+                Ok::<_, String>(client)
+            })
+            .await
+            .unwrap_or_else(|_| {
+                Err(format!(
+                    "MCP server did not start within {} seconds",
+                    START_TIMEOUT.as_secs()
+                ))
+            });
+            match started {
+                Ok(client) => {
+                    log::info!("MCP gateway started upstream server {}", self.server.name);
+                    *guard = Some(client);
+                    self.running.store(true, Ordering::SeqCst);
+                }
+                Err(e) => return Err(self.fail(e)),
+            }
+        }
+
+        let Some(client) = guard.as_mut() else {
+            return Err(self.fail("MCP server is not running".to_string()));
+        };
+        let timeout = match operation {
+            Operation::ListTools => LIST_TIMEOUT,
+            Operation::CallTool(_) => CALL_TIMEOUT,
+        };
+        let request = async {
+            match operation {
+                Operation::ListTools => client.list_all("tools/list", "tools").await.map(Value::Array),
+                Operation::CallTool(params) => client.request_raw("tools/call", params).await,
+            }
+        };
+        let outcome = tokio::time::timeout(timeout, request)
+            .await
+            .unwrap_or_else(|_| {
+                Err(format!(
+                    "MCP server did not respond within {} seconds",
+                    timeout.as_secs()
+                ))
+            });
+
+        match outcome {
+            Ok(value) => {
+                self.set_last_error(None);
+                Ok(value)
+            }
+            Err(e) => {
+                let e = match client.stderr_tail() {
+                    Some(stderr) => format!("{}\n{}", e, stderr),
+                    None => e,
+                };
+                if let Some(mut client) = guard.take() {
+                    client.close().await;
+                }
+                self.running.store(false, Ordering::SeqCst);
+                Err(self.fail(e))
+            }
+        }
+    }
+
+    fn fail(&self, error: String) -> String {
+        let error = format!("{}: {}", self.server.name, error);
+        self.set_last_error(Some(error.clone()));
+        error
+    }
+
+    fn set_last_error(&self, error: Option<String>) {
+        if let Ok(mut last_error) = self.last_error.lock() {
+            *last_error = error;
+        }
+    }
+
+    async fn stop(&self) {
+        if let Some(mut client) = self.client.lock().await.take() {
+            client.close().await;
+        }
+        self.running.store(false, Ordering::SeqCst);
+    }
+
+    fn status(&self) -> McpGatewayUpstream {
+        McpGatewayUpstream {
+            server_id: self.server.id.clone(),
+            server_name: self.server.name.clone(),
+            running: self.running.load(Ordering::SeqCst),
+            last_error: self.last_error.lock().ok().and_then(|e| e.clone()),
+        }
+    }
+}
+
+/// Upstream servers keyed by server id
+#[derive(Default)]
+pub(super) struct UpstreamPool {
+    upstreams: std::sync::Mutex<HashMap<String, Arc<Upstream>>>,
+}
+
+impl UpstreamPool {
+    /// Upstream of a server; a server whose config changed gets a fresh upstream and the
+    /// old one stops once its in-flight requests are done
+    pub(super) fn get(&self, server: &McpServer) -> Arc<Upstream> {
+        let mut upstreams = self.upstreams.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(upstream) = upstreams.get(&server.id) {
+            if upstream.fingerprint == fingerprint(server) {
+                return upstream.clone();
+            }
+            log::info!("MCP gateway restarting {} after a config change", server.name);
+        }
+        let upstream = Arc::new(Upstream::new(server));
+        upstreams.insert(server.id.clone(), upstream.clone());
+        upstream
+    }
+
+    /// Drop the upstreams of servers that no longer exist
+    pub(super) fn retain(&self, server_ids: &HashSet<&str>) {
+        let mut upstreams = self.upstreams.lock().unwrap_or_else(|e| e.into_inner());
+        upstreams.retain(|id, _| server_ids.contains(id.as_str()));
+    }
+
+    /// Stop every upstream server
+    pub(super) async fn stop_all(&self) {
+        let upstreams: Vec<Arc<Upstream>> = {
+            let mut upstreams = self.upstreams.lock().unwrap_or_else(|e| e.into_inner());
+            upstreams.drain().map(|(_, upstream)| upstream).collect()
+        };
+        for upstream in upstreams {
+            upstream.stop().await;
+        }
+    }
+
+    pub(super) fn status(&self) -> Vec<McpGatewayUpstream> {
+        let upstreams = self.upstreams.lock().unwrap_or_else(|e| e.into_inner());
+        let mut status: Vec<McpGatewayUpstream> = upstreams.values().map(|u| u.status()).collect();
+        status.sort_by(|a, b| a.server_name.cmp(&b.server_name));
+        status
+    }
+}
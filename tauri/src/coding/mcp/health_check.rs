@@ -0,0 +1,154 @@
+//! MCP server health check
+//!
+//! Verifies a configured server with a real protocol handshake: `initialize`,
+//! `notifications/initialized`, then `tools/list`, `prompts/list` and `resources/list`
+//! for the capabilities the server advertises.
+
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use super::client::McpClient;
+use super::types::{now_ms, McpHealthCheck, McpServer, McpToolInfo};
+use crate::db::DbState;
+
+/// Upper bound for the whole check, including server startup (e.g. npx downloads)
+const CHECK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Check an MCP server; returns the result to store on its record and, when the
+/// handshake succeeded and the server supports tools, its tool catalog
+pub async fn check_server(
+    state: &DbState,
+    server: &McpServer,
+) -> (McpHealthCheck, Option<Vec<McpToolInfo>>) {
+    let started = Instant::now();
+    let mut client: Option<McpClient> = None;
+
+    let outcome = tokio::time::timeout(CHECK_TIMEOUT, async {
+        let client = client.insert(McpClient::connect(state, server).await?);
+        handshake(client).await
+    })
+    .await
+    .unwrap_or_else(|_| {
+        Err(format!(
+            "MCP server did not respond within {} seconds",
+            CHECK_TIMEOUT.as_secs()
+        ))
+    });
+
+    let latency_ms = started.elapsed().as_millis() as u64;
+    let stderr_tail = client.as_ref().and_then(McpClient::stderr_tail);
+    if let Some(client) = client.as_mut() {
+        client.close().await;
+    }
+
+    let catalog = outcome.as_ref().ok().and_then(|h| h.tools.clone());
+    (health_from_outcome(outcome, latency_ms, stderr_tail), catalog)
+}
+
+/// Information collected during the handshake
+#[derive(Debug, Default)]
+struct Handshake {
+    server_name: Option<String>,
+    server_version: Option<String>,
+    protocol_version: Option<String>,
+    tools: Option<Vec<McpToolInfo>>,
+    prompts_count: Option<usize>,
+    resources_count: Option<usize>,
+}
+
+fn health_from_outcome(
+    outcome: Result<Handshake, String>,
+    latency_ms: u64,
+    stderr_tail: Option<String>,
+) -> McpHealthCheck {
+    match outcome {
+        Ok(handshake) => McpHealthCheck {
+            status: "ok".to_string(),
+            checked_at: now_ms(),
+            latency_ms,
+            server_name: handshake.server_name,
+            server_version: handshake.server_version,
+            protocol_version: handshake.protocol_version,
+            tools_count: handshake.tools.as_ref().map(Vec::len),
+            prompts_count: handshake.prompts_count,
+            resources_count: handshake.resources_count,
+            error_message: None,
+            stderr_tail,
+        },
+        Err(e) => McpHealthCheck {
+            status: "error".to_string(),
+            checked_at: now_ms(),
+            latency_ms,
+            error_message: Some(e),
+            stderr_tail,
+            ..Default::default()
+        },
+    }
+}
+
+async fn handshake(client: &mut McpClient) -> Result<Handshake, String> {
+    let init = client.initialize().await?;
+
+    let text_at = |pointer: &str| init.pointer(pointer).and_then(|v| v.as_str()).map(String::from);
+    let mut handshake = Handshake {
+        server_name: text_at("/serverInfo/name"),
+        server_version: text_at("/serverInfo/version"),
+        protocol_version: text_at("/protocolVersion"),
+        ..Default::default()
+    };
+
+    let has_capability = |name: &str| init.pointer(&format!("/capabilities/{}", name)).is_some();
+    if has_capability("tools") {
+        let tools = client.list_all("tools/list", "tools").await?;
+        handshake.tools = Some(tools.iter().filter_map(tool_info).collect());
+    }
+    if has_capability("prompts") {
+        handshake.prompts_count = Some(client.list_all("prompts/list", "prompts").await?.len());
+    }
+    if has_capability("resources") {
+        handshake.resources_count = Some(client.list_all("resources/list", "resources").await?.len());
+    }
+
+    Ok(handshake)
+}
+
+/// Catalog entry of a tool from `tools/list`
+fn tool_info(tool: &Value) -> Option<McpToolInfo> {
+    Some(McpToolInfo {
+        name: tool.get("name")?.as_str()?.to_string(),
+        description: tool.get("description").and_then(|v| v.as_str()).map(String::from),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_stdio_handshake() {
+        let script = r#"read l
+echo 'starting up'
+echo '{"jsonrpc":"2.0","id":1,"result":{"protocolVersion":"2025-06-18","capabilities":{"tools":{}},"serverInfo":{"name":"fake","version":"1.0"}}}'
+read l
+read l
+echo 'tool warning' >&2
+echo '{"jsonrpc":"2.0","id":2,"result":{"tools":[{"name":"a"},{"name":"b"}]}}'
+read l"#;
+        let config = json!({ "command": "sh", "args": ["-c", script] });
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let handshake = runtime.block_on(async {
+            let mut client = McpClient::spawn_stdio(&config).unwrap();
+            handshake(&mut client).await.unwrap()
+        });
+
+        assert_eq!(handshake.server_name.as_deref(), Some("fake"));
+        assert_eq!(handshake.protocol_version.as_deref(), Some("2025-06-18"));
+        let tools: Vec<String> = handshake.tools.unwrap().into_iter().map(|t| t.name).collect();
+        assert_eq!(tools, vec!["a", "b"]);
+        assert_eq!(handshake.prompts_count, None);
+    }
+}
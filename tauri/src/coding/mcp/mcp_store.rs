@@ -6,11 +6,11 @@ use serde_json::Value;
 
 use crate::DbState;
 use super::adapter::{
-    from_db_mcp_preferences, from_db_mcp_server, from_db_favorite_mcp, remove_sync_detail, set_sync_detail,
-    to_clean_mcp_server_payload, to_mcp_preferences_payload,
+    from_db_mcp_env_var, from_db_mcp_preferences, from_db_mcp_project, from_db_mcp_server, from_db_favorite_mcp, remove_sync_detail,
+    set_sync_detail, to_clean_mcp_server_payload, to_mcp_env_var_payload, to_mcp_preferences_payload, to_mcp_project_payload,
 };
 use super::command_normalize;
-use super::types::{McpPreferences, McpServer, McpSyncDetail, FavoriteMcp, now_ms};
+use super::types::{McpEnvVar, McpHealthCheck, McpPreferences, McpProject, McpServer, McpSyncDetail, McpToolInfo, FavoriteMcp, now_ms};
 
 // ==================== MCP Server CRUD ====================
 
@@ -260,6 +260,34 @@ pub async fn toggle_tool_enabled(
     Ok(is_now_enabled)
 }
 
+/// Store the result of a health check on an MCP server
+pub async fn update_health(state: &DbState, server_id: &str, health: &McpHealthCheck) -> Result<(), String> {
+    let db = state.0.lock().await;
+    let health_value = serde_json::to_value(health).map_err(|e| e.to_string())?;
+
+    db.query("UPDATE type::thing('mcp_server', $id) SET health = $health")
+        .bind(("id", server_id.to_string()))
+        .bind(("health", health_value))
+        .await
+        .map_err(|e| format!("Failed to update MCP server health: {}", e))?;
+
+    Ok(())
+}
+
+/// Store the tool catalog advertised by an MCP server
+pub async fn update_tool_catalog(state: &DbState, server_id: &str, catalog: &[McpToolInfo]) -> Result<(), String> {
+    let db = state.0.lock().await;
+    let catalog_value = serde_json::to_value(catalog).map_err(|e| e.to_string())?;
+
+    db.query("UPDATE type::thing('mcp_server', $id) SET tool_catalog = $catalog")
+        .bind(("id", server_id.to_string()))
+        .bind(("catalog", catalog_value))
+        .await
+        .map_err(|e| format!("Failed to update MCP tool catalog: {}", e))?;
+
+    Ok(())
+}
+
 // ==================== MCP Preferences ====================
 
 /// Get MCP preferences (singleton record)
@@ -365,3 +393,135 @@ pub async fn delete_favorite_mcp(state: &DbState, id: &str) -> Result<(), String
 
     Ok(())
 }
+
+// ==================== MCP Project CRUD ====================
+
+/// Get all MCP projects ordered by name
+pub async fn get_mcp_projects(state: &DbState) -> Result<Vec<McpProject>, String> {
+    let db = state.0.lock().await;
+
+    let mut result = db
+        .query("SELECT *, type::string(id) as id FROM mcp_project ORDER BY name ASC")
+        .await
+        .map_err(|e| format!("Failed to query MCP projects: {}", e))?;
+
+    let records: Vec<Value> = result.take(0).map_err(|e| e.to_string())?;
+    Ok(records.into_iter().map(from_db_mcp_project).collect())
+}
+
+/// Get a single MCP project by ID
+pub async fn get_mcp_project_by_id(state: &DbState, project_id: &str) -> Result<Option<McpProject>, String> {
+    let db = state.0.lock().await;
+
+    let mut result = db
+        .query(
+            "SELECT *, type::string(id) as id FROM mcp_project WHERE id = type::thing('mcp_project', $id) LIMIT 1",
+        )
+        .bind(("id", project_id.to_string()))
+        .await
+        .map_err(|e| format!("Failed to query MCP project: {}", e))?;
+
+    let records: Vec<Value> = result.take(0).map_err(|e| e.to_string())?;
+    Ok(records.first().map(|r| from_db_mcp_project(r.clone())))
+}
+
+/// Create or update an MCP project
+pub async fn upsert_mcp_project(state: &DbState, project: &McpProject) -> Result<String, String> {
+    let db = state.0.lock().await;
+    let payload = to_mcp_project_payload(project);
+
+    if project.id.is_empty() {
+        let id = uuid::Uuid::new_v4().to_string();
+        db.query("CREATE type::thing('mcp_project', $id) CONTENT $data")
+            .bind(("id", id.clone()))
+            .bind(("data", payload))
+            .await
+            .map_err(|e| format!("Failed to create MCP project: {}", e))?;
+        Ok(id)
+    } else {
+        let id = project.id.clone();
+        db.query("UPDATE type::thing('mcp_project', $id) CONTENT $data")
+            .bind(("id", id.clone()))
+            .bind(("data", payload))
+            .await
+            .map_err(|e| format!("Failed to update MCP project: {}", e))?;
+        Ok(id)
+    }
+}
+
+/// Delete an MCP project
+pub async fn delete_mcp_project(state: &DbState, project_id: &str) -> Result<(), String> {
+    let db = state.0.lock().await;
+
+    db.query("DELETE FROM mcp_project WHERE id = type::thing('mcp_project', $id)")
+        .bind(("id", project_id.to_string()))
+        .await
+        .map_err(|e| format!("Failed to delete MCP project: {}", e))?;
+
+    Ok(())
+}
+
+// ==================== MCP Variables ====================
+
+/// Get all MCP variables ordered by name
+pub async fn get_mcp_env_vars(state: &DbState) -> Result<Vec<McpEnvVar>, String> {
+    let db = state.0.lock().await;
+
+    let mut result = db
+        .query("SELECT *, type::string(id) as id FROM mcp_env_var ORDER BY name ASC")
+        .await
+        .map_err(|e| format!("Failed to query MCP variables: {}", e))?;
+
+    let records: Vec<Value> = result.take(0).map_err(|e| e.to_string())?;
+    Ok(records.into_iter().map(from_db_mcp_env_var).collect())
+}
+
+/// Get an MCP variable by name
+pub async fn get_mcp_env_var_by_name(state: &DbState, name: &str) -> Result<Option<McpEnvVar>, String> {
+    let db = state.0.lock().await;
+
+    let mut result = db
+        .query("SELECT *, type::string(id) as id FROM mcp_env_var WHERE name = $name LIMIT 1")
+        .bind(("name", name.to_string()))
+        .await
+        .map_err(|e| format!("Failed to query MCP variable by name: {}", e))?;
+
+    let records: Vec<Value> = result.take(0).map_err(|e| e.to_string())?;
+    Ok(records.first().map(|v| from_db_mcp_env_var(v.clone())))
+}
+
+/// Create or update an MCP variable
+pub async fn upsert_mcp_env_var(state: &DbState, var: &McpEnvVar) -> Result<String, String> {
+    let db = state.0.lock().await;
+    let payload = to_mcp_env_var_payload(var);
+
+    if var.id.is_empty() {
+        let id = uuid::Uuid::new_v4().to_string();
+        db.query("CREATE type::thing('mcp_env_var', $id) CONTENT $data")
+            .bind(("id", id.clone()))
+            .bind(("data", payload))
+            .await
+            .map_err(|e| format!("Failed to create MCP variable: {}", e))?;
+        Ok(id)
+    } else {
+        let id = var.id.clone();
+        db.query("UPDATE type::thing('mcp_env_var', $id) CONTENT $data")
+            .bind(("id", id.clone()))
+            .bind(("data", payload))
+            .await
+            .map_err(|e| format!("Failed to update MCP variable: {}", e))?;
+        Ok(id)
+    }
+}
+
+/// Delete an MCP variable
+pub async fn delete_mcp_env_var(state: &DbState, id: &str) -> Result<(), String> {
+    let db = state.0.lock().await;
+
+    db.query("DELETE FROM mcp_env_var WHERE id = type::thing('mcp_env_var', $id)")
+        .bind(("id", id.to_string()))
+        .await
+        .map_err(|e| format!("Failed to delete MCP variable: {}", e))?;
+
+    Ok(())
+}
@@ -7,10 +7,16 @@ pub mod types;
 pub mod adapter;
 pub mod mcp_store;
 pub mod config_sync;
+pub mod project_sync;
 pub mod format_configs;
 pub mod opencode_path;
 pub mod commands;
 pub mod tray_support;
 pub mod command_normalize;
+pub mod client;
+pub mod env_vars;
+pub mod health_check;
+pub mod gateway;
+pub mod yaml_config;
 
 pub use commands::*;
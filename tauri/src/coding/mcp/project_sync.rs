@@ -0,0 +1,217 @@
+//! Project-level MCP config synchronization
+//!
+//! Writes selected MCP servers into the project-scoped config files that tools read
+//! from a repository root (`.mcp.json`, `.cursor/mcp.json`, `.vscode/mcp.json`, ...).
+//! Reuses the JSON/TOML builders and format conversions of the global sync.
+//!
+//! These files are usually committed, so `${NAME}` placeholders are never resolved
+//! here: they are written in the tool's own variable syntax for the tool to expand, and
+//! servers using variables are refused for tools that cannot expand them.
+
+use std::path::{Path, PathBuf};
+
+use super::config_sync::{remove_server_from_file, sync_server_to_file};
+use super::env_vars::{has_placeholders, rewrite_placeholders};
+use super::types::{McpProject, McpProjectSyncEntry, McpServer, now_ms};
+
+/// Project-level MCP config file of a tool (path relative to the project root)
+#[derive(Clone, Debug)]
+pub struct ProjectMcpTarget {
+    pub tool_key: &'static str,
+    pub relative_path: &'static str,
+    pub format: &'static str, // "json" | "jsonc" | "toml"
+    pub field: &'static str,
+    /// Prefix and suffix of an environment variable reference the tool expands in this
+    /// file, None when it expands none
+    pub env_syntax: Option<(&'static str, &'static str)>,
+}
+
+/// Tools that read MCP servers from a project-level config file
+pub const PROJECT_MCP_TARGETS: &[ProjectMcpTarget] = &[
+    ProjectMcpTarget {
+        tool_key: "claude_code",
+        relative_path: ".mcp.json",
+        format: "json",
+        field: "mcpServers",
+        env_syntax: Some(("${", "}")),
+    },
+    ProjectMcpTarget {
+        tool_key: "cursor",
+        relative_path: ".cursor/mcp.json",
+        format: "json",
+        field: "mcpServers",
+        env_syntax: Some(("${env:", "}")),
+    },
+    ProjectMcpTarget {
+        tool_key: "github_copilot",
+        relative_path: ".vscode/mcp.json",
+        format: "json",
+        field: "servers",
+        env_syntax: Some(("${env:", "}")),
+    },
+    ProjectMcpTarget {
+        tool_key: "gemini_cli",
+        relative_path: ".gemini/settings.json",
+        format: "json",
+        field: "mcpServers",
+        env_syntax: Some(("${", "}")),
+    },
+    ProjectMcpTarget {
+        tool_key: "opencode",
+        relative_path: "opencode.json",
+        format: "json",
+        field: "mcp",
+        env_syntax: Some(("{env:", "}")),
+    },
+    ProjectMcpTarget {
+        tool_key: "roo_code",
+        relative_path: ".roo/mcp.json",
+        format: "json",
+        field: "mcpServers",
+        env_syntax: None,
+    },
+    ProjectMcpTarget {
+        tool_key: "kilo_code",
+        relative_path: ".kilocode/mcp.json",
+        format: "json",
+        field: "mcpServers",
+        env_syntax: None,
+    },
+];
+
+/// Get the project-level target of a tool
+pub fn project_target_by_key(tool_key: &str) -> Option<&'static ProjectMcpTarget> {
+    PROJECT_MCP_TARGETS.iter().find(|t| t.tool_key == tool_key)
+}
+
+/// Absolute path of a tool's project-level config file
+pub fn project_config_path(project_root: &str, target: &ProjectMcpTarget) -> PathBuf {
+    Path::new(project_root).join(target.relative_path)
+}
+
+/// Sync the selected servers of a project into each selected tool's project-level file.
+///
+/// Entries written by the previous sync whose server or tool is no longer selected
+/// are removed first. Returns the new sync entries to store on the project.
+pub fn sync_project(project: &McpProject, servers: &[McpServer]) -> Vec<McpProjectSyncEntry> {
+    let selected: Vec<&McpServer> = project
+        .server_ids
+        .iter()
+        .filter_map(|id| servers.iter().find(|s| &s.id == id))
+        .collect();
+    let targets: Vec<&ProjectMcpTarget> = project
+        .tools
+        .iter()
+        .filter_map(|key| project_target_by_key(key))
+        .collect();
+
+    let stale: Vec<McpProjectSyncEntry> = project
+        .sync_entries
+        .iter()
+        .filter(|entry| {
+            !targets.iter().any(|t| t.tool_key == entry.tool)
+                || !selected.iter().any(|s| s.name == entry.server_name)
+        })
+        .cloned()
+        .collect();
+    remove_project_entries(&project.path, &stale);
+
+    let mut entries = Vec::new();
+    for target in targets {
+        let config_path = project_config_path(&project.path, target);
+        for server in &selected {
+            let result = project_server(server, target).and_then(|server| {
+                sync_server_to_file(&server, target.tool_key, &config_path, target.format, target.field)
+            });
+            entries.push(McpProjectSyncEntry {
+                tool: target.tool_key.to_string(),
+                server_name: server.name.clone(),
+                status: if result.is_ok() { "ok" } else { "error" }.to_string(),
+                synced_at: Some(now_ms()),
+                error_message: result.err(),
+            });
+        }
+    }
+    entries
+}
+
+/// Server as written to a project-level file: placeholders in the target's syntax
+fn project_server(server: &McpServer, target: &ProjectMcpTarget) -> Result<McpServer, String> {
+    if !has_placeholders(server) {
+        return Ok(server.clone());
+    }
+    match target.env_syntax {
+        Some((prefix, suffix)) => Ok(rewrite_placeholders(server, prefix, suffix)),
+        None => Err(format!(
+            "{} does not expand variables in {}, server {} uses ${{NAME}} placeholders",
+            target.tool_key, target.relative_path, server.name
+        )),
+    }
+}
+
+/// Remove previously synced servers from a project's config files
+pub fn remove_project_entries(project_root: &str, entries: &[McpProjectSyncEntry]) {
+    for entry in entries {
+        let Some(target) = project_target_by_key(&entry.tool) else {
+            continue;
+        };
+        let config_path = project_config_path(project_root, target);
+        if let Err(e) = remove_server_from_file(&entry.server_name, &config_path, target.format, target.field) {
+            log::warn!(
+                "Failed to remove MCP server {} from {}: {}",
+                entry.server_name,
+                config_path.display(),
+                e
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_project_sync_keeps_placeholders() {
+        let dir = std::env::temp_dir().join(format!("ai-toolbox-project-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let server: McpServer = serde_json::from_value(json!({
+            "id": "1",
+            "name": "github",
+            "server_type": "stdio",
+            "server_config": {
+                "command": "npx",
+                "env": { "GITHUB_TOKEN": "${GITHUB_TOKEN}" },
+            },
+            "enabled_tools": [],
+            "created_at": 0,
+            "updated_at": 0,
+        }))
+        .unwrap();
+        let project = McpProject {
+            id: "p".to_string(),
+            name: "p".to_string(),
+            path: dir.to_string_lossy().to_string(),
+            tools: vec!["claude_code".to_string(), "github_copilot".to_string(), "roo_code".to_string()],
+            server_ids: vec!["1".to_string()],
+            sync_entries: vec![],
+            created_at: 0,
+            updated_at: 0,
+        };
+
+        let entries = sync_project(&project, &[server]);
+        let status: Vec<_> = entries.iter().map(|e| (e.tool.as_str(), e.status.as_str())).collect();
+        assert_eq!(
+            status,
+            vec![("claude_code", "ok"), ("github_copilot", "ok"), ("roo_code", "error")]
+        );
+
+        let claude = std::fs::read_to_string(dir.join(".mcp.json")).unwrap();
+        assert!(claude.contains("\"${GITHUB_TOKEN}\""));
+        let vscode = std::fs::read_to_string(dir.join(".vscode/mcp.json")).unwrap();
+        assert!(vscode.contains("\"${env:GITHUB_TOKEN}\""));
+        assert!(!dir.join(".roo/mcp.json").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
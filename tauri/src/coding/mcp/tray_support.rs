@@ -102,7 +102,8 @@ pub async fn apply_mcp_tool_toggle<R: Runtime>(
 
     // Sync or remove based on new state
     if is_enabled {
-        match super::config_sync::sync_server_to_tool(&server, &tool) {
+        let env = super::env_vars::EnvResolver::load(&state, None).await?;
+        match super::config_sync::sync_server_to_tool(&server, &tool, &env) {
             Ok(detail) => {
                 mcp_store::update_sync_detail(&state, server_id, &detail).await?;
             }
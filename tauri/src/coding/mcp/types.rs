@@ -2,6 +2,8 @@
 //!
 //! Contains types for MCP server configuration and synchronization.
 
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
@@ -72,10 +74,92 @@ pub struct McpServer {
     pub tags: Vec<String>,
     #[serde(default)]
     pub sort_index: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health: Option<McpHealthCheck>,
+    /// Tools advertised by the server in its last successful health check
+    #[serde(default)]
+    pub tool_catalog: Vec<McpToolInfo>,
+    /// Per-client tool filters, keyed by tool key (e.g. "codex")
+    #[serde(default)]
+    pub tool_filters: BTreeMap<String, McpToolFilter>,
     pub created_at: i64,
     pub updated_at: i64,
 }
 
+impl McpServer {
+    /// Tool filter configured for a client, None when the client sees every tool
+    pub fn tool_filter(&self, tool_key: &str) -> Option<&McpToolFilter> {
+        self.tool_filters.get(tool_key).filter(|f| !f.is_empty())
+    }
+}
+
+/// A tool advertised by an MCP server in `tools/list`
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct McpToolInfo {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Allow / deny list of MCP tool names for one client
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct McpToolFilter {
+    /// Only these tools are exposed; empty means all tools
+    #[serde(default)]
+    pub allowed: Vec<String>,
+    /// These tools are hidden
+    #[serde(default)]
+    pub denied: Vec<String>,
+}
+
+impl McpToolFilter {
+    pub fn is_empty(&self) -> bool {
+        self.allowed.is_empty() && self.denied.is_empty()
+    }
+
+    /// Whether a tool passes the filter
+    pub fn allows(&self, tool: &str) -> bool {
+        let tool = tool.to_string();
+        !self.denied.contains(&tool) && (self.allowed.is_empty() || self.allowed.contains(&tool))
+    }
+
+    /// Tools to hide for clients that only support a deny list: the denied tools plus,
+    /// when an allow list is set, every catalog tool outside of it
+    pub fn hidden_tools(&self, catalog: &[McpToolInfo]) -> Vec<String> {
+        let mut hidden = self.denied.clone();
+        if !self.allowed.is_empty() {
+            hidden.extend(
+                catalog
+                    .iter()
+                    .filter(|tool| !self.allowed.contains(&tool.name))
+                    .map(|tool| tool.name.clone()),
+            );
+        }
+        hidden.sort();
+        hidden.dedup();
+        hidden
+    }
+}
+
+/// Result of the last MCP protocol handshake with a server
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct McpHealthCheck {
+    pub status: String, // "ok" | "error"
+    pub checked_at: i64,
+    /// Time from launch / connect until all list requests answered
+    pub latency_ms: u64,
+    pub server_name: Option<String>,
+    pub server_version: Option<String>,
+    pub protocol_version: Option<String>,
+    /// Counts are None when the server does not support the capability
+    pub tools_count: Option<usize>,
+    pub prompts_count: Option<usize>,
+    pub resources_count: Option<usize>,
+    pub error_message: Option<String>,
+    /// Last lines written to stderr by a stdio server
+    pub stderr_tail: Option<String>,
+}
+
 /// MCP Server sync detail for a specific tool
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct McpSyncDetail {
@@ -97,6 +181,9 @@ pub struct McpServerDto {
     pub description: Option<String>,
     pub tags: Vec<String>,
     pub sort_index: i32,
+    pub health: Option<McpHealthCheck>,
+    pub tool_catalog: Vec<McpToolInfo>,
+    pub tool_filters: BTreeMap<String, McpToolFilter>,
     pub created_at: i64,
     pub updated_at: i64,
 }
@@ -132,6 +219,7 @@ pub struct UpdateMcpServerInput {
     pub enabled_tools: Option<Vec<String>>,
     pub description: Option<String>,
     pub tags: Option<Vec<String>>,
+    pub tool_filters: Option<BTreeMap<String, McpToolFilter>>,
 }
 
 /// MCP preferences (singleton record)
@@ -143,9 +231,19 @@ pub struct McpPreferences {
     pub preferred_tools: Vec<String>,
     #[serde(default)]
     pub favorites_initialized: bool,
+    /// Sync a single MCP gateway entry to clients instead of every server
+    #[serde(default)]
+    pub gateway_enabled: bool,
+    /// Localhost port of the gateway's HTTP endpoint
+    #[serde(default = "default_gateway_port")]
+    pub gateway_port: u16,
     pub updated_at: i64,
 }
 
+fn default_gateway_port() -> u16 {
+    crate::coding::mcp::gateway::DEFAULT_GATEWAY_PORT
+}
+
 impl Default for McpPreferences {
     fn default() -> Self {
         Self {
@@ -153,6 +251,8 @@ impl Default for McpPreferences {
             show_in_tray: false,
             preferred_tools: Vec::new(),
             favorites_initialized: false,
+            gateway_enabled: false,
+            gateway_port: default_gateway_port(),
             updated_at: 0,
         }
     }
@@ -236,6 +336,97 @@ pub struct FavoriteMcpInput {
     pub tags: Vec<String>,
 }
 
+/// Project root whose project-level MCP config files are managed
+/// (`.mcp.json`, `.cursor/mcp.json`, `.vscode/mcp.json`, ...)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct McpProject {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    /// Tool keys whose project-level config files are written
+    pub tools: Vec<String>,
+    /// MCP servers synced into this project
+    pub server_ids: Vec<String>,
+    /// Entries written by the last sync, used to remove deselected servers
+    #[serde(default)]
+    pub sync_entries: Vec<McpProjectSyncEntry>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Sync state of one server in one project-level config file
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct McpProjectSyncEntry {
+    pub tool: String,
+    pub server_name: String,
+    pub status: String, // "ok" | "error"
+    pub synced_at: Option<i64>,
+    pub error_message: Option<String>,
+}
+
+/// DTO for MCP project (frontend display)
+#[derive(Debug, Serialize)]
+pub struct McpProjectDto {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    pub tools: Vec<String>,
+    pub server_ids: Vec<String>,
+    pub sync_entries: Vec<McpProjectSyncEntry>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Variable referenced as `${NAME}` in MCP server configs, resolved at sync time
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct McpEnvVar {
+    pub id: String,
+    pub name: String,
+    pub value: String,
+    /// Value and overrides are kept in the secrets vault
+    #[serde(default)]
+    pub secret: bool,
+    /// Values for specific sync targets, keyed by target id (`ssh:<connection id>`,
+    /// `wsl:<distro>`)
+    #[serde(default)]
+    pub overrides: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Input for creating/updating an MCP variable
+#[derive(Clone, Debug, Deserialize)]
+pub struct McpEnvVarInput {
+    pub name: String,
+    pub value: String,
+    #[serde(default)]
+    pub secret: bool,
+    #[serde(default)]
+    pub overrides: BTreeMap<String, String>,
+    pub description: Option<String>,
+}
+
+/// Input for creating/updating an MCP project
+#[derive(Clone, Debug, Deserialize)]
+pub struct McpProjectInput {
+    pub name: String,
+    pub path: String,
+    #[serde(default)]
+    pub tools: Vec<String>,
+    #[serde(default)]
+    pub server_ids: Vec<String>,
+}
+
+/// Tool that supports a project-level MCP config file
+#[derive(Debug, Serialize)]
+pub struct McpProjectTargetDto {
+    pub tool_key: String,
+    pub display_name: String,
+    pub relative_path: String,
+}
+
 /// Helper function to get current timestamp in milliseconds
 pub fn now_ms() -> i64 {
     let now = std::time::SystemTime::now()
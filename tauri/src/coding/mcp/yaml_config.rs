@@ -0,0 +1,555 @@
+//! YAML MCP configs (Goose `extensions`, Continue `mcpServers`)
+//!
+//! serde_yaml can only write a whole document back, dropping comments and the layout
+//! of everything else in the file. Sync and removal therefore edit the text of the
+//! top-level servers block: only the entry of the server being synced is re-rendered
+//! and the rest of the file is kept as written. When the block is in a shape the
+//! editor does not understand (flow style, anchors), the block is re-rendered from the
+//! parsed value instead.
+
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+use super::config_sync::{build_json_server_config, parse_server_config};
+use super::types::{now_ms, McpServer};
+use crate::coding::config_writer::write_config_file;
+use crate::coding::tools::McpFormatConfig;
+
+/// Child indentation used for new blocks
+const INDENT: usize = 2;
+
+/// Sync a server to a YAML config file
+pub fn sync_server_to_yaml(
+    config_path: &PathBuf,
+    server: &McpServer,
+    tool_key: &str,
+    field: &str,
+    format_config: Option<&McpFormatConfig>,
+) -> Result<(), String> {
+    let content = if config_path.exists() {
+        std::fs::read_to_string(config_path)
+            .map_err(|e| format!("Failed to read config file: {}", e))?
+    } else {
+        String::new()
+    };
+
+    if let Some(parent) = config_path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+
+    let content = sync_server_to_content(&content, server, tool_key, field, format_config)?;
+    write_config_file(config_path, content)
+        .map_err(|e| format!("Failed to write config file: {}", e))?;
+
+    Ok(())
+}
+
+/// Add or replace the entry of a server in YAML config content
+pub(super) fn sync_server_to_content(
+    content: &str,
+    server: &McpServer,
+    tool_key: &str,
+    field: &str,
+    format_config: Option<&McpFormatConfig>,
+) -> Result<String, String> {
+    let server_config = build_json_server_config(server, tool_key, format_config)?;
+    upsert_entry(content, field, &server.name, server_config, format_config)
+}
+
+/// Remove a server from a YAML config file
+pub fn remove_server_from_yaml(
+    config_path: &PathBuf,
+    server_name: &str,
+    field: &str,
+) -> Result<(), String> {
+    if !config_path.exists() {
+        return Ok(()); // Nothing to remove
+    }
+
+    let content = std::fs::read_to_string(config_path)
+        .map_err(|e| format!("Failed to read config file: {}", e))?;
+    let Some(new_content) = remove_entry(&content, field, server_name)? else {
+        return Ok(());
+    };
+    write_config_file(config_path, new_content)
+        .map_err(|e| format!("Failed to write config file: {}", e))?;
+
+    Ok(())
+}
+
+/// Import servers from YAML config content; the servers are either a map keyed by
+/// server name or a list of entries with a `name`
+pub fn import_servers_from_yaml(
+    content: &str,
+    field: &str,
+    format_config: Option<&McpFormatConfig>,
+) -> Result<Vec<McpServer>, String> {
+    if content.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    let config: Value = serde_yaml::from_str(content)
+        .map_err(|e| format!("Failed to parse YAML config: {}", e))?;
+    let name_key = name_key(format_config);
+
+    let now = now_ms();
+    let servers = match config.get(field) {
+        Some(Value::Object(entries)) => entries
+            .iter()
+            .filter_map(|(name, server_config)| parse_server_config(name, server_config, format_config, now))
+            .collect(),
+        Some(Value::Array(entries)) => entries
+            .iter()
+            .filter_map(|server_config| {
+                let name = server_config.get(name_key)?.as_str()?;
+                parse_server_config(name, server_config, format_config, now)
+            })
+            .collect(),
+        _ => vec![],
+    };
+
+    Ok(servers)
+}
+
+fn name_key(format_config: Option<&McpFormatConfig>) -> &'static str {
+    format_config.and_then(|c| c.name_field).unwrap_or("name")
+}
+
+fn parse_document(content: &str) -> Result<serde_yaml::Value, String> {
+    if content.trim().is_empty() {
+        return Ok(serde_yaml::Value::Null);
+    }
+    let doc: serde_yaml::Value = serde_yaml::from_str(content)
+        .map_err(|e| format!("Failed to parse YAML config: {}", e))?;
+    match doc {
+        serde_yaml::Value::Mapping(_) | serde_yaml::Value::Null => Ok(doc),
+        _ => Err("Config is not a YAML mapping".to_string()),
+    }
+}
+
+/// Servers in a parsed servers field
+enum Entries {
+    Map(Vec<String>),
+    List(Vec<String>),
+}
+
+impl Entries {
+    fn read(value: Option<&serde_yaml::Value>, name_key: &str, list_default: bool) -> Self {
+        match value {
+            Some(serde_yaml::Value::Mapping(map)) => Entries::Map(
+                map.keys()
+                    .map(|k| k.as_str().unwrap_or_default().to_string())
+                    .collect(),
+            ),
+            Some(serde_yaml::Value::Sequence(items)) => Entries::List(
+                items
+                    .iter()
+                    .map(|item| {
+                        item.get(name_key)
+                            .and_then(|v| v.as_str())
+                            .unwrap_or_default()
+                            .to_string()
+                    })
+                    .collect(),
+            ),
+            _ if list_default => Entries::List(Vec::new()),
+            _ => Entries::Map(Vec::new()),
+        }
+    }
+
+    fn names(&self) -> &[String] {
+        match self {
+            Entries::Map(names) | Entries::List(names) => names,
+        }
+    }
+
+    fn is_list(&self) -> bool {
+        matches!(self, Entries::List(_))
+    }
+
+    /// Render one entry, indented by `indent`
+    fn render(&self, name: &str, server_config: &Value, indent: usize) -> Result<Vec<String>, String> {
+        let value = if self.is_list() {
+            Value::Array(vec![server_config.clone()])
+        } else {
+            let mut entry = serde_json::Map::new();
+            entry.insert(name.to_string(), server_config.clone());
+            Value::Object(entry)
+        };
+        let rendered = serde_yaml::to_string(&value)
+            .map_err(|e| format!("Failed to serialize config: {}", e))?;
+        let pad = " ".repeat(indent);
+        Ok(rendered.lines().map(|line| format!("{}{}", pad, line)).collect())
+    }
+}
+
+/// Add or replace the entry of a server in the `field` block
+fn upsert_entry(
+    content: &str,
+    field: &str,
+    name: &str,
+    server_config: Value,
+    format_config: Option<&McpFormatConfig>,
+) -> Result<String, String> {
+    let mut doc = parse_document(content)?;
+    let name_key = name_key(format_config);
+    let list_default = format_config.is_some_and(|c| c.list_entries);
+    let entries = Entries::read(doc.get(field), name_key, list_default);
+
+    // List entries are identified by their name, put it first
+    let server_config = match (&entries, server_config) {
+        (Entries::List(_), Value::Object(map)) => {
+            let mut entry = serde_json::Map::new();
+            entry.insert(name_key.to_string(), Value::String(name.to_string()));
+            entry.extend(map.into_iter().filter(|(k, _)| k != name_key));
+            Value::Object(entry)
+        }
+        (_, server_config) => server_config,
+    };
+    let position = entries.names().iter().position(|n| n == name);
+
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let Some(block) = find_block(&lines, field) else {
+        if doc.get(field).is_some_and(|v| !v.is_null()) {
+            set_entry(&mut doc, field, &entries, name, name_key, Some(&server_config))?;
+            return serialize_document(&doc);
+        }
+        // No servers yet: append the block
+        while lines.last().is_some_and(|l| l.trim().is_empty()) {
+            lines.pop();
+        }
+        lines.push(format!("{}:", field));
+        lines.extend(entries.render(name, &server_config, INDENT)?);
+        return Ok(join_lines(&lines));
+    };
+
+    match block_entries(&lines, &block, entries.names().len()) {
+        Some(spans) => {
+            let indent = spans.first().map(|s| s.indent).unwrap_or(INDENT);
+            let rendered = entries.render(name, &server_config, indent)?;
+            match position {
+                Some(i) => {
+                    lines.splice(spans[i].start..spans[i].end, rendered);
+                }
+                None => {
+                    let at = spans.last().map(|s| s.end).unwrap_or(block.start + 1);
+                    lines.splice(at..at, rendered);
+                    lines[block.start] = format!("{}:", field);
+                }
+            }
+            Ok(join_lines(&lines))
+        }
+        None => {
+            set_entry(&mut doc, field, &entries, name, name_key, Some(&server_config))?;
+            replace_block(&mut lines, &block, field, &doc)?;
+            Ok(join_lines(&lines))
+        }
+    }
+}
+
+/// Remove the entry of a server from the `field` block; None when it is not there
+fn remove_entry(content: &str, field: &str, name: &str) -> Result<Option<String>, String> {
+    let mut doc = parse_document(content)?;
+    let entries = Entries::read(doc.get(field), "name", false);
+    let Some(position) = entries.names().iter().position(|n| n == name) else {
+        return Ok(None);
+    };
+
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let Some(block) = find_block(&lines, field) else {
+        set_entry(&mut doc, field, &entries, name, "name", None)?;
+        return serialize_document(&doc).map(Some);
+    };
+
+    match block_entries(&lines, &block, entries.names().len()) {
+        Some(spans) => {
+            lines.drain(spans[position].start..spans[position].end);
+            if spans.len() == 1 {
+                let empty = if entries.is_list() { "[]" } else { "{}" };
+                lines[block.start] = format!("{}: {}", field, empty);
+            }
+        }
+        None => {
+            set_entry(&mut doc, field, &entries, name, "name", None)?;
+            replace_block(&mut lines, &block, field, &doc)?;
+        }
+    }
+    Ok(Some(join_lines(&lines)))
+}
+
+/// Set (`Some`) or remove (`None`) an entry in the parsed document
+fn set_entry(
+    doc: &mut serde_yaml::Value,
+    field: &str,
+    entries: &Entries,
+    name: &str,
+    name_key: &str,
+    server_config: Option<&Value>,
+) -> Result<(), String> {
+    let entry = server_config
+        .map(serde_yaml::to_value)
+        .transpose()
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    if doc.is_null() {
+        *doc = serde_yaml::Value::Mapping(Default::default());
+    }
+    let root = doc.as_mapping_mut().ok_or("Config is not a YAML mapping")?;
+    let key = serde_yaml::Value::String(field.to_string());
+    let servers = root.entry(key).or_insert(serde_yaml::Value::Null);
+
+    if entries.is_list() {
+        if !servers.is_sequence() {
+            *servers = serde_yaml::Value::Sequence(Vec::new());
+        }
+        let items = servers.as_sequence_mut().ok_or("Servers field is not a YAML list")?;
+        let position = items
+            .iter()
+            .position(|item| item.get(name_key).and_then(|v| v.as_str()) == Some(name));
+        match (position, entry) {
+            (Some(i), Some(entry)) => items[i] = entry,
+            (None, Some(entry)) => items.push(entry),
+            (Some(i), None) => {
+                items.remove(i);
+            }
+            (None, None) => {}
+        }
+    } else {
+        if !servers.is_mapping() {
+            *servers = serde_yaml::Value::Mapping(Default::default());
+        }
+        let map = servers.as_mapping_mut().ok_or("Servers field is not a YAML mapping")?;
+        match entry {
+            Some(entry) => {
+                map.insert(serde_yaml::Value::String(name.to_string()), entry);
+            }
+            None => {
+                map.remove(name);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn serialize_document(doc: &serde_yaml::Value) -> Result<String, String> {
+    serde_yaml::to_string(doc).map_err(|e| format!("Failed to serialize config: {}", e))
+}
+
+/// Lines of a top-level block: `start` is the `field:` line, `end` is exclusive
+struct Block {
+    start: usize,
+    end: usize,
+}
+
+/// Lines of one entry in a block
+struct Span {
+    start: usize,
+    end: usize,
+    indent: usize,
+}
+
+fn is_blank_or_comment(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.is_empty() || trimmed.starts_with('#')
+}
+
+/// Find the top-level `field:` block. None when the key is missing or has an inline
+/// value other than an empty collection
+fn find_block(lines: &[String], field: &str) -> Option<Block> {
+    let keys = [
+        format!("{}:", field),
+        format!("\"{}\":", field),
+        format!("'{}':", field),
+    ];
+    let start = lines.iter().position(|line| {
+        keys.iter().any(|key| {
+            line.strip_prefix(key.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t']))
+        })
+    })?;
+
+    let header = &lines[start];
+    let inline = header[header.find(':')? + 1..]
+        .split(" #")
+        .next()
+        .unwrap_or_default()
+        .trim();
+    if !matches!(inline, "" | "{}" | "[]" | "null" | "~") {
+        return None;
+    }
+
+    let mut end = start + 1;
+    while end < lines.len() {
+        let line = &lines[end];
+        let nested = line.trim().is_empty()
+            || line.starts_with([' ', '\t', '#'])
+            || (line.starts_with('-') && !line.starts_with("---"));
+        if !nested {
+            break;
+        }
+        end += 1;
+    }
+    while end > start + 1 && is_blank_or_comment(&lines[end - 1]) {
+        end -= 1;
+    }
+    if !inline.is_empty() && end > start + 1 {
+        return None;
+    }
+    Some(Block { start, end })
+}
+
+/// Split a block into its entries; None when the entries found in the text do not
+/// match the `expected` number of parsed entries
+fn block_entries(lines: &[String], block: &Block, expected: usize) -> Option<Vec<Span>> {
+    let content_lines: Vec<usize> = (block.start + 1..block.end)
+        .filter(|&i| !is_blank_or_comment(&lines[i]))
+        .collect();
+    let indent_of = |i: usize| lines[i].len() - lines[i].trim_start_matches(' ').len();
+    let Some(indent) = content_lines.first().map(|&i| indent_of(i)) else {
+        return (expected == 0).then(Vec::new);
+    };
+
+    let starts: Vec<usize> = content_lines
+        .into_iter()
+        .filter(|&i| indent_of(i) == indent)
+        .collect();
+    if starts.len() != expected {
+        return None;
+    }
+
+    let spans = starts
+        .iter()
+        .enumerate()
+        .map(|(n, &start)| {
+            let mut end = starts.get(n + 1).copied().unwrap_or(block.end);
+            while end > start + 1 && is_blank_or_comment(&lines[end - 1]) {
+                end -= 1;
+            }
+            Span { start, end, indent }
+        })
+        .collect();
+    Some(spans)
+}
+
+/// Re-render a whole block from the parsed document
+fn replace_block(
+    lines: &mut Vec<String>,
+    block: &Block,
+    field: &str,
+    doc: &serde_yaml::Value,
+) -> Result<(), String> {
+    let mut single = serde_yaml::Mapping::new();
+    if let Some(value) = doc.get(field) {
+        single.insert(serde_yaml::Value::String(field.to_string()), value.clone());
+    }
+    let rendered = serde_yaml::to_string(&single)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    lines.splice(block.start..block.end, rendered.lines().map(str::to_string));
+    Ok(())
+}
+
+fn join_lines(lines: &[String]) -> String {
+    let mut content = lines.join("\n");
+    content.push('\n');
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coding::mcp::format_configs::{CONTINUE_FORMAT, GOOSE_FORMAT};
+    use serde_json::json;
+
+    fn server(name: &str, config: Value) -> McpServer {
+        serde_json::from_value(json!({
+            "id": "1",
+            "name": name,
+            "server_type": "stdio",
+            "server_config": config,
+            "enabled_tools": [],
+            "created_at": 0,
+            "updated_at": 0,
+        }))
+        .unwrap()
+    }
+
+    fn upsert(content: &str, server: &McpServer, tool_key: &str, field: &str, config: &McpFormatConfig) -> String {
+        let server_config = build_json_server_config(server, tool_key, Some(config)).unwrap();
+        upsert_entry(content, field, &server.name, server_config, Some(config)).unwrap()
+    }
+
+    #[test]
+    fn test_goose_upsert_keeps_comments() {
+        let content = "\
+# Goose config
+GOOSE_PROVIDER: anthropic
+extensions:
+  # built in
+  developer:
+    enabled: true
+    name: developer
+    type: builtin
+
+  github:
+    cmd: old
+    type: stdio
+GOOSE_MODEL: claude # model
+";
+        let github = server("github", json!({ "command": "npx", "args": ["-y", "gh"] }));
+        let updated = upsert(content, &github, "goose", "extensions", &GOOSE_FORMAT);
+        assert!(updated.starts_with("# Goose config\nGOOSE_PROVIDER: anthropic\nextensions:\n  # built in\n"));
+        assert!(updated.ends_with("GOOSE_MODEL: claude # model\n"));
+        assert!(!updated.contains("cmd: old"));
+
+        let fetch = server("fetch", json!({ "command": "uvx", "args": ["mcp-server-fetch"] }));
+        let updated = upsert(&updated, &fetch, "goose", "extensions", &GOOSE_FORMAT);
+        let doc: Value = serde_yaml::from_str(&updated).unwrap();
+        let extensions = doc["extensions"].as_object().unwrap();
+        assert_eq!(extensions.keys().collect::<Vec<_>>(), ["developer", "github", "fetch"]);
+        assert_eq!(doc["extensions"]["github"]["cmd"], "npx");
+        assert_eq!(doc["extensions"]["github"]["enabled"], true);
+        assert_eq!(doc["extensions"]["fetch"]["name"], "fetch");
+        assert_eq!(doc["extensions"]["fetch"]["timeout"], 300);
+        assert_eq!(doc["GOOSE_MODEL"], "claude");
+
+        let servers = import_servers_from_yaml(&updated, "extensions", Some(&GOOSE_FORMAT)).unwrap();
+        let names: Vec<&str> = servers.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["github", "fetch"]);
+        assert_eq!(servers[0].server_config["command"], "npx");
+    }
+
+    #[test]
+    fn test_continue_list_entries() {
+        let content = "name: Local Assistant\nversion: 1.0.0\nmodels: []\n";
+        let fetch = server("fetch", json!({ "command": "uvx", "args": ["mcp-server-fetch"] }));
+        let updated = upsert(content, &fetch, "continue", "mcpServers", &CONTINUE_FORMAT);
+        assert!(updated.starts_with(content));
+        assert!(updated.contains("mcpServers:\n  - name: fetch\n"));
+
+        let memory = server("memory", json!({ "command": "npx", "args": ["memory"] }));
+        let updated = upsert(&updated, &memory, "continue", "mcpServers", &CONTINUE_FORMAT);
+        let servers = import_servers_from_yaml(&updated, "mcpServers", Some(&CONTINUE_FORMAT)).unwrap();
+        let names: Vec<&str> = servers.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["fetch", "memory"]);
+
+        let removed = remove_entry(&updated, "mcpServers", "fetch").unwrap().unwrap();
+        let servers = import_servers_from_yaml(&removed, "mcpServers", Some(&CONTINUE_FORMAT)).unwrap();
+        assert_eq!(servers.len(), 1);
+        assert_eq!(servers[0].name, "memory");
+
+        let removed = remove_entry(&removed, "mcpServers", "memory").unwrap().unwrap();
+        assert_eq!(removed, format!("{}mcpServers: []\n", content));
+        assert!(remove_entry(&removed, "mcpServers", "memory").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_flow_style_fallback() {
+        let content = "# keep\nmcpServers: [{name: a, command: x}]\nother: 1\n";
+        let b = server("b", json!({ "command": "y" }));
+        let updated = upsert(content, &b, "continue", "mcpServers", &CONTINUE_FORMAT);
+        let doc: Value = serde_yaml::from_str(&updated).unwrap();
+        assert_eq!(doc["mcpServers"][0]["name"], "a");
+        assert_eq!(doc["mcpServers"][1]["name"], "b");
+        assert_eq!(doc["other"], 1);
+    }
+}
@@ -1,5 +1,7 @@
 pub mod claude_code;
 pub mod codex;
+pub mod config_drift;
+pub mod config_writer;
 pub mod open_code;
 pub mod oh_my_opencode;
 pub mod oh_my_opencode_slim;
@@ -8,6 +10,9 @@ pub mod tools;
 pub mod mcp;
 pub mod wsl;
 pub mod ssh;
+pub mod remote_pull;
+pub mod remote_tools;
+pub mod sync_plan;
 
 mod db_id;
 pub use db_id::{db_clean_id, db_extract_id, db_extract_id_opt, db_build_id};
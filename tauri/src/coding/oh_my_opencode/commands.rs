@@ -2,6 +2,7 @@ use chrono::Local;
 use std::fs;
 use serde_json::Value;
 
+use crate::coding::config_writer::{preview_config_file, write_config_file, ConfigFilePreview};
 use crate::db::DbState;
 use super::adapter;
 use super::types::*;
@@ -457,6 +458,8 @@ pub async fn update_oh_my_opencode_config(
             // Trigger WSL sync via event (Windows only)
             #[cfg(target_os = "windows")]
             let _ = app.emit("wsl-sync-request-opencode", ());
+            // Trigger SSH sync via event (debounced by the SSH module)
+            let _ = app.emit("ssh-sync-request-opencode", ());
         }
     }
 
@@ -508,6 +511,19 @@ pub async fn apply_config_to_file_public(
     db: &surrealdb::Surreal<surrealdb::engine::local::Db>,
     config_id: &str,
 ) -> Result<(), String> {
+    let (config_path, json_content) = build_config_content(db, config_id).await?;
+
+    write_config_file(&config_path, json_content)
+        .map_err(|e| format!("Failed to write config file: {}", e))?;
+
+    Ok(())
+}
+
+/// Build the merged config file content (global config + profile) without writing it
+async fn build_config_content(
+    db: &surrealdb::Surreal<surrealdb::engine::local::Db>,
+    config_id: &str,
+) -> Result<(std::path::PathBuf, String), String> {
     // Get the config from database using direct ID format (like ClaudeCode)
     let records_result: Result<Vec<Value>, _> = db
         .query(format!(
@@ -536,14 +552,6 @@ pub async fn apply_config_to_file_public(
     // Get config path using unified function
     let config_path = get_oh_my_opencode_config_path()?;
 
-    // Ensure parent directory exists
-    if let Some(parent) = config_path.parent() {
-        if !parent.exists() {
-            fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create opencode config directory: {}", e))?;
-        }
-    }
-
     // 获取 Global Config
     let global_records_result: Result<Vec<Value>, _> = db
         .query("SELECT *, type::string(id) as id FROM oh_my_opencode_global_config:`global` LIMIT 1")
@@ -680,10 +688,18 @@ pub async fn apply_config_to_file_public(
     let json_content = serde_json::to_string_pretty(&final_json)
         .map_err(|e| format!("Failed to serialize final config: {}", e))?;
 
-    fs::write(&config_path, json_content)
-        .map_err(|e| format!("Failed to write config file: {}", e))?;
+    Ok((config_path, json_content))
+}
 
-    Ok(())
+/// Preview applying an oh-my-opencode config: returns the would-be file and a diff against the current file
+#[tauri::command]
+pub async fn preview_oh_my_opencode_config(
+    state: tauri::State<'_, DbState>,
+    config_id: String,
+) -> Result<Vec<ConfigFilePreview>, String> {
+    let db = state.0.lock().await;
+    let (config_path, json_content) = build_config_content(&db, &config_id).await?;
+    Ok(vec![preview_config_file(&config_path, &json_content)])
 }
 /// Apply an oh-my-opencode config to the JSON file
 #[tauri::command]
@@ -731,6 +747,8 @@ pub async fn apply_config_internal<R: tauri::Runtime>(
     // Trigger WSL sync via event (Windows only)
     #[cfg(target_os = "windows")]
     let _ = app.emit("wsl-sync-request-opencode", ());
+    // Trigger SSH sync via event (debounced by the SSH module)
+    let _ = app.emit("ssh-sync-request-opencode", ());
 
     Ok(())
 }
@@ -949,6 +967,8 @@ pub async fn save_oh_my_opencode_global_config(
                 // Trigger WSL sync via event (Windows only)
                 #[cfg(target_os = "windows")]
                 let _ = app.emit("wsl-sync-request-opencode", ());
+                // Trigger SSH sync via event (debounced by the SSH module)
+                let _ = app.emit("ssh-sync-request-opencode", ());
             }
         }
     }
@@ -6,6 +6,7 @@ use tauri::Emitter;
 
 use super::adapter;
 use super::types::*;
+use crate::coding::config_writer::{preview_config_file, write_config_file, ConfigFilePreview};
 use crate::db::DbState;
 
 // ============================================================================
@@ -258,26 +259,9 @@ pub async fn apply_config_internal<R: tauri::Runtime>(
     let config_path_str = get_opencode_config_path(state).await?;
     let config_path = Path::new(&config_path_str);
 
-    // Ensure directory exists
-    if let Some(parent) = config_path.parent() {
-        if !parent.exists() {
-            fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create config directory: {}", e))?;
-        }
-    }
+    let json_content = render_opencode_config(&config)?;
 
-    // Serialize to JSON Value first, then clean up empty objects
-    let mut json_value = serde_json::to_value(&config)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
-
-    // Clean up empty objects in models (options, variants, modalities)
-    clean_empty_objects(&mut json_value);
-
-    // Serialize with pretty printing
-    let json_content = serde_json::to_string_pretty(&json_value)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
-
-    fs::write(config_path, json_content)
+    write_config_file(config_path, json_content)
         .map_err(|e| format!("Failed to write config file: {}", e))?;
 
     // Notify based on source
@@ -287,10 +271,37 @@ pub async fn apply_config_internal<R: tauri::Runtime>(
     // Trigger WSL sync via event (Windows only)
     #[cfg(target_os = "windows")]
     let _ = app.emit("wsl-sync-request-opencode", ());
+    // Trigger SSH sync via event (debounced by the SSH module)
+    let _ = app.emit("ssh-sync-request-opencode", ());
 
     Ok(())
 }
 
+/// Render the config file content exactly as `save_opencode_config` writes it
+fn render_opencode_config(config: &OpenCodeConfig) -> Result<String, String> {
+    // Serialize to JSON Value first, then clean up empty objects
+    let mut json_value = serde_json::to_value(config)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+
+    // Clean up empty objects in models (options, variants, modalities)
+    clean_empty_objects(&mut json_value);
+
+    // Serialize with pretty printing
+    serde_json::to_string_pretty(&json_value)
+        .map_err(|e| format!("Failed to serialize config: {}", e))
+}
+
+/// Preview saving an OpenCode config: returns the would-be file and a diff against the current file
+#[tauri::command]
+pub async fn preview_opencode_config(
+    state: tauri::State<'_, DbState>,
+    config: OpenCodeConfig,
+) -> Result<Vec<ConfigFilePreview>, String> {
+    let config_path_str = get_opencode_config_path(state).await?;
+    let json_content = render_opencode_config(&config)?;
+    Ok(vec![preview_config_file(Path::new(&config_path_str), &json_content)])
+}
+
 // ============================================================================
 // OpenCode Common Config Commands
 // ============================================================================
@@ -0,0 +1,375 @@
+//! Reverse sync: pull MCP servers and skills from a remote machine (SSH / WSL)
+//!
+//! The SSH and WSL modules read the remote files with their own transport and
+//! hand the content to this module, which parses it with the regular MCP import
+//! code, compares it with the database and imports the items the user selected.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::mcp::config_sync::{import_servers_from_content, sync_server_to_tool};
+use super::mcp::env_vars::EnvResolver;
+use super::mcp::mcp_store;
+use super::mcp::types::{McpServer, McpSyncDetail};
+use super::skills::installer::install_local_skill;
+use super::skills::skill_store;
+use super::skills::sync_engine::sync_dir_for_tool_with_overwrite;
+use super::skills::tool_adapters::{
+    is_tool_installed as is_skill_tool_installed, resolve_runtime_skills_path,
+    runtime_adapter_by_key,
+};
+use super::skills::types::SkillTarget;
+use super::tools::{custom_store, is_tool_installed, runtime_tool_by_key, BUILTIN_TOOLS};
+use crate::DbState;
+
+/// Remote MCP config files read by a pull: tool key and candidate paths (first existing wins)
+pub const REMOTE_MCP_SOURCES: &[(&str, &[&str])] = &[
+    ("claude_code", &["~/.claude.json"]),
+    ("codex", &["~/.codex/config.toml"]),
+    (
+        "opencode",
+        &["~/.config/opencode/opencode.jsonc", "~/.config/opencode/opencode.json"],
+    ),
+];
+
+/// An MCP server or skill found on the remote machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PullItem {
+    /// "mcp" | "skill"
+    pub kind: String,
+    pub name: String,
+    /// Tool the item was found in
+    pub tool: String,
+    /// Compared with the database: "new" | "changed" | "same" (MCP) / "new" | "exists" (skills)
+    pub status: String,
+    /// MCP server type and config as found on the remote
+    pub server_type: Option<String>,
+    pub server_config: Option<Value>,
+    /// Remote directory of a skill
+    pub remote_path: Option<String>,
+}
+
+/// Items found by a pull, for the user to select from
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PullPreview {
+    pub items: Vec<PullItem>,
+    /// Files that could not be read or parsed
+    pub errors: Vec<String>,
+}
+
+/// Result of importing the selected items
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PullResult {
+    pub imported: Vec<String>,
+    pub skipped: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+/// Remote skills directory of every tool that supports skills: (tool key, directory)
+pub fn remote_skill_dirs() -> Vec<(&'static str, String)> {
+    BUILTIN_TOOLS
+        .iter()
+        .filter_map(|t| Some((t.key, t.relative_skills_dir?.to_string())))
+        .collect()
+}
+
+/// Whether a stored server matches a remote config, either as stored or with its
+/// `${NAME}` placeholders resolved for the remote's sync target
+fn matches_remote(existing: &McpServer, env: &EnvResolver, server_type: &str, server_config: &Value) -> bool {
+    existing.server_type == server_type
+        && (existing.server_config == *server_config
+            || env
+                .resolve_server(existing)
+                .is_ok_and(|resolved| resolved.server_config == *server_config))
+}
+
+/// Compare the MCP servers of a remote config file with the database. `target` is the
+/// sync target id of the remote, see [`super::mcp::env_vars::ssh_target`].
+pub async fn diff_mcp_config(
+    state: &DbState,
+    target: &str,
+    tool_key: &str,
+    content: &str,
+) -> Result<Vec<PullItem>, String> {
+    let custom_tools = custom_store::get_custom_tools(state).await.unwrap_or_default();
+    let tool = runtime_tool_by_key(tool_key, &custom_tools)
+        .ok_or_else(|| format!("Tool not found: {}", tool_key))?;
+    let servers = import_servers_from_content(&tool, content)?;
+    let env = EnvResolver::load(state, Some(target)).await?;
+
+    let mut items = Vec::new();
+    for server in servers {
+        let status = match mcp_store::get_mcp_server_by_name(state, &server.name).await? {
+            None => "new",
+            Some(existing) if matches_remote(&existing, &env, &server.server_type, &server.server_config) => {
+                "same"
+            }
+            Some(_) => "changed",
+        };
+        items.push(PullItem {
+            kind: "mcp".to_string(),
+            name: server.name,
+            tool: tool_key.to_string(),
+            status: status.to_string(),
+            server_type: Some(server.server_type),
+            server_config: Some(server.server_config),
+            remote_path: None,
+        });
+    }
+    Ok(items)
+}
+
+/// Compare the skill directories found in a remote tool skills directory with the database
+pub async fn diff_skills(
+    state: &DbState,
+    tool_key: &str,
+    skills_dir: &str,
+    names: Vec<String>,
+) -> Result<Vec<PullItem>, String> {
+    let mut items = Vec::new();
+    for name in names {
+        if name.starts_with('.') {
+            continue;
+        }
+        let exists = skill_store::get_skill_by_name(state, &name).await?.is_some();
+        items.push(PullItem {
+            kind: "skill".to_string(),
+            remote_path: Some(format!("{}/{}", skills_dir, name)),
+            name,
+            tool: tool_key.to_string(),
+            status: if exists { "exists" } else { "new" }.to_string(),
+            server_type: None,
+            server_config: None,
+        });
+    }
+    Ok(items)
+}
+
+/// Import a pulled MCP server: new servers are created enabled for the tool they
+/// were found in, existing ones get the remote config unless their stored config
+/// (with placeholders) resolves to it for `target`. Enabled local tools are re-synced.
+pub async fn import_mcp_item(state: &DbState, target: &str, item: &PullItem) -> Result<(), String> {
+    let server_type = item.server_type.clone().ok_or("Missing MCP server type")?;
+    let server_config = item.server_config.clone().ok_or("Missing MCP server config")?;
+    let now = super::mcp::types::now_ms();
+    let target_env = EnvResolver::load(state, Some(target)).await?;
+
+    let server = match mcp_store::get_mcp_server_by_name(state, &item.name).await? {
+        Some(existing) if matches_remote(&existing, &target_env, &server_type, &server_config) => existing,
+        Some(mut existing) => {
+            existing.server_type = server_type;
+            existing.server_config = server_config;
+            existing.updated_at = now;
+            existing
+        }
+        None => McpServer {
+            id: String::new(),
+            name: item.name.clone(),
+            server_type,
+            server_config,
+            enabled_tools: vec![item.tool.clone()],
+            sync_details: None,
+            description: None,
+            tags: vec![],
+            sort_index: 0,
+            health: None,
+            tool_catalog: vec![],
+            tool_filters: Default::default(),
+            created_at: now,
+            updated_at: now,
+        },
+    };
+    let server_id = mcp_store::upsert_mcp_server(state, &server).await?;
+
+    let custom_tools = custom_store::get_custom_tools(state).await.unwrap_or_default();
+    let env = EnvResolver::load(state, None).await?;
+    for tool_key in &server.enabled_tools {
+        let Some(tool) = runtime_tool_by_key(tool_key, &custom_tools) else {
+            continue;
+        };
+        if !is_tool_installed(&tool) {
+            continue;
+        }
+        let detail = sync_server_to_tool(&server, &tool, &env).unwrap_or_else(|e| McpSyncDetail {
+            tool: tool_key.clone(),
+            status: "error".to_string(),
+            synced_at: Some(now),
+            error_message: Some(e),
+        });
+        let _ = mcp_store::update_sync_detail(state, &server_id, &detail).await;
+    }
+    Ok(())
+}
+
+/// Import a skill downloaded from the remote machine into the central repo and
+/// link it to the local tool it was found in when that tool is installed
+pub async fn import_skill_dir(
+    app: &tauri::AppHandle,
+    state: &DbState,
+    item: &PullItem,
+    local_dir: &Path,
+    source_ref: String,
+) -> Result<(), String> {
+    let installed = install_local_skill(app, state, local_dir, true)
+        .await
+        .map_err(|e| format!("{:#}", e))?;
+
+    if let Some(mut skill) = skill_store::get_skill_by_id(state, &installed.skill_id).await? {
+        skill.source_ref = Some(source_ref);
+        skill_store::upsert_skill(state, &skill).await?;
+    }
+
+    let custom_tools = skill_store::get_custom_tools(state).await.unwrap_or_default();
+    let Some(adapter) = runtime_adapter_by_key(&item.tool, &custom_tools) else {
+        return Ok(());
+    };
+    if !is_skill_tool_installed(&adapter).unwrap_or(false) {
+        return Ok(());
+    }
+
+    let target = resolve_runtime_skills_path(&adapter)
+        .map_err(|e| format!("{:#}", e))?
+        .join(&installed.name);
+    let outcome = sync_dir_for_tool_with_overwrite(
+        &item.tool,
+        &installed.central_path,
+        &target,
+        false,
+        adapter.force_copy,
+    )
+    .map_err(|e| {
+        format!(
+            "Skill '{}' imported but not linked to {}: {:#}",
+            item.name, item.tool, e
+        )
+    })?;
+
+    let record = SkillTarget {
+        tool: item.tool.clone(),
+        target_path: outcome.target_path.to_string_lossy().to_string(),
+        mode: outcome.mode_used.as_str().to_string(),
+        status: "ok".to_string(),
+        error_message: None,
+        synced_at: Some(super::skills::types::now_ms()),
+    };
+    skill_store::upsert_skill_target(state, &installed.skill_id, &record).await
+}
+
+/// Temporary local directory a remote skill is downloaded to before it is imported
+pub fn skill_download_dir(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir()
+        .join(format!("ai-toolbox-pull-{}", uuid::Uuid::new_v4()))
+        .join(name)
+}
+
+/// Label of an item in pull results
+pub fn item_label(item: &PullItem) -> String {
+    format!("{} {} ({})", item.kind, item.name, item.tool)
+}
+
+/// Whether a selected item should be imported. Unchanged MCP servers and names
+/// already imported by this pull (the same item found in several tools) are skipped.
+pub fn should_import(item: &PullItem, imported: &[(String, String)], result: &mut PullResult) -> bool {
+    let label = item_label(item);
+    if item.status == "same"
+        || imported.iter().any(|(kind, name)| kind == &item.kind && name == &item.name)
+    {
+        result.skipped.push(label);
+        return false;
+    }
+    if item.name.is_empty() || item.name.contains(['/', '\\']) || item.name == ".." || item.name == "." {
+        result.errors.push(format!("{}: invalid name", label));
+        return false;
+    }
+    true
+}
+
+/// Notify listeners about imported items so the UI reloads and local tools are re-synced
+pub fn emit_pull_changes(app: &tauri::AppHandle, imported: &[(String, String)]) {
+    use tauri::Emitter;
+
+    if imported.iter().any(|(kind, _)| kind == "mcp") {
+        let _ = app.emit("mcp-changed", "window");
+    }
+    if imported.iter().any(|(kind, _)| kind == "skill") {
+        let _ = app.emit("skills-changed", "window");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(kind: &str, name: &str, tool: &str, status: &str) -> PullItem {
+        PullItem {
+            kind: kind.to_string(),
+            name: name.to_string(),
+            tool: tool.to_string(),
+            status: status.to_string(),
+            server_type: None,
+            server_config: None,
+            remote_path: None,
+        }
+    }
+
+    #[test]
+    fn test_should_import() {
+        let mut result = PullResult::default();
+        let imported = vec![("skill".to_string(), "docs".to_string())];
+
+        assert!(should_import(&item("mcp", "docs", "codex", "new"), &imported, &mut result));
+        assert!(!should_import(&item("skill", "docs", "codex", "new"), &imported, &mut result));
+        assert!(!should_import(&item("mcp", "fs", "codex", "same"), &imported, &mut result));
+        assert!(!should_import(&item("skill", "../x", "codex", "new"), &imported, &mut result));
+
+        assert_eq!(result.skipped.len(), 2);
+        assert_eq!(result.errors.len(), 1);
+    }
+
+    #[test]
+    fn test_matches_remote_resolves_placeholders() {
+        let existing = McpServer {
+            id: String::new(),
+            name: "github".to_string(),
+            server_type: "stdio".to_string(),
+            server_config: serde_json::json!({
+                "command": "github-mcp",
+                "env": { "GITHUB_TOKEN": "${GITHUB_TOKEN}" },
+            }),
+            enabled_tools: vec![],
+            sync_details: None,
+            description: None,
+            tags: vec![],
+            sort_index: 0,
+            health: None,
+            tool_catalog: vec![],
+            tool_filters: Default::default(),
+            created_at: 0,
+            updated_at: 0,
+        };
+        let vars = vec![super::super::mcp::types::McpEnvVar {
+            id: String::new(),
+            name: "GITHUB_TOKEN".to_string(),
+            value: "local-token".to_string(),
+            secret: false,
+            overrides: [("ssh:box".to_string(), "remote-token".to_string())].into(),
+            description: None,
+            created_at: 0,
+            updated_at: 0,
+        }];
+        let env = EnvResolver::new(&vars, Some("ssh:box"));
+        let remote = |token: &str| {
+            serde_json::json!({ "command": "github-mcp", "env": { "GITHUB_TOKEN": token } })
+        };
+
+        assert!(matches_remote(&existing, &env, "stdio", &remote("remote-token")));
+        assert!(matches_remote(&existing, &env, "stdio", &existing.server_config));
+        assert!(!matches_remote(&existing, &env, "stdio", &remote("local-token")));
+        assert!(!matches_remote(&existing, &env, "http", &remote("remote-token")));
+    }
+}
@@ -0,0 +1,204 @@
+//! Tool installation detection on a remote machine (SSH / WSL)
+//!
+//! Builds one shell script that checks every built-in tool's detect directory and
+//! CLI binaries on the remote side, so skills and MCP are only pushed to tools that
+//! exist there. The SSH and WSL modules run the script with their own transport.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::tools::{BuiltinTool, BUILTIN_TOOLS};
+
+/// CLI binaries of the built-in tools, any of them found on PATH counts as installed
+const TOOL_BINARIES: &[(&str, &[&str])] = &[
+    ("claude_code", &["claude"]),
+    ("codex", &["codex"]),
+    ("gemini_cli", &["gemini"]),
+    ("cursor", &["cursor-agent", "cursor"]),
+    ("opencode", &["opencode"]),
+    ("amp", &["amp"]),
+    ("goose", &["goose"]),
+    ("github_copilot", &["copilot"]),
+    ("clawdbot", &["clawdbot"]),
+    ("droid", &["droid"]),
+    ("windsurf", &["windsurf"]),
+    ("continue", &["cn"]),
+];
+
+/// User install locations missing from the PATH of non-interactive shells
+const EXTRA_PATH: &str = "$HOME/.local/bin:$HOME/bin:$HOME/.npm-global/bin:$HOME/.bun/bin:$HOME/.opencode/bin:/usr/local/bin:/opt/homebrew/bin";
+
+/// Installation status of one tool on a remote machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteToolStatus {
+    pub key: String,
+    pub display_name: String,
+    pub installed: bool,
+    /// Remote detect directory that was found
+    pub detect_dir: Option<String>,
+    /// Remote path of the CLI binary that was found
+    pub binary_path: Option<String>,
+    pub supports_skills: bool,
+    pub supports_mcp: bool,
+}
+
+/// Tool matrix of one sync target (an SSH host or the WSL distro)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteToolMatrix {
+    pub target_id: String,
+    pub target_name: String,
+    pub tools: Vec<RemoteToolStatus>,
+    pub error: Option<String>,
+}
+
+/// Remote candidates of a detect directory. `%APPDATA%` maps to `~/.config` on Linux;
+/// VS Code extensions may also live in the VS Code Server data directory.
+fn remote_detect_dirs(detect_dir: &str) -> Vec<String> {
+    let dir = detect_dir.replace('\\', "/");
+    if let Some(rest) = dir.strip_prefix("%APPDATA%/") {
+        let mut dirs = vec![format!("~/.config/{}", rest)];
+        if let Some(code_rest) = rest.strip_prefix("Code/") {
+            dirs.push(format!("~/.vscode-server/data/{}", code_rest));
+        }
+        dirs
+    } else {
+        vec![dir]
+    }
+}
+
+/// Remote detect directory candidates of a tool and the entry our own skills sync
+/// creates in them, for transports that check directories without the script
+pub fn detect_dir_candidates(tool: &BuiltinTool) -> (Vec<String>, Option<&'static str>) {
+    let dirs = tool.relative_detect_dir.map(remote_detect_dirs).unwrap_or_default();
+    (dirs, own_entry(tool))
+}
+
+/// Status of a tool from the detect directory and CLI binary found on the remote
+pub fn tool_status(
+    tool: &BuiltinTool,
+    detect_dir: Option<String>,
+    binary_path: Option<String>,
+) -> RemoteToolStatus {
+    RemoteToolStatus {
+        key: tool.key.to_string(),
+        display_name: tool.display_name.to_string(),
+        installed: detect_dir.is_some() || binary_path.is_some(),
+        detect_dir,
+        binary_path,
+        supports_skills: tool.relative_skills_dir.is_some(),
+        supports_mcp: tool.mcp_config_path.is_some(),
+    }
+}
+
+/// Entry of the detect directory that our own skills sync creates (e.g. `skills`);
+/// a detect directory holding only that entry does not mean the tool is installed
+fn own_entry(tool: &BuiltinTool) -> Option<&'static str> {
+    let skills = tool.relative_skills_dir?.strip_prefix(tool.relative_detect_dir?)?;
+    skills.trim_start_matches('/').split('/').next().filter(|s| !s.is_empty())
+}
+
+fn shell_path(path: &str) -> String {
+    match path.strip_prefix("~/") {
+        Some(rest) => format!("$HOME/{}", rest),
+        None => path.to_string(),
+    }
+}
+
+/// POSIX shell script printing `key|detect dir|binary path` for every built-in tool
+pub fn detection_script() -> String {
+    let mut script = format!("PATH=\"$PATH:{}\"; ", EXTRA_PATH);
+    for tool in BUILTIN_TOOLS {
+        script.push_str("d=''; b=''; ");
+        for dir in tool.relative_detect_dir.map(remote_detect_dirs).unwrap_or_default() {
+            let path = shell_path(&dir);
+            let check = match own_entry(tool) {
+                Some(entry) => format!(
+                    "[ -n \"$(ls -A \"{}\" 2>/dev/null | grep -vxF '{}')\" ]",
+                    path, entry
+                ),
+                None => format!("[ -e \"{}\" ]", path),
+            };
+            script.push_str(&format!("[ -z \"$d\" ] && {} && d='{}'; ", check, dir));
+        }
+        let binaries = TOOL_BINARIES
+            .iter()
+            .find(|(key, _)| *key == tool.key)
+            .map(|(_, bins)| *bins)
+            .unwrap_or_default();
+        for bin in binaries {
+            script.push_str(&format!(
+                "[ -z \"$b\" ] && b=$(command -v {} 2>/dev/null); ",
+                bin
+            ));
+        }
+        script.push_str(&format!("echo \"{}|$d|$b\"; ", tool.key));
+    }
+    script.push_str("true");
+    script
+}
+
+/// Parse the output of `detection_script`
+pub fn parse_detection_output(output: &str) -> Vec<RemoteToolStatus> {
+    BUILTIN_TOOLS
+        .iter()
+        .map(|tool| {
+            let line = output
+                .lines()
+                .find(|line| line.split('|').next() == Some(tool.key));
+            let mut parts = line.unwrap_or_default().splitn(3, '|').skip(1);
+            let non_empty = |s: Option<&str>| s.map(str::trim).filter(|s| !s.is_empty()).map(String::from);
+            let detect_dir = non_empty(parts.next());
+            let binary_path = non_empty(parts.next());
+            tool_status(tool, detect_dir, binary_path)
+        })
+        .collect()
+}
+
+/// Keys of the installed tools; when detection failed every tool is assumed installed
+/// so a sync behaves as before instead of silently dropping everything
+pub fn installed_tool_keys(detected: Result<Vec<RemoteToolStatus>, String>) -> HashSet<String> {
+    match detected {
+        Ok(tools) => tools
+            .into_iter()
+            .filter(|t| t.installed)
+            .map(|t| t.key)
+            .collect(),
+        Err(e) => {
+            log::warn!("Remote tool detection failed, syncing to all tools: {}", e);
+            BUILTIN_TOOLS.iter().map(|t| t.key.to_string()).collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_detection_output() {
+        let output = "claude_code|~/.claude|/usr/bin/claude\ncodex||\ngoose||/home/u/.local/bin/goose\n";
+        let tools = parse_detection_output(output);
+        let find = |key: &str| tools.iter().find(|t| t.key == key).unwrap();
+
+        assert!(find("claude_code").installed);
+        assert_eq!(find("claude_code").detect_dir.as_deref(), Some("~/.claude"));
+        assert!(!find("codex").installed);
+        assert!(find("goose").installed);
+        assert!(!find("cursor").installed);
+        assert_eq!(tools.len(), BUILTIN_TOOLS.len());
+    }
+
+    #[test]
+    fn test_remote_detect_dirs() {
+        assert_eq!(remote_detect_dirs("~/.codex"), vec!["~/.codex"]);
+        assert_eq!(
+            remote_detect_dirs("%APPDATA%/Code/User/globalStorage/x"),
+            vec!["~/.config/Code/User/globalStorage/x", "~/.vscode-server/data/User/globalStorage/x"]
+        );
+        let claude = BUILTIN_TOOLS.iter().find(|t| t.key == "claude_code").unwrap();
+        assert_eq!(own_entry(claude), Some("skills"));
+    }
+}
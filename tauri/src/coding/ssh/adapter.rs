@@ -1,6 +1,7 @@
 use serde_json::{json, Value};
 use super::types::{SSHConnection, SSHFileMapping, SSHSyncConfig};
 use super::super::db_id;
+use crate::secrets;
 use chrono::Local;
 
 // ============================================================================
@@ -29,6 +30,14 @@ pub fn config_from_db_value(
         sync_skills: true,
         file_mappings,
         connections,
+        auto_sync: value
+            .get("auto_sync")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true),
+        sync_interval_minutes: value
+            .get("sync_interval_minutes")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as u32,
         last_sync_time: value
             .get("last_sync_time")
             .or_else(|| value.get("lastSyncTime"))
@@ -53,6 +62,8 @@ pub fn config_to_db_value(config: &SSHSyncConfig) -> Value {
     json!({
         "enabled": config.enabled,
         "active_connection_id": config.active_connection_id,
+        "auto_sync": config.auto_sync,
+        "sync_interval_minutes": config.sync_interval_minutes,
         "last_sync_time": config.last_sync_time,
         "last_sync_status": config.last_sync_status,
         "last_sync_error": config.last_sync_error,
@@ -94,33 +105,62 @@ pub fn connection_from_db_value(value: Value) -> SSHConnection {
             .and_then(|v| v.as_str())
             .unwrap_or("key")
             .to_string(),
-        password: value
-            .get("password")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string(),
+        password: secrets::reveal(
+            value
+                .get("password")
+                .and_then(|v| v.as_str())
+                .unwrap_or(""),
+        ),
         private_key_path: value
             .get("private_key_path")
             .or_else(|| value.get("privateKeyPath"))
             .and_then(|v| v.as_str())
             .unwrap_or("")
             .to_string(),
-        private_key_content: value
-            .get("private_key_content")
-            .or_else(|| value.get("privateKeyContent"))
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string(),
-        passphrase: value
-            .get("passphrase")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string(),
+        private_key_content: secrets::reveal(
+            value
+                .get("private_key_content")
+                .or_else(|| value.get("privateKeyContent"))
+                .and_then(|v| v.as_str())
+                .unwrap_or(""),
+        ),
+        passphrase: secrets::reveal(
+            value
+                .get("passphrase")
+                .and_then(|v| v.as_str())
+                .unwrap_or(""),
+        ),
         sort_order: value
             .get("sort_order")
             .or_else(|| value.get("sortOrder"))
             .and_then(|v| v.as_u64())
             .unwrap_or(0) as u32,
+        host_key: value
+            .get("host_key")
+            .or_else(|| value.get("hostKey"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+        sync_target: value
+            .get("sync_target")
+            .or_else(|| value.get("syncTarget"))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+        proxy_jump: value
+            .get("proxy_jump")
+            .or_else(|| value.get("proxyJump"))
+            .and_then(|v| v.as_array())
+            .map(|ids| {
+                ids.iter()
+                    .filter_map(|id| id.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        sftp_only: value
+            .get("sftp_only")
+            .or_else(|| value.get("sftpOnly"))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
     }
 }
 
@@ -132,11 +172,15 @@ pub fn connection_to_db_value(conn: &SSHConnection) -> Value {
         "port": conn.port,
         "username": conn.username,
         "auth_method": conn.auth_method,
-        "password": conn.password,
+        "password": secrets::seal(&conn.password),
         "private_key_path": conn.private_key_path,
-        "private_key_content": conn.private_key_content,
-        "passphrase": conn.passphrase,
+        "private_key_content": secrets::seal(&conn.private_key_content),
+        "passphrase": secrets::seal(&conn.passphrase),
         "sort_order": conn.sort_order,
+        "host_key": conn.host_key,
+        "sync_target": conn.sync_target,
+        "proxy_jump": conn.proxy_jump,
+        "sftp_only": conn.sftp_only,
         "updated_at": Local::now().to_rfc3339(),
     })
 }
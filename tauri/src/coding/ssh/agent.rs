@@ -0,0 +1,71 @@
+//! SSH agent 连接
+//!
+//! Unix 上通过 SSH_AUTH_SOCK 指定的 socket 连接；Windows 上优先使用 SSH_AUTH_SOCK
+//! 指定的命名管道，其次是 OpenSSH for Windows 的默认管道，最后尝试 Pageant。
+
+use std::future::Future;
+use std::pin::Pin;
+
+use russh::keys::agent::client::{AgentClient, AgentStream};
+use russh::keys::ssh_key;
+
+/// 可在不同平台间通用的 agent 客户端
+pub type DynAgentClient = AgentClient<Box<dyn AgentStream + Send + Unpin + 'static>>;
+
+/// 连接本机 SSH agent
+#[cfg(unix)]
+pub async fn connect_agent() -> Result<DynAgentClient, String> {
+    AgentClient::connect_env()
+        .await
+        .map(|client| client.dynamic())
+        .map_err(|e| format!("连接 SSH agent 失败（请检查 SSH_AUTH_SOCK）: {}", e))
+}
+
+/// 连接本机 SSH agent
+#[cfg(windows)]
+pub async fn connect_agent() -> Result<DynAgentClient, String> {
+    const OPENSSH_AGENT_PIPE: &str = r"\\.\pipe\openssh-ssh-agent";
+
+    let pipe = std::env::var("SSH_AUTH_SOCK")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| OPENSSH_AGENT_PIPE.to_string());
+    match AgentClient::connect_named_pipe(&pipe).await {
+        Ok(client) => Ok(client.dynamic()),
+        Err(pipe_err) => AgentClient::connect_pageant()
+            .await
+            .map(|client| client.dynamic())
+            .map_err(|e| {
+                format!(
+                    "连接 SSH agent 失败: {} ({}); Pageant: {}",
+                    pipe_err, pipe, e
+                )
+            }),
+    }
+}
+
+/// 对 agent 签名 future 装箱的 Signer
+///
+/// russh 为 AgentClient 实现的签名 future 在 tauri::async_runtime::spawn 中
+/// 无法被编译器证明为 Send（高阶生命周期），这里返回具体的装箱类型。
+pub struct AgentSigner(pub DynAgentClient);
+
+impl russh::Signer for AgentSigner {
+    type Error = russh::AgentAuthError;
+
+    #[allow(refining_impl_trait)]
+    fn auth_publickey_sign(
+        &mut self,
+        key: &ssh_key::PublicKey,
+        hash_alg: Option<ssh_key::HashAlg>,
+        to_sign: russh::CryptoVec,
+    ) -> Pin<Box<dyn Future<Output = Result<russh::CryptoVec, Self::Error>> + Send + '_>> {
+        let key = key.clone();
+        Box::pin(async move {
+            self.0
+                .sign_request(&key, hash_alg, to_sign)
+                .await
+                .map_err(Into::into)
+        })
+    }
+}
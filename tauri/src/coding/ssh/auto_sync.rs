@@ -0,0 +1,211 @@
+//! 自动同步：配置、MCP、Skills 变更后防抖触发同步，以及可选的定时同步
+//!
+//! 变更事件在防抖时间内合并为一次同步：有文件模块变更时执行完整同步（包括 MCP 和
+//! Skills），只有 MCP / Skills 变更时只同步对应内容到各个同步目标主机。
+
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+use futures_util::future::join_all;
+use tauri::{Emitter, Manager};
+use tokio::sync::Mutex;
+
+use super::commands::{connect_session, get_ssh_config_internal, sync_all_targets, update_sync_status};
+use super::mcp_sync::sync_mcp_to_ssh;
+use super::session::SshSessionState;
+use super::skills_sync::sync_skills_to_ssh;
+use super::types::SSHSyncConfig;
+use crate::db::DbState;
+
+/// 变更事件的防抖时间
+const DEBOUNCE: Duration = Duration::from_secs(3);
+
+/// 定时同步的检查间隔
+const SCHEDULE_TICK: Duration = Duration::from_secs(60);
+
+/// 触发自动同步的变更
+#[derive(Debug, Clone)]
+pub enum AutoSyncTrigger {
+    /// 某个模块（opencode / claude / codex）的配置已应用
+    Module(String),
+    Mcp,
+    Skills,
+}
+
+/// 防抖期间累积的变更
+#[derive(Debug, Default)]
+struct PendingSync {
+    modules: BTreeSet<String>,
+    mcp: bool,
+    skills: bool,
+}
+
+/// 自动同步状态，由 Tauri 管理
+#[derive(Default)]
+pub struct SshAutoSyncState {
+    inner: Mutex<(u64, PendingSync)>,
+}
+
+impl SshAutoSyncState {
+    /// 记录一次变更，返回本次变更的序号
+    async fn push(&self, trigger: AutoSyncTrigger) -> u64 {
+        let mut inner = self.inner.lock().await;
+        inner.0 += 1;
+        match trigger {
+            AutoSyncTrigger::Module(module) => {
+                inner.1.modules.insert(module);
+            }
+            AutoSyncTrigger::Mcp => inner.1.mcp = true,
+            AutoSyncTrigger::Skills => inner.1.skills = true,
+        }
+        inner.0
+    }
+
+    /// 防抖时间内没有新的变更时取出累积的变更
+    async fn take_if_latest(&self, generation: u64) -> Option<PendingSync> {
+        let mut inner = self.inner.lock().await;
+        if inner.0 != generation {
+            return None;
+        }
+        Some(std::mem::take(&mut inner.1))
+    }
+}
+
+/// 请求一次自动同步（防抖），在事件监听器中调用
+pub fn request_auto_sync(app: &tauri::AppHandle, trigger: AutoSyncTrigger) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<SshAutoSyncState>();
+        let generation = state.push(trigger).await;
+        tokio::time::sleep(DEBOUNCE).await;
+
+        let Some(pending) = state.take_if_latest(generation).await else {
+            return;
+        };
+        let Some(config) = load_config(&app).await else {
+            return;
+        };
+        if !config.auto_sync {
+            return;
+        }
+        run_pending(&app, &config, pending).await;
+    });
+}
+
+/// 启动定时同步：距上次同步超过设定间隔时执行完整同步
+pub fn start_scheduled_sync(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(SCHEDULE_TICK).await;
+
+            let Some(config) = load_config(&app).await else {
+                continue;
+            };
+            if config.sync_interval_minutes == 0 || !is_sync_due(&config, Local::now()) {
+                continue;
+            }
+            log::info!("SSH 定时同步（间隔 {} 分钟）", config.sync_interval_minutes);
+            run_full_sync(&app, &config, None).await;
+        }
+    });
+}
+
+/// 读取 SSH 配置，未启用或没有同步目标时返回 None
+async fn load_config(app: &tauri::AppHandle) -> Option<SSHSyncConfig> {
+    let db_state = app.state::<DbState>();
+    let config = {
+        let db = db_state.0.lock().await;
+        get_ssh_config_internal(&db, true).await.ok()?
+    };
+    if !config.enabled || config.sync_targets().is_empty() {
+        return None;
+    }
+    Some(config)
+}
+
+/// 距上次同步是否已超过定时同步间隔
+fn is_sync_due(config: &SSHSyncConfig, now: DateTime<Local>) -> bool {
+    let Some(last) = config
+        .last_sync_time
+        .as_deref()
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+    else {
+        return true;
+    };
+    let elapsed = now.signed_duration_since(last);
+    elapsed.num_minutes() >= i64::from(config.sync_interval_minutes)
+}
+
+/// 执行累积的变更
+async fn run_pending(app: &tauri::AppHandle, config: &SSHSyncConfig, pending: PendingSync) {
+    if !pending.modules.is_empty() {
+        // 完整同步包括 MCP 和 Skills；多个模块变更时同步全部模块
+        let module = match pending.modules.len() {
+            1 => pending.modules.into_iter().next(),
+            _ => None,
+        };
+        run_full_sync(app, config, module.as_deref()).await;
+        return;
+    }
+
+    let db_state = app.state::<DbState>();
+    let session_state = app.state::<SshSessionState>();
+    let (db_state, session_state) = (db_state.inner(), session_state.inner());
+
+    join_all(config.sync_targets().into_iter().map(|conn| async move {
+        let session = session_state.session(&conn.id).await;
+        let mut session = session.lock().await;
+        if let Err(e) = connect_session(db_state, &mut session, conn).await {
+            log::warn!("SSH 自动同步连接 {} 失败: {}", conn.name, e);
+            return;
+        }
+        if pending.mcp {
+            if let Err(e) = sync_mcp_to_ssh(db_state, &session, app.clone()).await {
+                log::warn!("SSH 自动同步 MCP 到 {} 失败: {}", conn.name, e);
+            }
+        }
+        if pending.skills {
+            if let Err(e) = sync_skills_to_ssh(db_state, &session, app.clone()).await {
+                log::warn!("SSH 自动同步 Skills 到 {} 失败: {}", conn.name, e);
+            }
+        }
+    }))
+    .await;
+}
+
+/// 同步到所有同步目标并更新同步状态
+async fn run_full_sync(app: &tauri::AppHandle, config: &SSHSyncConfig, module: Option<&str>) {
+    let db_state = app.state::<DbState>();
+    let session_state = app.state::<SshSessionState>();
+
+    let result = sync_all_targets(&db_state, &session_state, app, config, module).await;
+    if !result.success {
+        log::warn!("SSH 自动同步失败: {:?}", result.errors);
+    }
+    if let Err(e) = update_sync_status(&db_state, &result).await {
+        log::warn!("{}", e);
+    }
+    let _ = app.emit("ssh-sync-completed", result);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_sync_due() {
+        let now = Local::now();
+        let mut config = SSHSyncConfig {
+            sync_interval_minutes: 30,
+            ..SSHSyncConfig::default()
+        };
+        assert!(is_sync_due(&config, now));
+
+        config.last_sync_time = Some((now - chrono::Duration::minutes(10)).to_rfc3339());
+        assert!(!is_sync_due(&config, now));
+
+        config.last_sync_time = Some((now - chrono::Duration::minutes(31)).to_rfc3339());
+        assert!(is_sync_due(&config, now));
+    }
+}
@@ -1,12 +1,15 @@
-use super::{adapter, session::SshSession, session::SshSessionState, sync};
+use super::{adapter, plan, session::SshSession, session::SshSessionState, sync};
 use super::key_file;
+use super::ssh_config::{self, SshConfig, SshConfigHost};
 use super::types::{
-    SSHConnection, SSHConnectionResult, SSHFileMapping, SSHStatusResult, SSHSyncConfig,
-    SyncProgress, SyncResult,
+    SSHConnection, SSHConnectionResult, SSHFileMapping, SSHHostSyncStatus, SSHStatusResult,
+    SSHSyncConfig, SyncProgress, SyncResult,
 };
-use crate::coding::{oh_my_opencode, oh_my_opencode_slim, open_code};
+use crate::coding::remote_tools::RemoteToolMatrix;
+use crate::coding::{oh_my_opencode, oh_my_opencode_slim, open_code, sync_plan};
 use crate::db::DbState;
 use chrono::Local;
+use futures_util::future::join_all;
 use tauri::Emitter;
 
 // ============================================================================
@@ -25,6 +28,74 @@ fn normalise_key_fields(conn: &mut SSHConnection) {
     }
 }
 
+/// 保存首次连接时信任的主机密钥
+async fn save_host_key(db_state: &DbState, conn_id: String, host_key: String) -> Result<(), String> {
+    let db = db_state.0.lock().await;
+    db.query("UPDATE type::thing('ssh_connection', $id) SET host_key = $host_key")
+        .bind(("id", conn_id))
+        .bind(("host_key", host_key))
+        .await
+        .map_err(|e| format!("Failed to save SSH host key: {}", e))?;
+    Ok(())
+}
+
+/// 按 proxy_jump 查找跳板机连接（按连接顺序）
+async fn resolve_jump_hosts(
+    db_state: &DbState,
+    conn: &SSHConnection,
+) -> Result<Vec<SSHConnection>, String> {
+    if conn.proxy_jump.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let connections = {
+        let db = db_state.0.lock().await;
+        get_ssh_config_internal(&db, false).await?.connections
+    };
+
+    let mut jumps = Vec::new();
+    expand_jump_hosts(&conn.proxy_jump, &connections, &mut vec![conn.id.clone()], &mut jumps)?;
+    Ok(jumps)
+}
+
+/// 展开跳板机链：跳板机自身配置的跳板机先于它连接
+fn expand_jump_hosts(
+    jump_ids: &[String],
+    connections: &[SSHConnection],
+    visiting: &mut Vec<String>,
+    jumps: &mut Vec<SSHConnection>,
+) -> Result<(), String> {
+    for jump_id in jump_ids {
+        if visiting.contains(jump_id) {
+            return Err(format!("跳板机配置存在循环: {}", jump_id));
+        }
+        let jump = connections
+            .iter()
+            .find(|c| &c.id == jump_id)
+            .ok_or_else(|| format!("跳板机连接不存在: {}", jump_id))?;
+
+        visiting.push(jump_id.clone());
+        expand_jump_hosts(&jump.proxy_jump, connections, visiting, jumps)?;
+        visiting.pop();
+        jumps.push(jump.clone());
+    }
+    Ok(())
+}
+
+/// 建立主连接，并保存首次连接时信任的主机密钥（包括跳板机）
+pub async fn connect_session(
+    db_state: &DbState,
+    session: &mut SshSession,
+    conn: &SSHConnection,
+) -> Result<(), String> {
+    let jumps = resolve_jump_hosts(db_state, conn).await?;
+    session.connect(conn, &jumps).await?;
+    for (conn_id, host_key) in session.take_new_host_keys() {
+        save_host_key(db_state, conn_id, host_key).await?;
+    }
+    Ok(())
+}
+
 /// 内部共享函数：从数据库读取完整 SSH 配置
 /// 参数 include_mappings 控制是否加载 file_mappings（mcp_sync/skills_sync 不需要）
 pub async fn get_ssh_config_internal(
@@ -137,19 +208,12 @@ pub async fn ssh_save_config(
     }
 
     // 连接生命周期管理
-    let mut session = session_state.0.lock().await;
     if config.enabled && !config.active_connection_id.is_empty() {
-        // 找到目标连接并建立/切换主连接
-        if let Some(conn) = config
-            .connections
-            .iter()
-            .find(|c| c.id == config.active_connection_id)
-        {
-            let _ = session.connect(conn).await;
-        }
+        // 为每个同步目标建立连接，断开不再同步的主机
+        connect_targets(&state, &session_state, &config).await;
     } else if !config.enabled {
-        // 禁用时断开主连接
-        session.disconnect().await;
+        // 禁用时断开所有连接
+        session_state.disconnect_all().await;
 
         // 清除同步状态，避免残留错误信息
         let db = state.0.lock().await;
@@ -165,18 +229,13 @@ pub async fn ssh_save_config(
     if is_being_enabled && !config.active_connection_id.is_empty() {
         log::info!("SSH sync enabled, triggering full sync...");
 
-        if session.try_acquire_sync_lock() {
-            let _ = session.ensure_connected().await;
-            let result = do_full_sync(&state, &app, &session, &config, None).await;
-            session.release_sync_lock();
-
-            if !result.errors.is_empty() {
-                log::warn!("SSH full sync errors: {:?}", result.errors);
-            }
-
-            update_sync_status(state.inner(), &result).await?;
-            let _ = app.emit("ssh-sync-completed", result);
+        let result = sync_all_targets(&state, &session_state, &app, &config, None).await;
+        if !result.errors.is_empty() {
+            log::warn!("SSH full sync errors: {:?}", result.errors);
         }
+
+        update_sync_status(state.inner(), &result).await?;
+        let _ = app.emit("ssh-sync-completed", result);
     }
 
     Ok(())
@@ -242,6 +301,22 @@ pub async fn ssh_update_connection(
 
     let db = state.0.lock().await;
 
+    // 未修改主机和端口时保留已信任的主机密钥，修改后需重新信任
+    if connection.host_key.is_empty() {
+        let existing: Vec<serde_json::Value> = db
+            .query("SELECT *, type::string(id) as id FROM type::thing('ssh_connection', $id)")
+            .bind(("id", connection.id.clone()))
+            .await
+            .map_err(|e| format!("Failed to query SSH connection: {}", e))?
+            .take(0)
+            .unwrap_or_default();
+        if let Some(existing) = existing.into_iter().next().map(adapter::connection_from_db_value) {
+            if existing.host == connection.host && existing.port == connection.port {
+                connection.host_key = existing.host_key;
+            }
+        }
+    }
+
     let conn_data = adapter::connection_to_db_value(&connection);
     let conn_id = connection.id.clone();  // 克隆以满足 'static 生命周期
     db.query("UPSERT type::thing('ssh_connection', $id) CONTENT $data")
@@ -258,9 +333,12 @@ pub async fn ssh_update_connection(
 #[tauri::command]
 pub async fn ssh_delete_connection(
     state: tauri::State<'_, DbState>,
+    session_state: tauri::State<'_, SshSessionState>,
     app: tauri::AppHandle,
     id: String,
 ) -> Result<(), String> {
+    session_state.remove(&id).await;
+
     let db = state.0.lock().await;
 
     db.query("DELETE ssh_connection WHERE id = type::thing('ssh_connection', $id)")
@@ -268,11 +346,19 @@ pub async fn ssh_delete_connection(
         .await
         .map_err(|e| format!("Failed to delete SSH connection: {}", e))?;
 
+    db.query("DELETE type::thing('ssh_host_sync_status', $id)")
+        .bind(("id", id.clone()))
+        .await
+        .map_err(|e| format!("Failed to delete SSH sync status: {}", e))?;
+
     // 如果删除的是当前活跃连接，清除 active_connection_id
     db.query("UPDATE ssh_sync_config SET active_connection_id = '' WHERE id = ssh_sync_config:`config` AND active_connection_id = $id")
-        .bind(("id", id))
+        .bind(("id", id.clone()))
         .await
         .map_err(|e| format!("Failed to clear active connection: {}", e))?;
+    drop(db);
+
+    sync_plan::clear_pushed_hashes(&state, &plan::plan_target_id(&id)).await?;
 
     let _ = app.emit("ssh-config-changed", ());
     Ok(())
@@ -294,22 +380,15 @@ pub async fn ssh_set_active_connection(
             .map_err(|e| format!("Failed to set active connection: {}", e))?;
     }
 
-    // 切换连接：找到目标连接并建立主连接
+    // 切换连接：断开不再同步的主机，同步到所有目标
     let config = ssh_get_config(state.clone()).await?;
-    if config.enabled {
-        if let Some(conn) = config
-            .connections
-            .iter()
-            .find(|c| c.id == connection_id)
-        {
-            let mut session = session_state.0.lock().await;
-            if session.connect(conn).await.is_ok() && session.try_acquire_sync_lock() {
-                let result = do_full_sync(&state, &app, &session, &config, None).await;
-                session.release_sync_lock();
-                let _ = update_sync_status(state.inner(), &result).await;
-                let _ = app.emit("ssh-sync-completed", result);
-            }
-        }
+    if config.enabled && config.connections.iter().any(|c| c.id == connection_id) {
+        let target_ids: Vec<String> = config.sync_targets().iter().map(|c| c.id.clone()).collect();
+        session_state.retain(&target_ids).await;
+
+        let result = sync_all_targets(&state, &session_state, &app, &config, None).await;
+        let _ = update_sync_status(state.inner(), &result).await;
+        let _ = app.emit("ssh-sync-completed", result);
     }
 
     let _ = app.emit("ssh-config-changed", ());
@@ -317,13 +396,91 @@ pub async fn ssh_set_active_connection(
 }
 
 /// Test an SSH connection (async, non-blocking)
+///
+/// A host key trusted for the first time is saved when the connection already exists,
+/// unsaved connections get it back in `host_key`.
 #[tauri::command]
 pub async fn ssh_test_connection(
+    state: tauri::State<'_, DbState>,
     mut connection: SSHConnection,
-) -> SSHConnectionResult {
+) -> Result<SSHConnectionResult, String> {
     normalise_key_fields(&mut connection);
 
-    sync::test_connection(&connection).await
+    let jumps = resolve_jump_hosts(&state, &connection).await?;
+    let result = sync::test_connection(&connection, &jumps).await;
+    if let (Some(host_key), false) = (&result.host_key, connection.id.is_empty()) {
+        save_host_key(&state, connection.id.clone(), host_key.clone()).await?;
+    }
+    Ok(result)
+}
+
+/// Forget the trusted host key of a connection, the next connect trusts the server key again
+#[tauri::command]
+pub async fn ssh_reset_host_key(
+    state: tauri::State<'_, DbState>,
+    session_state: tauri::State<'_, SshSessionState>,
+    app: tauri::AppHandle,
+    id: String,
+) -> Result<(), String> {
+    {
+        let db = state.0.lock().await;
+        db.query("UPDATE type::thing('ssh_connection', $id) SET host_key = ''")
+            .bind(("id", id.clone()))
+            .await
+            .map_err(|e| format!("Failed to reset SSH host key: {}", e))?;
+    }
+
+    for session in session_state.sessions().await {
+        session.lock().await.reset_host_key(&id);
+    }
+
+    let _ = app.emit("ssh-config-changed", ());
+    Ok(())
+}
+
+/// List the Host entries of the local ~/.ssh/config that can be imported
+#[tauri::command]
+pub fn ssh_list_ssh_config_hosts() -> Result<Vec<SshConfigHost>, String> {
+    Ok(SshConfig::read_local()?.hosts())
+}
+
+/// Import Host entries of the local ~/.ssh/config as connection presets
+///
+/// Jump hosts referenced by ProxyJump are imported too. Returns the created connections.
+#[tauri::command]
+pub async fn ssh_import_ssh_config(
+    state: tauri::State<'_, DbState>,
+    app: tauri::AppHandle,
+    aliases: Vec<String>,
+) -> Result<Vec<SSHConnection>, String> {
+    let config = SshConfig::read_local()?;
+    let db = state.0.lock().await;
+
+    let existing = get_ssh_config_internal(&db, false).await?.connections;
+    let created = ssh_config::build_connections(&config, &aliases, &existing, &default_identity_file())?;
+
+    for connection in &created {
+        db.query("UPSERT type::thing('ssh_connection', $id) CONTENT $data")
+            .bind(("id", connection.id.clone()))
+            .bind(("data", adapter::connection_to_db_value(connection)))
+            .await
+            .map_err(|e| format!("Failed to create SSH connection: {}", e))?;
+    }
+
+    let _ = app.emit("ssh-config-changed", ());
+    Ok(created)
+}
+
+/// OpenSSH 默认私钥（~/.ssh/id_ed25519、id_ecdsa、id_rsa 中第一个存在的）
+fn default_identity_file() -> String {
+    let Some(ssh_dir) = dirs::home_dir().map(|home| home.join(".ssh")) else {
+        return String::new();
+    };
+    ["id_ed25519", "id_ecdsa", "id_rsa"]
+        .iter()
+        .find(|name| ssh_dir.join(name).exists())
+        .map(|name| format!("~/.ssh/{}", name))
+        .unwrap_or_default()
 }
 
 // ============================================================================
@@ -429,6 +586,7 @@ pub async fn do_full_sync(
             current: 0,
             total: total_files,
             message: format!("文件同步: 0/{}", total_files),
+            connection_id: session.conn().map(|c| c.id.clone()),
         },
     );
 
@@ -454,9 +612,111 @@ pub async fn do_full_sync(
         }
     }
 
+    // 记录推送后的远程哈希，供同步计划判断远程修改
+    plan::record_pushed(state, session, &file_mappings, module, &result.errors).await;
+
+    result
+}
+
+fn failed_result(error: String) -> SyncResult {
+    SyncResult {
+        success: false,
+        synced_files: vec![],
+        skipped_files: vec![],
+        errors: vec![error],
+    }
+}
+
+/// 为所有同步目标建立连接，断开不再同步的主机
+pub async fn connect_targets(
+    state: &DbState,
+    session_state: &SshSessionState,
+    config: &SSHSyncConfig,
+) {
+    let targets = config.sync_targets();
+    let target_ids: Vec<String> = targets.iter().map(|c| c.id.clone()).collect();
+    session_state.retain(&target_ids).await;
+
+    join_all(targets.into_iter().map(|conn| async move {
+        let session = session_state.session(&conn.id).await;
+        let mut session = session.lock().await;
+        if let Err(e) = connect_session(state, &mut session, conn).await {
+            log::warn!("SSH 连接 {} 失败: {}", conn.name, e);
+        }
+    }))
+    .await;
+}
+
+/// 同步到单个主机，使用该连接自己的会话
+async fn sync_target(
+    state: &DbState,
+    session_state: &SshSessionState,
+    app: &tauri::AppHandle,
+    config: &SSHSyncConfig,
+    conn: &SSHConnection,
+    module: Option<&str>,
+) -> SyncResult {
+    let session = session_state.session(&conn.id).await;
+    let mut session = session.lock().await;
+
+    // 并发控制：如果该主机正在同步，直接返回
+    if !session.try_acquire_sync_lock() {
+        return failed_result("另一个同步操作正在进行中".to_string());
+    }
+
+    // 确保连接可用（自动重连）
+    let result = match connect_session(state, &mut session, conn).await {
+        Ok(()) => do_full_sync(state, app, &session, config, module).await,
+        Err(e) => failed_result(format!("SSH 连接失败: {}", e)),
+    };
+
+    session.release_sync_lock();
     result
 }
 
+/// 并发同步到所有同步目标（活动连接和勾选为同步目标的连接），
+/// 记录每个主机的同步状态并返回合并后的结果
+pub async fn sync_all_targets(
+    state: &DbState,
+    session_state: &SshSessionState,
+    app: &tauri::AppHandle,
+    config: &SSHSyncConfig,
+    module: Option<&str>,
+) -> SyncResult {
+    let results = join_all(config.sync_targets().into_iter().map(|conn| async move {
+        let result = sync_target(state, session_state, app, config, conn, module).await;
+        if let Err(e) = update_host_sync_status(state, &conn.id, &result).await {
+            log::warn!("{}", e);
+        }
+        (conn, result)
+    }))
+    .await;
+
+    merge_sync_results(results)
+}
+
+/// 合并多个主机的同步结果，多主机时文件和错误前加上主机名
+fn merge_sync_results(mut results: Vec<(&SSHConnection, SyncResult)>) -> SyncResult {
+    if results.len() == 1 {
+        return results.remove(0).1;
+    }
+
+    let mut merged = SyncResult {
+        success: true,
+        synced_files: vec![],
+        skipped_files: vec![],
+        errors: vec![],
+    };
+    for (conn, result) in results {
+        let tag = |item: String| format!("[{}] {}", conn.name, item);
+        merged.success &= result.success;
+        merged.synced_files.extend(result.synced_files.into_iter().map(tag));
+        merged.skipped_files.extend(result.skipped_files.into_iter().map(tag));
+        merged.errors.extend(result.errors.into_iter().map(tag));
+    }
+    merged
+}
+
 /// Sync file mappings with progress events
 async fn sync_mappings_with_progress(
     mappings: &[SSHFileMapping],
@@ -487,6 +747,7 @@ async fn sync_mappings_with_progress(
                 current,
                 total,
                 message: format!("文件同步: {}/{} - {}", current, total, mapping.name),
+                connection_id: session.conn().map(|c| c.id.clone()),
             },
         );
 
@@ -521,41 +782,11 @@ pub async fn ssh_sync(
 ) -> Result<SyncResult, String> {
     let config = ssh_get_config(state.clone()).await?;
 
-    if !config.enabled || config.active_connection_id.is_empty() {
-        return Ok(SyncResult {
-            success: false,
-            synced_files: vec![],
-            skipped_files: vec![],
-            errors: vec!["SSH 同步未启用".to_string()],
-        });
+    if !config.enabled || config.sync_targets().is_empty() {
+        return Ok(failed_result("SSH 同步未启用".to_string()));
     }
 
-    let mut session = session_state.0.lock().await;
-
-    // 并发控制：如果正在同步，直接返回
-    if !session.try_acquire_sync_lock() {
-        return Ok(SyncResult {
-            success: false,
-            synced_files: vec![],
-            skipped_files: vec![],
-            errors: vec!["另一个同步操作正在进行中".to_string()],
-        });
-    }
-
-    // 确保连接可用（自动重连）
-    if let Err(e) = session.ensure_connected().await {
-        session.release_sync_lock();
-        return Ok(SyncResult {
-            success: false,
-            synced_files: vec![],
-            skipped_files: vec![],
-            errors: vec![format!("SSH 连接失败: {}", e)],
-        });
-    }
-
-    let result = do_full_sync(&state, &app, &session, &config, module.as_deref()).await;
-
-    session.release_sync_lock();
+    let result = sync_all_targets(&state, &session_state, &app, &config, module.as_deref()).await;
 
     update_sync_status(state.inner(), &result).await?;
     let _ = app.emit("ssh-sync-completed", result.clone());
@@ -566,7 +797,7 @@ pub async fn ssh_sync(
 /// Get SSH sync status
 #[tauri::command]
 pub async fn ssh_get_status(state: tauri::State<'_, DbState>) -> Result<SSHStatusResult, String> {
-    let config = ssh_get_config(state).await?;
+    let config = ssh_get_config(state.clone()).await?;
 
     let active_connection_name = if config.enabled && !config.active_connection_id.is_empty() {
         config
@@ -578,15 +809,51 @@ pub async fn ssh_get_status(state: tauri::State<'_, DbState>) -> Result<SSHStatu
         None
     };
 
+    let hosts = get_host_sync_statuses(&state, &config).await?;
+
     Ok(SSHStatusResult {
         ssh_available: config.enabled && active_connection_name.is_some(),
         active_connection_name,
         last_sync_time: config.last_sync_time,
         last_sync_status: config.last_sync_status,
         last_sync_error: config.last_sync_error,
+        hosts,
     })
 }
 
+/// 检测每个同步目标主机上安装了哪些工具（按主机返回工具矩阵）
+#[tauri::command]
+pub async fn ssh_detect_remote_tools(
+    state: tauri::State<'_, DbState>,
+    session_state: tauri::State<'_, SshSessionState>,
+) -> Result<Vec<RemoteToolMatrix>, String> {
+    let config = ssh_get_config(state.clone()).await?;
+    let (state, session_state) = (state.inner(), session_state.inner());
+
+    let matrices = join_all(config.sync_targets().into_iter().map(|conn| async move {
+        let mut matrix = RemoteToolMatrix {
+            target_id: plan::plan_target_id(&conn.id),
+            target_name: conn.name.clone(),
+            ..Default::default()
+        };
+
+        let session = session_state.session(&conn.id).await;
+        let mut session = session.lock().await;
+        let detected = match connect_session(state, &mut session, conn).await {
+            Ok(()) => sync::detect_remote_tools(&session).await,
+            Err(e) => Err(format!("SSH 连接失败: {}", e)),
+        };
+        match detected {
+            Ok(tools) => matrix.tools = tools,
+            Err(e) => matrix.error = Some(e),
+        }
+        matrix
+    }))
+    .await;
+
+    Ok(matrices)
+}
+
 /// Test if a local path exists
 #[tauri::command]
 pub fn ssh_test_local_path(local_path: String) -> Result<bool, String> {
@@ -675,6 +942,67 @@ pub async fn update_sync_status(
     Ok(())
 }
 
+/// Update the sync status of one host in database
+pub async fn update_host_sync_status(
+    state: &DbState,
+    conn_id: &str,
+    result: &SyncResult,
+) -> Result<(), String> {
+    let db = state.0.lock().await;
+
+    let data = serde_json::json!({
+        "last_sync_time": Local::now().to_rfc3339(),
+        "last_sync_status": if result.success { "success" } else { "error" },
+        "last_sync_error": if result.success { None } else { Some(result.errors.join("; ")) },
+    });
+
+    db.query("UPSERT type::thing('ssh_host_sync_status', $id) CONTENT $data")
+        .bind(("id", conn_id.to_string()))
+        .bind(("data", data))
+        .await
+        .map_err(|e| format!("Failed to update SSH host sync status: {}", e))?;
+
+    Ok(())
+}
+
+/// Get the sync status of every sync target
+async fn get_host_sync_statuses(
+    state: &DbState,
+    config: &SSHSyncConfig,
+) -> Result<Vec<SSHHostSyncStatus>, String> {
+    let db = state.0.lock().await;
+
+    let records: Vec<serde_json::Value> = db
+        .query("SELECT *, type::string(id) as id FROM ssh_host_sync_status")
+        .await
+        .map_err(|e| format!("Failed to query SSH host sync status: {}", e))?
+        .take(0)
+        .unwrap_or_default();
+
+    Ok(config
+        .sync_targets()
+        .into_iter()
+        .map(|conn| {
+            let record = records
+                .iter()
+                .find(|r| super::super::db_id::db_extract_id(r) == conn.id);
+            let field = |name: &str| {
+                record
+                    .and_then(|r| r.get(name))
+                    .and_then(|v| v.as_str())
+                    .map(String::from)
+            };
+            SSHHostSyncStatus {
+                connection_id: conn.id.clone(),
+                connection_name: conn.name.clone(),
+                last_sync_time: field("last_sync_time"),
+                last_sync_status: field("last_sync_status").unwrap_or_else(|| "never".to_string()),
+                last_sync_error: field("last_sync_error"),
+            }
+        })
+        .collect())
+}
+
 /// Get default file mappings for SSH sync
 pub fn default_file_mappings() -> Vec<SSHFileMapping> {
     vec![
@@ -0,0 +1,174 @@
+//! SSH 主机密钥校验（StrictHostKeyChecking=accept-new 语义）
+//!
+//! 校验顺序：连接上保存的已信任密钥 → ~/.ssh/known_hosts（支持哈希条目）→ 首次连接时信任。
+//! 任一来源记录的密钥与服务器提供的不一致时拒绝连接；known_hosts 无法读取或解析时
+//! 同样拒绝，不回退为首次信任。
+
+use std::path::{Path, PathBuf};
+
+use russh::keys::ssh_key::{HashAlg, PublicKey};
+
+/// 主机密钥校验结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum HostKeyVerdict {
+    /// 与连接上保存的密钥一致
+    Pinned,
+    /// 与 ~/.ssh/known_hosts 中的记录一致
+    KnownHosts,
+    /// 首次连接，按 accept-new 信任（OpenSSH 格式的公钥）
+    New(String),
+    /// 密钥不一致，拒绝连接（错误信息）
+    Mismatch(String),
+    /// known_hosts 无法读取或解析，无法确认密钥，拒绝连接（错误信息）
+    Unverifiable(String),
+}
+
+impl HostKeyVerdict {
+    /// 返回给前端的状态标识
+    pub fn status(&self) -> &'static str {
+        match self {
+            HostKeyVerdict::Pinned => "pinned",
+            HostKeyVerdict::KnownHosts => "knownHosts",
+            HostKeyVerdict::New(_) => "new",
+            HostKeyVerdict::Mismatch(_) => "mismatch",
+            HostKeyVerdict::Unverifiable(_) => "unverifiable",
+        }
+    }
+
+    /// 拒绝连接时的错误信息
+    pub fn rejection(&self) -> Option<&str> {
+        match self {
+            HostKeyVerdict::Mismatch(msg) | HostKeyVerdict::Unverifiable(msg) => Some(msg),
+            _ => None,
+        }
+    }
+}
+
+/// SHA256 指纹，格式与 `ssh-keygen -l` 一致（SHA256:...）
+pub fn fingerprint(key: &PublicKey) -> String {
+    key.fingerprint(HashAlg::Sha256).to_string()
+}
+
+/// OpenSSH 格式的公钥（algorithm base64），用于保存到连接配置
+pub fn format_key(key: &PublicKey) -> String {
+    let mut key = key.clone();
+    key.set_comment("");
+    key.to_openssh().unwrap_or_default()
+}
+
+fn known_hosts_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".ssh").join("known_hosts"))
+}
+
+/// 校验服务器公钥
+pub fn verify_host_key(host: &str, port: u16, pinned: &str, key: &PublicKey) -> HostKeyVerdict {
+    verify_with_known_hosts(host, port, pinned, key, known_hosts_path().as_deref())
+}
+
+fn verify_with_known_hosts(
+    host: &str,
+    port: u16,
+    pinned: &str,
+    key: &PublicKey,
+    known_hosts: Option<&Path>,
+) -> HostKeyVerdict {
+    let pinned = pinned.trim();
+    if !pinned.is_empty() {
+        return match PublicKey::from_openssh(pinned) {
+            Ok(trusted) if trusted.key_data() == key.key_data() => HostKeyVerdict::Pinned,
+            Ok(trusted) => HostKeyVerdict::Mismatch(format!(
+                "主机密钥不匹配: {}:{} 的服务器密钥 ({}) 与已信任的密钥 ({}) 不一致，可能存在中间人攻击。如服务器确实更换了密钥，请在连接设置中重置主机密钥后重试",
+                host,
+                port,
+                fingerprint(key),
+                fingerprint(&trusted)
+            )),
+            Err(e) => HostKeyVerdict::Mismatch(format!("已保存的主机密钥无效: {}", e)),
+        };
+    }
+
+    // 没有 home 目录时等同于 known_hosts 不存在
+    let Some(known_hosts) = known_hosts else {
+        return HostKeyVerdict::New(format_key(key));
+    };
+    match russh::keys::check_known_hosts_path(host, port, key, known_hosts) {
+        Ok(true) => HostKeyVerdict::KnownHosts,
+        Ok(false) => HostKeyVerdict::New(format_key(key)),
+        Err(russh::keys::Error::KeyChanged { line }) => HostKeyVerdict::Mismatch(format!(
+            "主机密钥不匹配: {}:{} 的服务器密钥 ({}) 与 ~/.ssh/known_hosts 第 {} 行的记录不一致，可能存在中间人攻击。如服务器确实更换了密钥，请先更新 known_hosts",
+            host,
+            port,
+            fingerprint(key),
+            line
+        )),
+        Err(e) => HostKeyVerdict::Unverifiable(format!(
+            "无法校验 {}:{} 的主机密钥: 读取 {} 失败: {}。请修复该文件后重试",
+            host,
+            port,
+            known_hosts.display(),
+            e
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_A: &str = "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAILM+rvN+ot98qgEN796jTiQfZfG1KaT0PtFDJ/XFSqti";
+    const KEY_B: &str = "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8g";
+
+    #[test]
+    fn test_pinned_key() {
+        let key = PublicKey::from_openssh(KEY_A).unwrap();
+        assert_eq!(verify_host_key("example.com", 22, KEY_A, &key), HostKeyVerdict::Pinned);
+        assert_eq!(format_key(&key), KEY_A);
+
+        let other = PublicKey::from_openssh(KEY_B).unwrap();
+        assert!(matches!(
+            verify_host_key("example.com", 22, KEY_A, &other),
+            HostKeyVerdict::Mismatch(_)
+        ));
+    }
+
+    #[test]
+    fn test_known_hosts() {
+        let dir = std::env::temp_dir().join(format!("ai-toolbox-known-hosts-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("known_hosts");
+        std::fs::write(
+            &path,
+            format!(
+                "# comment\nexample.com,10.0.0.1 {a}\n|1|AQIDBAUGBwgJCgsMDQ4PEBESExQ=|w/dydvw8h8t5kyyF70RcMB78ae8= {a}\nbroken.example.com ssh-ed25519 !!!\n",
+                a = KEY_A
+            ),
+        )
+        .unwrap();
+        let key = PublicKey::from_openssh(KEY_A).unwrap();
+        let other = PublicKey::from_openssh(KEY_B).unwrap();
+        let verify = |host: &str, port: u16, key: &PublicKey| {
+            verify_with_known_hosts(host, port, "", key, Some(&path))
+        };
+
+        // 明文条目
+        assert_eq!(verify("example.com", 22, &key), HostKeyVerdict::KnownHosts);
+        assert_eq!(verify("10.0.0.1", 22, &key), HostKeyVerdict::KnownHosts);
+        // 哈希条目（[example.org]:2222）
+        assert_eq!(verify("example.org", 2222, &key), HostKeyVerdict::KnownHosts);
+        assert_eq!(verify("example.org", 22, &key), HostKeyVerdict::New(KEY_A.to_string()));
+        // 记录的密钥与服务器密钥不一致
+        assert!(matches!(verify("example.com", 22, &other), HostKeyVerdict::Mismatch(_)));
+        assert!(matches!(verify("example.org", 2222, &other), HostKeyVerdict::Mismatch(_)));
+        // 无法解析的条目不回退为首次信任
+        let verdict = verify("broken.example.com", 22, &key);
+        assert!(matches!(verdict, HostKeyVerdict::Unverifiable(_)));
+        assert!(verdict.rejection().is_some());
+        // 文件不存在时按首次连接处理
+        assert_eq!(
+            verify_with_known_hosts("example.com", 22, "", &key, Some(&dir.join("missing"))),
+            HostKeyVerdict::New(KEY_A.to_string())
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,199 @@
+//! 增量同步清单
+//!
+//! 远程目录中保存一份清单，记录上次同步时每个文件的 SHA-256。
+//! 再次同步时只上传与清单不一致的文件，并删除本地已不存在的文件。
+//! 清单在文件全部上传后最后写入，同步中断时下次会重新上传未确认的文件。
+//! 远程文件被手动修改时清单无法感知，删除清单即可触发一次完整同步。
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use walkdir::WalkDir;
+
+use super::session::SshSession;
+use super::sync::{read_remote_file_raw, write_remote_file};
+
+/// 目录同步清单文件名（位于远程目录内）
+pub const DIR_MANIFEST_NAME: &str = ".ai-toolbox-sync.json";
+
+/// 目录同步清单：相对路径（/ 分隔）→ 文件 SHA-256
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DirManifest {
+    pub files: BTreeMap<String, String>,
+}
+
+/// 清单对比结果
+#[derive(Debug, Default, PartialEq)]
+pub struct ManifestDiff {
+    /// 新增或内容变化的文件
+    pub changed: Vec<String>,
+    /// 本地已删除的文件
+    pub removed: Vec<String>,
+}
+
+impl ManifestDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl DirManifest {
+    /// 计算本地目录的清单（跟随符号链接，与上传行为一致）
+    pub fn from_local_dir(dir: &Path) -> Result<Self, String> {
+        let mut files = BTreeMap::new();
+        for entry in WalkDir::new(dir).follow_links(true) {
+            let entry = entry.map_err(|e| format!("读取本地目录失败 {}: {}", dir.display(), e))?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+
+            let relative = entry
+                .path()
+                .strip_prefix(dir)
+                .map_err(|e| format!("计算相对路径失败: {}", e))?
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join("/");
+            if relative == DIR_MANIFEST_NAME {
+                continue;
+            }
+
+            let bytes = std::fs::read(entry.path())
+                .map_err(|e| format!("读取文件失败 {}: {}", entry.path().display(), e))?;
+            files.insert(relative, hex::encode(Sha256::digest(&bytes)));
+        }
+        Ok(Self { files })
+    }
+
+    /// 与远程清单对比，得到需要上传和删除的文件
+    pub fn diff(&self, remote: &DirManifest) -> ManifestDiff {
+        ManifestDiff {
+            changed: self
+                .files
+                .iter()
+                .filter(|(path, hash)| remote.files.get(*path) != Some(*hash))
+                .map(|(path, _)| path.clone())
+                .collect(),
+            // 清单来自远程，忽略绝对路径和包含 .. 的路径
+            removed: remote
+                .files
+                .keys()
+                .filter(|path| !self.files.contains_key(*path))
+                .filter(|path| is_safe_relative_path(path))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+/// 是否为目录内的相对路径：非空、不以 / 或盘符开头、不含 \、. 和 ..
+pub(super) fn is_safe_relative_path(path: &str) -> bool {
+    let drive_prefix = path.as_bytes().get(1) == Some(&b':');
+    !path.is_empty()
+        && !path.starts_with('/')
+        && !drive_prefix
+        && !path.contains('\\')
+        && path
+            .split('/')
+            .all(|part| !part.is_empty() && part != "." && part != "..")
+}
+
+/// Skills 同步清单（位于远程 Skills 中央目录）：skill 名称 → 上次同步的状态
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SkillsManifest {
+    pub skills: BTreeMap<String, SyncedSkill>,
+}
+
+/// 上次同步的 skill 内容 hash 和已链接的工具
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncedSkill {
+    pub content_hash: String,
+    pub tools: Vec<String>,
+}
+
+/// 读取远程 JSON 清单，不存在或无法解析时返回 None
+pub async fn read_remote_manifest<T: serde::de::DeserializeOwned>(
+    session: &SshSession,
+    path: &str,
+) -> Option<T> {
+    let content = read_remote_file_raw(session, path).await.ok()?;
+    let content = content.trim();
+    if content.is_empty() {
+        return None;
+    }
+    match serde_json::from_str(content) {
+        Ok(manifest) => Some(manifest),
+        Err(e) => {
+            log::warn!("远程同步清单 {} 无法解析，将完整同步: {}", path, e);
+            None
+        }
+    }
+}
+
+/// 写入远程 JSON 清单
+pub async fn write_remote_manifest<T: Serialize>(
+    session: &SshSession,
+    path: &str,
+    manifest: &T,
+) -> Result<(), String> {
+    let content = serde_json::to_string(manifest)
+        .map_err(|e| format!("序列化同步清单失败: {}", e))?;
+    write_remote_file(session, path, &content).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(files: &[(&str, &str)]) -> DirManifest {
+        DirManifest {
+            files: files
+                .iter()
+                .map(|(p, h)| (p.to_string(), h.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let local = manifest(&[("SKILL.md", "a"), ("scripts/run.sh", "b"), ("new.txt", "c")]);
+        let remote = manifest(&[("SKILL.md", "a"), ("scripts/run.sh", "old"), ("gone.txt", "d")]);
+
+        let diff = local.diff(&remote);
+        assert_eq!(diff.changed, vec!["new.txt".to_string(), "scripts/run.sh".to_string()]);
+        assert_eq!(diff.removed, vec!["gone.txt".to_string()]);
+        assert!(local.diff(&local).is_empty());
+
+        let unsafe_remote = manifest(&[
+            ("../outside.txt", "x"),
+            ("/etc/passwd", "x"),
+            ("a/../../b", "x"),
+            ("C:/Windows/x", "x"),
+            ("dir\\..\\x", "x"),
+            ("ok/file.txt", "x"),
+        ]);
+        assert_eq!(local.diff(&unsafe_remote).removed, vec!["ok/file.txt".to_string()]);
+    }
+
+    #[test]
+    fn test_from_local_dir() {
+        let dir = std::env::temp_dir().join(format!("ai-toolbox-manifest-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::write(dir.join("a.txt"), "hello").unwrap();
+        std::fs::write(dir.join("sub").join("b.txt"), "world").unwrap();
+        std::fs::write(dir.join(DIR_MANIFEST_NAME), "{}").unwrap();
+
+        let local = DirManifest::from_local_dir(&dir).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(local.files.len(), 2);
+        assert_eq!(
+            local.files["a.txt"],
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert!(local.files.contains_key("sub/b.txt"));
+    }
+}
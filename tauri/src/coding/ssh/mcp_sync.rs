@@ -10,10 +10,14 @@ use tauri::{AppHandle, Emitter};
 
 use super::commands::resolve_dynamic_paths;
 use super::session::SshSession;
-use super::sync::{read_remote_file, sync_mappings, write_remote_file};
+use super::sync::{detect_remote_tools, read_remote_file, sync_mappings, write_remote_file};
 use super::types::{SSHFileMapping, SyncProgress};
 use crate::coding::mcp::command_normalize;
+use crate::coding::mcp::config_sync::apply_target_env_to_content;
+use crate::coding::mcp::env_vars::{self, EnvResolver};
 use crate::coding::mcp::mcp_store;
+use crate::coding::mcp::types::McpServer;
+use crate::coding::remote_tools::installed_tool_keys;
 use crate::DbState;
 
 /// Get file mappings from database
@@ -61,17 +65,26 @@ pub async fn sync_mcp_to_ssh(
             current: 1,
             total: 2,
             message: "MCP 同步: Claude Code...".to_string(),
+            connection_id: session.conn().map(|c| c.id.clone()),
         },
     );
 
+    // Only push MCP config to tools installed on the remote
+    let installed_tools = installed_tool_keys(detect_remote_tools(session).await);
+
     // 1. Claude Code: directly modify remote ~/.claude.json
     let servers = mcp_store::get_mcp_servers(state).await?;
+    // Variable values for this host (`${NAME}` placeholders)
+    let target = session.conn().map(|c| env_vars::ssh_target(&c.id));
+    let env = EnvResolver::load(state, target.as_deref()).await?;
     let claude_servers: Vec<_> = servers
         .iter()
         .filter(|s| s.enabled_tools.contains(&"claude_code".to_string()))
         .collect();
 
-    if let Err(e) = sync_mcp_to_ssh_claude(session, &claude_servers).await {
+    if !installed_tools.contains("claude_code") {
+        info!("Skipped claude.json MCP sync: Claude Code is not installed on the remote");
+    } else if let Err(e) = sync_mcp_to_ssh_claude(session, &claude_servers, &env).await {
         log::warn!("Skipped claude.json MCP sync: {}", e);
         all_errors.push(format!("Claude Code: {}", e));
         let _ = app.emit(
@@ -92,6 +105,7 @@ pub async fn sync_mcp_to_ssh(
             current: 2,
             total: 2,
             message: "MCP 同步: OpenCode/Codex...".to_string(),
+            connection_id: session.conn().map(|c| c.id.clone()),
         },
     );
 
@@ -102,6 +116,7 @@ pub async fn sync_mcp_to_ssh(
             let mcp_mappings: Vec<_> = file_mappings
                 .into_iter()
                 .filter(|m| m.enabled && mcp_modules.contains(&m.module.as_str()))
+                .filter(|m| installed_tools.contains(&m.module))
                 .collect();
 
             if !mcp_mappings.is_empty() {
@@ -128,6 +143,16 @@ pub async fn sync_mcp_to_ssh(
                         && is_mcp_config_file(&mapping.id)
                         && synced_paths.contains(&mapping.remote_path)
                     {
+                        if let Err(e) = apply_target_env_to_remote_mcp_file(
+                            session,
+                            &mapping.remote_path,
+                            &mapping.module,
+                            &servers,
+                            &env,
+                        ).await {
+                            log::warn!("Failed to apply MCP variables to {}: {}", mapping.remote_path, e);
+                            all_errors.push(format!("{}: {}", mapping.remote_path, e));
+                        }
                         if let Err(e) = strip_cmd_c_from_remote_mcp_file(
                             session,
                             &mapping.remote_path,
@@ -170,7 +195,8 @@ pub async fn sync_mcp_to_ssh(
 /// Sync MCP servers to remote Claude Code ~/.claude.json
 async fn sync_mcp_to_ssh_claude(
     session: &SshSession,
-    servers: &[&crate::coding::mcp::types::McpServer],
+    servers: &[&McpServer],
+    env: &EnvResolver,
 ) -> Result<(), String> {
     let config_path = "~/.claude.json";
 
@@ -188,7 +214,7 @@ async fn sync_mcp_to_ssh_claude(
     // Build mcpServers object
     let mut mcp_servers = serde_json::Map::new();
     for server in servers {
-        let server_config = build_standard_server_config(server);
+        let server_config = build_standard_server_config(&env.resolve_server(server)?);
         mcp_servers.insert(server.name.clone(), server_config);
     }
 
@@ -207,7 +233,7 @@ async fn sync_mcp_to_ssh_claude(
 }
 
 /// Build standard JSON server config for Claude Code format
-fn build_standard_server_config(server: &crate::coding::mcp::types::McpServer) -> Value {
+fn build_standard_server_config(server: &McpServer) -> Value {
     match server.server_type.as_str() {
         "stdio" => {
             let command = server
@@ -281,6 +307,29 @@ fn is_mcp_config_file(mapping_id: &str) -> bool {
     )
 }
 
+/// Apply this host's variable values to the servers with placeholders in a synced
+/// MCP config file (the local copy holds the local values)
+async fn apply_target_env_to_remote_mcp_file(
+    session: &SshSession,
+    remote_path: &str,
+    module: &str,
+    servers: &[McpServer],
+    env: &EnvResolver,
+) -> Result<(), String> {
+    if module == "codex" && !remote_path.ends_with(".toml") {
+        return Ok(());
+    }
+    let content = read_remote_file(session, remote_path).await?;
+    let processed = apply_target_env_to_content(&content, module, servers, env)?;
+
+    if processed != content {
+        write_remote_file(session, remote_path, &processed).await?;
+        info!("Applied MCP variables to remote config: {}", remote_path);
+    }
+
+    Ok(())
+}
+
 /// Strip cmd /c from remote MCP config file after sync
 async fn strip_cmd_c_from_remote_mcp_file(
     session: &SshSession,
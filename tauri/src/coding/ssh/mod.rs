@@ -1,14 +1,27 @@
 mod types;
 mod adapter;
+mod agent;
+mod auto_sync;
+mod host_key;
+mod manifest;
 mod session;
+mod sftp_ops;
 mod sync;
 mod commands;
 mod mcp_sync;
+mod plan;
+mod pull;
 mod skills_sync;
+mod ssh_config;
 pub mod key_file;
 
 pub use types::*;
+pub use auto_sync::{request_auto_sync, start_scheduled_sync, AutoSyncTrigger, SshAutoSyncState};
+pub use ssh_config::{SshConfigHost, SshConfigJump};
 pub use session::*;
 pub use commands::*;
+pub use plan::*;
+pub use pull::*;
 pub use mcp_sync::sync_mcp_to_ssh;
 pub use skills_sync::sync_skills_to_ssh;
+pub use manifest::DIR_MANIFEST_NAME;
@@ -0,0 +1,142 @@
+//! 同步计划：同步前逐个文件映射报告远程文件的状态
+//!
+//! 对比本地内容、远程当前内容和上次推送后记录的远程哈希，得出远程文件是缺失、
+//! 相同、旧于本地，还是推送后在远程被修改过（同步会覆盖这些修改）。
+
+use super::commands::{connect_session, get_ssh_config_internal, resolve_dynamic_paths};
+use super::session::{SshSession, SshSessionState};
+use super::sftp_ops;
+use super::sync::expand_local_path;
+use super::types::SSHFileMapping;
+use crate::coding::sync_plan::{
+    self, combined_hash, get_pushed_hashes, local_hashes, parse_remote_hashes, plan_item,
+    remote_hash_script, FileHashes, MappingKind, SyncPlan,
+};
+use crate::db::DbState;
+use futures_util::future::join_all;
+
+/// 推送哈希记录的同步目标 ID
+pub(super) fn plan_target_id(conn_id: &str) -> String {
+    format!("ssh:{}", conn_id)
+}
+
+fn mapping_kind(mapping: &SSHFileMapping) -> MappingKind {
+    MappingKind::of(mapping.is_directory, mapping.is_pattern)
+}
+
+/// 计算映射在远程的文件哈希，远程不存在时返回 None
+async fn remote_hashes(
+    session: &SshSession,
+    mapping: &SSHFileMapping,
+) -> Result<Option<FileHashes>, String> {
+    let kind = mapping_kind(mapping);
+    if session.sftp_only() {
+        let sftp = session.create_sftp_session().await?;
+        return sftp_ops::remote_hashes(&sftp, &mapping.remote_path, &mapping.local_path, kind).await;
+    }
+
+    let script = remote_hash_script(&mapping.remote_path, &mapping.local_path, kind)?;
+    let output = session.exec_command(&script).await?;
+    Ok(parse_remote_hashes(&output, kind))
+}
+
+/// 按模块过滤启用的映射，并解析动态配置路径
+fn plan_mappings(mappings: &[SSHFileMapping], module: Option<&str>) -> Vec<SSHFileMapping> {
+    resolve_dynamic_paths(mappings.to_vec())
+        .into_iter()
+        .filter(|m| m.enabled)
+        .filter(|m| module.is_none() || Some(m.module.as_str()) == module)
+        .collect()
+}
+
+/// 同步完成后记录远程哈希，作为下次判断远程是否被修改的基准
+///
+/// 同步出错的映射不记录（`errors` 中以 "映射名: " 开头的条目）。
+pub(super) async fn record_pushed(
+    state: &DbState,
+    session: &SshSession,
+    mappings: &[SSHFileMapping],
+    module: Option<&str>,
+    errors: &[String],
+) {
+    let Some(conn) = session.conn() else {
+        return;
+    };
+
+    let mut hashes = Vec::new();
+    for mapping in plan_mappings(mappings, module) {
+        let prefix = format!("{}: ", mapping.name);
+        if errors.iter().any(|e| e.starts_with(&prefix)) {
+            continue;
+        }
+        match remote_hashes(session, &mapping).await {
+            Ok(remote) => hashes.push((mapping.id.clone(), remote.as_ref().map(combined_hash))),
+            Err(e) => log::warn!("读取远程文件哈希失败 {}: {}", mapping.name, e),
+        }
+    }
+
+    if let Err(e) = sync_plan::record_pushed_hashes(state, &plan_target_id(&conn.id), hashes).await {
+        log::warn!("{}", e);
+    }
+}
+
+/// 生成同步计划：对每个同步目标主机，逐个映射报告远程文件状态
+#[tauri::command]
+pub async fn ssh_sync_plan(
+    state: tauri::State<'_, DbState>,
+    session_state: tauri::State<'_, SshSessionState>,
+    module: Option<String>,
+) -> Result<Vec<SyncPlan>, String> {
+    let config = {
+        let db = state.0.lock().await;
+        get_ssh_config_internal(&db, true).await?
+    };
+    let mappings = plan_mappings(&config.file_mappings, module.as_deref());
+    let state = state.inner();
+    let session_state = session_state.inner();
+    let mappings = &mappings;
+
+    let plans = join_all(config.sync_targets().into_iter().map(|conn| async move {
+        let target_id = plan_target_id(&conn.id);
+        let mut plan = SyncPlan {
+            target_id: target_id.clone(),
+            target_name: conn.name.clone(),
+            ..Default::default()
+        };
+
+        let pushed = match get_pushed_hashes(state, &target_id).await {
+            Ok(pushed) => pushed,
+            Err(e) => {
+                plan.errors.push(e);
+                return plan;
+            }
+        };
+
+        let session = session_state.session(&conn.id).await;
+        let mut session = session.lock().await;
+        if let Err(e) = connect_session(state, &mut session, conn).await {
+            plan.errors.push(format!("SSH 连接失败: {}", e));
+            return plan;
+        }
+
+        for mapping in mappings {
+            let local = expand_local_path(&mapping.local_path)
+                .and_then(|path| local_hashes(std::path::Path::new(&path), mapping_kind(mapping)));
+            let remote = remote_hashes(&session, mapping).await;
+            plan.items.push(plan_item(
+                &mapping.id,
+                &mapping.name,
+                &mapping.module,
+                &mapping.local_path,
+                &mapping.remote_path,
+                local,
+                remote,
+                pushed.get(&mapping.id),
+            ));
+        }
+        plan
+    }))
+    .await;
+
+    Ok(plans)
+}
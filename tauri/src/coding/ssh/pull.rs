@@ -0,0 +1,142 @@
+//! 反向同步：从远程主机拉取 MCP 服务器和 Skills
+//!
+//! 读取远程的 ~/.claude.json、~/.codex/config.toml、opencode.jsonc 和各工具的
+//! Skills 目录，与数据库对比后由用户选择导入。
+
+use super::commands::{connect_session, get_ssh_config_internal};
+use super::session::SshSessionState;
+use super::sync::{list_remote_dir, read_remote_file};
+use super::types::SSHConnection;
+use crate::coding::mcp::env_vars::ssh_target;
+use crate::coding::remote_pull::{
+    diff_mcp_config, diff_skills, emit_pull_changes, import_mcp_item, import_skill_dir,
+    item_label, remote_skill_dirs, should_import, skill_download_dir, PullItem, PullPreview,
+    PullResult, REMOTE_MCP_SOURCES,
+};
+use crate::db::DbState;
+
+/// 查找要拉取的连接，未指定时使用活动连接
+async fn pull_connection(
+    state: &DbState,
+    connection_id: Option<String>,
+) -> Result<SSHConnection, String> {
+    let config = {
+        let db = state.0.lock().await;
+        get_ssh_config_internal(&db, false).await?
+    };
+    let id = connection_id.unwrap_or(config.active_connection_id);
+    config
+        .connections
+        .into_iter()
+        .find(|c| c.id == id)
+        .ok_or_else(|| format!("SSH 连接不存在: {}", id))
+}
+
+/// 读取远程 MCP 配置和 Skills 目录，返回与数据库的对比结果
+#[tauri::command]
+pub async fn ssh_pull_preview(
+    state: tauri::State<'_, DbState>,
+    session_state: tauri::State<'_, SshSessionState>,
+    connection_id: Option<String>,
+) -> Result<PullPreview, String> {
+    let conn = pull_connection(&state, connection_id).await?;
+    let session = session_state.session(&conn.id).await;
+    let mut session = session.lock().await;
+    connect_session(&state, &mut session, &conn).await?;
+
+    let mut preview = PullPreview::default();
+
+    for (tool, paths) in REMOTE_MCP_SOURCES {
+        for path in paths.iter() {
+            let content = match read_remote_file(&session, path).await {
+                Ok(content) => content,
+                Err(e) => {
+                    preview.errors.push(format!("{}: {}", path, e));
+                    break;
+                }
+            };
+            if content.trim().is_empty() {
+                continue;
+            }
+            match diff_mcp_config(&state, &ssh_target(&conn.id), tool, &content).await {
+                Ok(items) => preview.items.extend(items),
+                Err(e) => preview.errors.push(format!("{}: {}", path, e)),
+            }
+            break;
+        }
+    }
+
+    for (tool, dir) in remote_skill_dirs() {
+        let names = match list_remote_dir(&session, &dir).await {
+            Ok(names) => names,
+            Err(e) => {
+                preview.errors.push(format!("{}: {}", dir, e));
+                continue;
+            }
+        };
+        match diff_skills(&state, tool, &dir, names).await {
+            Ok(items) => preview.items.extend(items),
+            Err(e) => preview.errors.push(format!("{}: {}", dir, e)),
+        }
+    }
+
+    Ok(preview)
+}
+
+/// 导入用户选择的远程 MCP 服务器和 Skills
+#[tauri::command]
+pub async fn ssh_pull_import(
+    state: tauri::State<'_, DbState>,
+    session_state: tauri::State<'_, SshSessionState>,
+    app: tauri::AppHandle,
+    connection_id: Option<String>,
+    items: Vec<PullItem>,
+) -> Result<PullResult, String> {
+    let conn = pull_connection(&state, connection_id).await?;
+    let session = session_state.session(&conn.id).await;
+    let mut session = session.lock().await;
+    connect_session(&state, &mut session, &conn).await?;
+
+    let mut result = PullResult::default();
+    let mut imported = Vec::new();
+
+    for item in items {
+        if !should_import(&item, &imported, &mut result) {
+            continue;
+        }
+
+        let outcome = match item.kind.as_str() {
+            "mcp" => import_mcp_item(&state, &ssh_target(&conn.id), &item).await,
+            "skill" => {
+                let remote_path = item.remote_path.clone().unwrap_or_default();
+                let local_dir = skill_download_dir(&item.name);
+                let local_path = local_dir.to_string_lossy().to_string();
+                let outcome = match session.download_dir(&remote_path, &local_path).await {
+                    Ok(()) => {
+                        let source_ref = format!("ssh://{}@{}/{}", conn.username, conn.host, remote_path);
+                        import_skill_dir(&app, &state, &item, &local_dir, source_ref).await
+                    }
+                    Err(e) => Err(format!("下载失败: {}", e)),
+                };
+                if let Some(temp_dir) = local_dir.parent() {
+                    let _ = std::fs::remove_dir_all(temp_dir);
+                }
+                outcome
+            }
+            other => Err(format!("未知类型: {}", other)),
+        };
+
+        match outcome {
+            Ok(()) => {
+                result.imported.push(item_label(&item));
+                imported.push((item.kind, item.name));
+            }
+            Err(e) => result.errors.push(format!("{}: {}", item_label(&item), e)),
+        }
+    }
+
+    // 释放会话锁后再通知，避免 mcp-changed/skills-changed 触发的推送同步等待
+    drop(session);
+    emit_pull_changes(&app, &imported);
+    Ok(result)
+}
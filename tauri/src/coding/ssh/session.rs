@@ -3,6 +3,7 @@
 //! 维护一个进程内持久 SSH 连接，所有操作复用该连接。
 //! 网络断开后自动重连。跨平台兼容（Windows/macOS/Linux）。
 
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
@@ -13,7 +14,10 @@ use russh::{client, ChannelMsg, Disconnect};
 use tokio::io::AsyncWriteExt;
 use tokio::sync::Mutex;
 
+use super::agent;
+use super::host_key::{self, HostKeyVerdict};
 use super::key_file;
+use super::manifest;
 use super::types::SSHConnection;
 
 /// 加载私钥：优先从内容直接解析，否则从文件路径加载
@@ -51,27 +55,147 @@ pub enum SessionStatus {
     Failed(String),
 }
 
+/// 服务器主机密钥的校验结果和 SHA256 指纹
+type HostKeyCheck = (HostKeyVerdict, String);
+
 /// russh 客户端 Handler 实现
-struct SshHandler;
+struct SshHandler {
+    host: String,
+    port: u16,
+    /// 连接上保存的已信任主机密钥
+    pinned_host_key: String,
+    /// 握手时记录的校验结果，连接建立后由调用方读取
+    host_key_check: Arc<std::sync::Mutex<Option<HostKeyCheck>>>,
+}
 
 impl client::Handler for SshHandler {
     type Error = russh::Error;
 
     async fn check_server_key(
         &mut self,
-        _server_public_key: &ssh_key::PublicKey,
+        server_public_key: &ssh_key::PublicKey,
     ) -> Result<bool, Self::Error> {
-        // TODO: 实现 known_hosts 验证以达到真正的 StrictHostKeyChecking=accept-new 行为
-        // 当前行为等同于 StrictHostKeyChecking=no，无条件接受所有服务器密钥
-        Ok(true)
+        let verdict = host_key::verify_host_key(
+            &self.host,
+            self.port,
+            &self.pinned_host_key,
+            server_public_key,
+        );
+        let fingerprint = host_key::fingerprint(server_public_key);
+        if let HostKeyVerdict::New(_) = verdict {
+            info!(
+                "首次连接 {}:{}，信任主机密钥 {}",
+                self.host, self.port, fingerprint
+            );
+        }
+
+        let accepted = verdict.rejection().is_none();
+        if let Ok(mut check) = self.host_key_check.lock() {
+            *check = Some((verdict, fingerprint));
+        }
+        Ok(accepted)
     }
 }
 
-/// 对已建立的 SSH 连接进行用户认证（密码或公钥）
+/// 已建立的 SSH 连接
+struct OpenedConnection {
+    handle: client::Handle<SshHandler>,
+    /// 经由跳板机连接时的跳板连接句柄，需与目标连接一起保持存活
+    jump_handles: Vec<client::Handle<SshHandler>>,
+    /// 目标主机的主机密钥校验结果
+    check: HostKeyCheck,
+    /// 跳板机上首次信任的主机密钥（连接 ID, 公钥）
+    new_jump_keys: Vec<(String, String)>,
+}
+
+/// 建立 SSH 连接，依次经过跳板机（ProxyJump），每一跳都完成主机密钥校验和用户认证
+async fn open_connection(
+    conn: &SSHConnection,
+    jumps: &[SSHConnection],
+    config: client::Config,
+    timeout: Duration,
+) -> Result<OpenedConnection, String> {
+    let config = Arc::new(config);
+    let mut jump_handles: Vec<client::Handle<SshHandler>> = Vec::new();
+    let mut new_jump_keys = Vec::new();
+
+    for jump in jumps {
+        let (handle, (verdict, _)) = open_hop(jump, jump_handles.last(), config.clone(), timeout)
+            .await
+            .map_err(|e| format!("跳板机 {} 连接失败: {}", jump.name, e))?;
+        if let HostKeyVerdict::New(key) = verdict {
+            new_jump_keys.push((jump.id.clone(), key));
+        }
+        jump_handles.push(handle);
+    }
+
+    let (handle, check) = open_hop(conn, jump_handles.last(), config, timeout).await?;
+    Ok(OpenedConnection {
+        handle,
+        jump_handles,
+        check,
+        new_jump_keys,
+    })
+}
+
+/// 连接单个主机：直接连接，或通过上一跳的 direct-tcpip 通道连接
+async fn open_hop(
+    conn: &SSHConnection,
+    via: Option<&client::Handle<SshHandler>>,
+    config: Arc<client::Config>,
+    timeout: Duration,
+) -> Result<(client::Handle<SshHandler>, HostKeyCheck), String> {
+    let host_key_check = Arc::new(std::sync::Mutex::new(None));
+    let handler = SshHandler {
+        host: conn.host.clone(),
+        port: conn.port,
+        pinned_host_key: conn.host_key.clone(),
+        host_key_check: host_key_check.clone(),
+    };
+
+    let connect = async {
+        match via {
+            Some(jump) => {
+                let channel = jump
+                    .channel_open_direct_tcpip(conn.host.clone(), conn.port as u32, "127.0.0.1", 0)
+                    .await?;
+                client::connect_stream(config, channel.into_stream(), handler).await
+            }
+            None => client::connect(config, (conn.host.as_str(), conn.port), handler).await,
+        }
+    };
+
+    let result = tokio::time::timeout(timeout, connect)
+        .await
+        .map_err(|_| format!("连接超时: {}:{}", conn.host, conn.port))?;
+
+    let check = host_key_check.lock().ok().and_then(|mut c| c.take());
+    let (mut session, check) = match (result, check) {
+        (Ok(session), Some(check)) => (session, check),
+        // 主机密钥被拒绝时返回明确的错误，而不是笼统的握手失败
+        (Err(e), check) => {
+            return Err(match check.as_ref().and_then(|(verdict, _)| verdict.rejection()) {
+                Some(msg) => msg.to_string(),
+                None => format!("连接到 {}:{} 失败: {}", conn.host, conn.port, e),
+            })
+        }
+        (Ok(_), None) => return Err("服务器未提供主机密钥".to_string()),
+    };
+
+    authenticate(&mut session, conn).await?;
+
+    Ok((session, check))
+}
+
+/// 对已建立的 SSH 连接进行用户认证（密码、公钥或 SSH agent）
 async fn authenticate(
     session: &mut client::Handle<SshHandler>,
     conn: &SSHConnection,
 ) -> Result<(), String> {
+    for secret in [&conn.password, &conn.passphrase, &conn.private_key_content] {
+        crate::secrets::check_resolved(secret)?;
+    }
+
     if conn.auth_method == "password" && !conn.password.is_empty() {
         let auth_result = session
             .authenticate_password(&conn.username, &conn.password)
@@ -100,35 +224,136 @@ async fn authenticate(
         if !auth_result.success() {
             return Err("公钥认证失败: 密钥不被服务器接受".to_string());
         }
+    } else if conn.auth_method == "agent" {
+        authenticate_with_agent(session, conn).await?;
     } else {
         return Err(format!("不支持的认证方式: {}", conn.auth_method));
     }
     Ok(())
 }
 
+/// 依次尝试 SSH agent 提供的每个身份，私钥不经过本应用
+async fn authenticate_with_agent(
+    session: &mut client::Handle<SshHandler>,
+    conn: &SSHConnection,
+) -> Result<(), String> {
+    let mut agent = agent::AgentSigner(agent::connect_agent().await?);
+    let identities = agent
+        .0
+        .request_identities()
+        .await
+        .map_err(|e| format!("读取 SSH agent 身份失败: {}", e))?;
+    if identities.is_empty() {
+        return Err("SSH agent 中没有可用的身份".to_string());
+    }
+
+    let rsa_hash = session
+        .best_supported_rsa_hash()
+        .await
+        .map_err(|e| format!("获取 RSA hash 算法失败: {}", e))?
+        .flatten();
+
+    for key in identities {
+        let hash_alg = match key.algorithm() {
+            ssh_key::Algorithm::Rsa { .. } => rsa_hash,
+            _ => None,
+        };
+        let comment = key.comment().to_string();
+        match session
+            .authenticate_publickey_with(conn.username.clone(), key, hash_alg, &mut agent)
+            .await
+        {
+            Ok(result) if result.success() => return Ok(()),
+            Ok(_) => {}
+            Err(e) => warn!("SSH agent 身份 {} 签名失败: {}", comment, e),
+        }
+    }
+    Err("SSH agent 认证失败: agent 中的身份均不被服务器接受".to_string())
+}
+
 /// SSH 持久连接会话管理器
 pub struct SshSession {
     /// 当前使用的连接信息
     conn: Option<SSHConnection>,
+    /// 跳板机连接信息（ProxyJump，按连接顺序）
+    jumps: Vec<SSHConnection>,
     /// russh 持久连接句柄
     handle: Option<client::Handle<SshHandler>>,
+    /// 跳板机连接句柄，与主连接一起保持存活
+    jump_handles: Vec<client::Handle<SshHandler>>,
     /// 当前会话状态
     status: SessionStatus,
     /// 是否正在进行同步操作（防止并发）
     syncing: AtomicBool,
+    /// 首次连接时信任的主机密钥（连接 ID, 公钥），包括跳板机，等待保存到数据库
+    new_host_keys: Vec<(String, String)>,
 }
 
 /// 全局 SSH 会话状态，注册到 Tauri State
-pub struct SshSessionState(pub Arc<Mutex<SshSession>>);
+///
+/// 每个同步目标连接（按连接 ID）维护一个独立的持久会话，多主机同步时并发使用。
+#[derive(Default)]
+pub struct SshSessionState {
+    sessions: Mutex<HashMap<String, Arc<Mutex<SshSession>>>>,
+}
+
+impl SshSessionState {
+    /// 获取连接对应的会话，不存在时创建（不连接）
+    pub async fn session(&self, conn_id: &str) -> Arc<Mutex<SshSession>> {
+        self.sessions
+            .lock()
+            .await
+            .entry(conn_id.to_string())
+            .or_insert_with(|| Arc::new(Mutex::new(SshSession::new())))
+            .clone()
+    }
+
+    /// 所有会话
+    pub async fn sessions(&self) -> Vec<Arc<Mutex<SshSession>>> {
+        self.sessions.lock().await.values().cloned().collect()
+    }
+
+    /// 断开并移除不在 `conn_ids` 中的会话
+    pub async fn retain(&self, conn_ids: &[String]) {
+        let removed: Vec<_> = {
+            let mut sessions = self.sessions.lock().await;
+            let ids: Vec<String> = sessions
+                .keys()
+                .filter(|id| !conn_ids.contains(id))
+                .cloned()
+                .collect();
+            ids.iter().filter_map(|id| sessions.remove(id)).collect()
+        };
+        for session in removed {
+            session.lock().await.disconnect().await;
+        }
+    }
+
+    /// 断开并移除连接对应的会话
+    pub async fn remove(&self, conn_id: &str) {
+        let session = self.sessions.lock().await.remove(conn_id);
+        if let Some(session) = session {
+            session.lock().await.disconnect().await;
+        }
+    }
+
+    /// 断开并移除所有会话
+    pub async fn disconnect_all(&self) {
+        self.retain(&[]).await;
+    }
+}
 
 impl SshSession {
     /// 创建新会话（不连接）
     pub fn new() -> Self {
         Self {
             conn: None,
+            jumps: Vec::new(),
             handle: None,
+            jump_handles: Vec::new(),
             status: SessionStatus::Disconnected,
             syncing: AtomicBool::new(false),
+            new_host_keys: Vec::new(),
         }
     }
 
@@ -142,10 +367,15 @@ impl SshSession {
         self.conn.as_ref()
     }
 
-    /// 建立持久连接
-    pub async fn connect(&mut self, conn: &SSHConnection) -> Result<(), String> {
-        // 如果已连接同一个目标，先检查是否存活
-        if self.conn.as_ref().map(|c| &c.id) == Some(&conn.id) && self.is_alive() {
+    /// 建立持久连接，jumps 为依次经过的跳板机
+    pub async fn connect(
+        &mut self,
+        conn: &SSHConnection,
+        jumps: &[SSHConnection],
+    ) -> Result<(), String> {
+        // 如果已连接同一个目标（经过相同的跳板机），先检查是否存活
+        let same_jumps = self.jumps.iter().map(|j| &j.id).eq(jumps.iter().map(|j| &j.id));
+        if self.conn.as_ref().map(|c| &c.id) == Some(&conn.id) && same_jumps && self.is_alive() {
             self.status = SessionStatus::Connected;
             return Ok(());
         }
@@ -155,10 +385,25 @@ impl SshSession {
 
         self.status = SessionStatus::Connecting;
         self.conn = Some(conn.clone());
-
-        match self.do_connect(conn).await {
-            Ok(handle) => {
-                self.handle = Some(handle);
+        self.jumps = jumps.to_vec();
+
+        match self.do_connect(conn, jumps).await {
+            Ok(opened) => {
+                self.handle = Some(opened.handle);
+                self.jump_handles = opened.jump_handles;
+                // 重连时按已信任的密钥校验
+                for (jump_id, key) in opened.new_jump_keys {
+                    if let Some(jump) = self.jumps.iter_mut().find(|j| j.id == jump_id) {
+                        jump.host_key = key.clone();
+                    }
+                    self.new_host_keys.push((jump_id, key));
+                }
+                if let HostKeyVerdict::New(key) = opened.check.0 {
+                    if let Some(current) = self.conn.as_mut() {
+                        current.host_key = key.clone();
+                    }
+                    self.new_host_keys.push((conn.id.clone(), key));
+                }
                 self.status = SessionStatus::Connected;
                 info!(
                     "SSH 连接已建立: {}@{}:{}",
@@ -178,7 +423,8 @@ impl SshSession {
     async fn do_connect(
         &self,
         conn: &SSHConnection,
-    ) -> Result<client::Handle<SshHandler>, String> {
+        jumps: &[SSHConnection],
+    ) -> Result<OpenedConnection, String> {
         let config = client::Config {
             inactivity_timeout: Some(Duration::from_secs(90)),
             keepalive_interval: Some(Duration::from_secs(30)),
@@ -186,18 +432,20 @@ impl SshSession {
             ..Default::default()
         };
 
-        let handler = SshHandler;
-        let mut session = tokio::time::timeout(
-            Duration::from_secs(30),
-            client::connect(Arc::new(config), (conn.host.as_str(), conn.port), handler),
-        )
-        .await
-        .map_err(|_| format!("连接超时: {}:{}", conn.host, conn.port))?
-        .map_err(|e| format!("连接到 {}:{} 失败: {}", conn.host, conn.port, e))?;
+        open_connection(conn, jumps, config, Duration::from_secs(30)).await
+    }
 
-        authenticate(&mut session, conn).await?;
+    /// 清除当前连接上已信任的主机密钥，下次重连时重新信任
+    pub fn reset_host_key(&mut self, conn_id: &str) {
+        let conns = self.conn.iter_mut().chain(self.jumps.iter_mut());
+        for conn in conns.filter(|c| c.id == conn_id) {
+            conn.host_key.clear();
+        }
+    }
 
-        Ok(session)
+    /// 取出首次连接时信任的主机密钥（连接 ID, 公钥），由调用方保存
+    pub fn take_new_host_keys(&mut self) -> Vec<(String, String)> {
+        std::mem::take(&mut self.new_host_keys)
     }
 
     /// 检查连接是否存活
@@ -218,8 +466,9 @@ impl SshSession {
             .conn
             .clone()
             .ok_or("没有可用的 SSH 连接配置".to_string())?;
+        let jumps = self.jumps.clone();
         warn!("SSH 连接已断开，正在重连...");
-        self.connect(&conn).await
+        self.connect(&conn, &jumps).await
     }
 
     /// 断开连接
@@ -235,7 +484,14 @@ impl SshSession {
                 );
             }
         }
+        // 由内向外断开跳板机连接
+        while let Some(handle) = self.jump_handles.pop() {
+            let _ = handle
+                .disconnect(Disconnect::ByApplication, "", "")
+                .await;
+        }
         self.conn = None;
+        self.jumps.clear();
         self.status = SessionStatus::Disconnected;
     }
 
@@ -360,20 +616,7 @@ impl SshSession {
         &self,
     ) -> Result<russh_sftp::client::SftpSession, String> {
         let handle = self.handle.as_ref().ok_or("SSH 会话未建立")?;
-
-        let channel = handle
-            .channel_open_session()
-            .await
-            .map_err(|e| format!("打开 SFTP channel 失败: {}", e))?;
-
-        channel
-            .request_subsystem(true, "sftp")
-            .await
-            .map_err(|e| format!("请求 SFTP 子系统失败: {}", e))?;
-
-        russh_sftp::client::SftpSession::new(channel.into_stream())
-            .await
-            .map_err(|e| format!("初始化 SFTP 会话失败: {}", e))
+        open_sftp(handle).await
     }
 
     /// 通过 SFTP 上传单个文件
@@ -393,6 +636,21 @@ impl SshSession {
         upload_dir_recursive(&sftp, std::path::Path::new(local_path), &abs_remote_path).await
     }
 
+    /// 通过 SFTP 递归下载远程目录到本地（跟随符号链接）
+    pub async fn download_dir(&self, remote_path: &str, local_path: &str) -> Result<(), String> {
+        let sftp = self.create_sftp_session().await?;
+
+        // 将 ~ 展开为绝对路径
+        let abs_remote_path = resolve_remote_path(&sftp, remote_path).await?;
+
+        download_dir_recursive(&sftp, &abs_remote_path, std::path::Path::new(local_path)).await
+    }
+
+    /// 是否为纯 SFTP 模式（不执行远程命令）
+    pub fn sftp_only(&self) -> bool {
+        self.conn.as_ref().is_some_and(|c| c.sftp_only)
+    }
+
     /// 获取 user@host 字符串
     pub fn target_str(&self) -> Result<String, String> {
         let conn = self.conn.as_ref().ok_or("SSH 会话未建立")?;
@@ -413,28 +671,46 @@ impl SshSession {
 }
 
 /// 创建一个独立的临时 SSH 连接并执行命令（用于测试连接）
-/// 返回命令输出结果
+/// 返回命令输出、主机密钥校验结果和服务器密钥指纹
 pub async fn test_connection_with_command(
     conn: &SSHConnection,
+    jumps: &[SSHConnection],
     cmd: &str,
-) -> Result<String, String> {
+) -> Result<(String, HostKeyVerdict, String), String> {
     let config = client::Config {
         inactivity_timeout: Some(Duration::from_secs(10)),
         ..Default::default()
     };
 
-    let handler = SshHandler;
-    let mut session = tokio::time::timeout(
-        Duration::from_secs(15),
-        client::connect(Arc::new(config), (conn.host.as_str(), conn.port), handler),
-    )
-    .await
-    .map_err(|_| format!("连接超时: {}:{}", conn.host, conn.port))?
-    .map_err(|e| format!("连接到 {}:{} 失败: {}", conn.host, conn.port, e))?;
+    let OpenedConnection {
+        handle: session,
+        jump_handles,
+        check: (verdict, fingerprint),
+        ..
+    } = open_connection(conn, jumps, config, Duration::from_secs(15)).await?;
 
-    authenticate(&mut session, conn).await?;
+    // 纯 SFTP 模式只验证 SFTP 子系统可用，不执行命令
+    let output = if conn.sftp_only {
+        test_sftp(&session).await
+    } else {
+        exec_test_command(&session, cmd).await
+    };
+
+    let _ = session
+        .disconnect(Disconnect::ByApplication, "", "")
+        .await;
+    for jump in jump_handles.into_iter().rev() {
+        let _ = jump.disconnect(Disconnect::ByApplication, "", "").await;
+    }
 
-    // 执行命令
+    Ok((output?, verdict, fingerprint))
+}
+
+/// 在测试连接上执行命令并返回 stdout
+async fn exec_test_command(
+    session: &client::Handle<SshHandler>,
+    cmd: &str,
+) -> Result<String, String> {
     let mut channel = session
         .channel_open_session()
         .await
@@ -464,13 +740,38 @@ pub async fn test_connection_with_command(
         }
     }
 
-    let _ = session
-        .disconnect(Disconnect::ByApplication, "", "")
-        .await;
-
     Ok(String::from_utf8_lossy(&stdout_buf).to_string())
 }
 
+/// 在测试连接上打开 SFTP 会话并返回远程 home 路径
+async fn test_sftp(session: &client::Handle<SshHandler>) -> Result<String, String> {
+    let sftp = open_sftp(session).await?;
+    let home = sftp
+        .canonicalize(".")
+        .await
+        .map_err(|e| format!("获取远程 home 路径失败: {}", e))?;
+    Ok(format!("SFTP: {}", home))
+}
+
+/// 在连接上打开 SFTP 子系统
+async fn open_sftp(
+    handle: &client::Handle<SshHandler>,
+) -> Result<russh_sftp::client::SftpSession, String> {
+    let channel = handle
+        .channel_open_session()
+        .await
+        .map_err(|e| format!("打开 SFTP channel 失败: {}", e))?;
+
+    channel
+        .request_subsystem(true, "sftp")
+        .await
+        .map_err(|e| format!("请求 SFTP 子系统失败: {}", e))?;
+
+    russh_sftp::client::SftpSession::new(channel.into_stream())
+        .await
+        .map_err(|e| format!("初始化 SFTP 会话失败: {}", e))
+}
+
 /// 通过已有 SFTP 会话上传单个文件
 pub async fn upload_file_via_sftp(
     sftp: &russh_sftp::client::SftpSession,
@@ -585,7 +886,7 @@ async fn upload_dir_recursive(
 
 /// 将远程路径中的 ~ 和 $HOME 展开为绝对路径
 /// SFTP 协议不支持 shell 变量或 ~ 语法，需要用 canonicalize 获取 home 路径
-async fn resolve_remote_path(
+pub(super) async fn resolve_remote_path(
     sftp: &russh_sftp::client::SftpSession,
     path: &str,
 ) -> Result<String, String> {
@@ -603,8 +904,65 @@ async fn resolve_remote_path(
     }
 }
 
+/// 递归下载远程目录内容到本地
+async fn download_dir_recursive(
+    sftp: &russh_sftp::client::SftpSession,
+    remote_dir: &str,
+    local_dir: &std::path::Path,
+) -> Result<(), String> {
+    tokio::fs::create_dir_all(local_dir)
+        .await
+        .map_err(|e| format!("创建本地目录失败 {}: {}", local_dir.display(), e))?;
+
+    let entries = sftp
+        .read_dir(remote_dir)
+        .await
+        .map_err(|e| format!("读取远程目录失败 {}: {}", remote_dir, e))?;
+
+    for entry in entries {
+        let file_name = entry.file_name();
+        // 文件名来自远程服务器，不能包含路径分隔符或 ..，否则会写到本地目录之外
+        if file_name.contains('/') || !manifest::is_safe_relative_path(&file_name) {
+            return Err(format!("远程文件名不安全: {}/{}", remote_dir, file_name));
+        }
+        let remote_child = format!("{}/{}", remote_dir, file_name);
+        let local_child = local_dir.join(&file_name);
+
+        // lstat 获取条目本身的类型；符号链接只跟随指向文件的，
+        // 指向目录的链接可能指向上级目录导致无限递归，跳过
+        let mut metadata = sftp
+            .symlink_metadata(&remote_child)
+            .await
+            .map_err(|e| format!("获取远程文件信息失败 {}: {}", remote_child, e))?;
+        if metadata.file_type().is_symlink() {
+            metadata = sftp
+                .metadata(&remote_child)
+                .await
+                .map_err(|e| format!("获取远程文件信息失败 {}: {}", remote_child, e))?;
+            if metadata.file_type().is_dir() {
+                warn!("跳过指向目录的符号链接: {}", remote_child);
+                continue;
+            }
+        }
+
+        if metadata.file_type().is_dir() {
+            Box::pin(download_dir_recursive(sftp, &remote_child, &local_child)).await?;
+        } else if metadata.file_type().is_file() {
+            let data = sftp
+                .read(&remote_child)
+                .await
+                .map_err(|e| format!("读取远程文件失败 {}: {}", remote_child, e))?;
+            tokio::fs::write(&local_child, data)
+                .await
+                .map_err(|e| format!("写入本地文件失败 {}: {}", local_child.display(), e))?;
+        }
+    }
+
+    Ok(())
+}
+
 /// 获取远程路径的父目录
-fn parent_path(path: &str) -> Option<String> {
+pub(super) fn parent_path(path: &str) -> Option<String> {
     let trimmed = path.trim_end_matches('/');
     if let Some(pos) = trimmed.rfind('/') {
         if pos == 0 {
@@ -618,7 +976,7 @@ fn parent_path(path: &str) -> Option<String> {
 }
 
 /// 递归创建远程目录（类似 mkdir -p）
-async fn sftp_mkdir_p(sftp: &russh_sftp::client::SftpSession, path: &str) {
+pub(super) async fn sftp_mkdir_p(sftp: &russh_sftp::client::SftpSession, path: &str) {
     let parts: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
     let mut current = String::new();
     for part in parts {
@@ -0,0 +1,253 @@
+//! 纯 SFTP 实现的同步原语
+//!
+//! 用于只允许 SFTP 的账号或非 POSIX shell 的主机（连接配置 sftp_only）：
+//! 不执行任何远程命令，mkdir -p、rm -rf、原子替换、符号链接和 ~ 展开都通过 SFTP 完成。
+
+use russh_sftp::client::SftpSession;
+use sha2::{Digest, Sha256};
+
+use super::manifest::DIR_MANIFEST_NAME;
+use super::session::{parent_path, resolve_remote_path, sftp_mkdir_p};
+use crate::coding::sync_plan::{FileHashes, MappingKind};
+
+/// 将 ~ 和 $HOME 展开为绝对路径（通过 SFTP realpath）
+pub async fn resolve(sftp: &SftpSession, path: &str) -> Result<String, String> {
+    resolve_remote_path(sftp, path).await
+}
+
+/// 递归创建目录（类似 mkdir -p）
+pub async fn mkdir_p(sftp: &SftpSession, path: &str) -> Result<(), String> {
+    let abs = resolve(sftp, path).await?;
+    sftp_mkdir_p(sftp, &abs).await;
+    Ok(())
+}
+
+/// 创建文件的父目录
+pub async fn mkdir_parent(sftp: &SftpSession, path: &str) -> Result<(), String> {
+    let abs = resolve(sftp, path).await?;
+    if let Some(parent) = parent_path(&abs) {
+        sftp_mkdir_p(sftp, &parent).await;
+    }
+    Ok(())
+}
+
+/// 递归删除文件、符号链接或目录（类似 rm -rf），路径不存在时直接返回
+pub async fn remove_all(sftp: &SftpSession, path: &str) -> Result<(), String> {
+    let abs = resolve(sftp, path).await?;
+    remove_all_abs(sftp, &abs).await
+}
+
+async fn remove_all_abs(sftp: &SftpSession, abs: &str) -> Result<(), String> {
+    // lstat：符号链接本身被删除，不跟随到目标
+    let Ok(metadata) = sftp.symlink_metadata(abs).await else {
+        return Ok(());
+    };
+
+    if metadata.file_type().is_dir() {
+        let entries = sftp
+            .read_dir(abs)
+            .await
+            .map_err(|e| format!("读取远程目录失败 {}: {}", abs, e))?;
+        for entry in entries {
+            Box::pin(remove_all_abs(sftp, &format!("{}/{}", abs, entry.file_name()))).await?;
+        }
+        sftp.remove_dir(abs)
+            .await
+            .map_err(|e| format!("删除远程目录失败 {}: {}", abs, e))
+    } else {
+        sftp.remove_file(abs)
+            .await
+            .map_err(|e| format!("删除远程文件失败 {}: {}", abs, e))
+    }
+}
+
+/// 用 from 替换 to：删除旧的 to 后重命名（SFTP v3 的 rename 不覆盖已存在的目标）
+pub async fn replace(sftp: &SftpSession, from: &str, to: &str) -> Result<(), String> {
+    let from = resolve(sftp, from).await?;
+    let to = resolve(sftp, to).await?;
+    remove_all_abs(sftp, &to).await?;
+    sftp.rename(&from, &to)
+        .await
+        .map_err(|e| format!("重命名远程路径失败 {} -> {}: {}", from, to, e))
+}
+
+/// 读取符号链接指向的目标，不是符号链接时返回 None
+pub async fn read_link(sftp: &SftpSession, link_path: &str) -> Option<String> {
+    let abs = resolve(sftp, link_path).await.ok()?;
+    let metadata = sftp.symlink_metadata(&abs).await.ok()?;
+    if !metadata.file_type().is_symlink() {
+        return None;
+    }
+    sftp.read_link(&abs).await.ok()
+}
+
+/// 创建符号链接（替换已存在的路径）
+///
+/// OpenSSH 的 sftp-server 实现 SSH_FXP_SYMLINK 时参数顺序与协议相反，
+/// 先按 OpenSSH 的顺序创建，校验失败再按协议顺序重试。
+pub async fn symlink(sftp: &SftpSession, target: &str, link_path: &str) -> Result<(), String> {
+    let target = resolve(sftp, target).await?;
+    let link = resolve(sftp, link_path).await?;
+    if let Some(parent) = parent_path(&link) {
+        sftp_mkdir_p(sftp, &parent).await;
+    }
+    remove_all_abs(sftp, &link).await?;
+
+    if sftp.symlink(&target, &link).await.is_ok()
+        && read_link(sftp, &link).await.as_deref() == Some(target.as_str())
+    {
+        return Ok(());
+    }
+    remove_all_abs(sftp, &link).await?;
+    // 按协议顺序的服务器上，上面的调用可能在 target 处创建了指向 link 的链接
+    if read_link(sftp, &target).await.as_deref() == Some(link.as_str()) {
+        remove_all_abs(sftp, &target).await?;
+    }
+    sftp.symlink(&link, &target)
+        .await
+        .map_err(|e| format!("创建远程符号链接失败 {} -> {}: {}", link, target, e))
+}
+
+/// 读取远程文件，不存在时返回空字符串
+pub async fn read_file(sftp: &SftpSession, path: &str) -> Result<String, String> {
+    let abs = resolve(sftp, path).await?;
+    match sftp.metadata(&abs).await {
+        Ok(metadata) if metadata.file_type().is_file() => {}
+        _ => return Ok(String::new()),
+    }
+    let data = sftp
+        .read(&abs)
+        .await
+        .map_err(|e| format!("读取远程文件失败 {}: {}", abs, e))?;
+    Ok(String::from_utf8_lossy(&data).to_string())
+}
+
+/// 写入远程文件（自动创建父目录）
+pub async fn write_file(sftp: &SftpSession, path: &str, content: &[u8]) -> Result<(), String> {
+    let abs = resolve(sftp, path).await?;
+    if let Some(parent) = parent_path(&abs) {
+        sftp_mkdir_p(sftp, &parent).await;
+    }
+    let mut file = sftp
+        .create(&abs)
+        .await
+        .map_err(|e| format!("打开远程文件失败 {}: {}", abs, e))?;
+
+    use tokio::io::AsyncWriteExt;
+    file.write_all(content)
+        .await
+        .map_err(|e| format!("写入远程文件失败 {}: {}", abs, e))?;
+    file.shutdown()
+        .await
+        .map_err(|e| format!("关闭远程文件失败: {}", e))
+}
+
+/// 列出远程目录的条目名，目录不存在时返回空列表
+pub async fn list_dir(sftp: &SftpSession, path: &str) -> Result<Vec<String>, String> {
+    let abs = resolve(sftp, path).await?;
+    match sftp.metadata(&abs).await {
+        Ok(metadata) if metadata.file_type().is_dir() => {}
+        _ => return Ok(vec![]),
+    }
+    let mut names: Vec<String> = sftp
+        .read_dir(&abs)
+        .await
+        .map_err(|e| format!("读取远程目录失败 {}: {}", abs, e))?
+        .map(|entry| entry.file_name())
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+/// 远程路径是否存在且不是只包含 ignore 条目的目录（用于工具检测）
+pub async fn has_content(sftp: &SftpSession, path: &str, ignore: Option<&str>) -> bool {
+    let Ok(abs) = resolve(sftp, path).await else {
+        return false;
+    };
+    let Ok(metadata) = sftp.metadata(&abs).await else {
+        return false;
+    };
+    let Some(ignore) = ignore.filter(|_| metadata.file_type().is_dir()) else {
+        return true;
+    };
+    match sftp.read_dir(&abs).await {
+        Ok(entries) => entries.into_iter().any(|entry| entry.file_name() != ignore),
+        Err(_) => false,
+    }
+}
+
+/// 计算文件映射在远程的文件哈希（同步计划），远程不存在时返回 None
+pub async fn remote_hashes(
+    sftp: &SftpSession,
+    remote_path: &str,
+    local_path: &str,
+    kind: MappingKind,
+) -> Result<Option<FileHashes>, String> {
+    let abs = resolve(sftp, remote_path).await?;
+    let mut hashes = FileHashes::new();
+
+    match kind {
+        MappingKind::File => {
+            if let Some(hash) = hash_file(sftp, &abs).await? {
+                hashes.insert(String::new(), hash);
+            }
+        }
+        MappingKind::Directory => hash_dir(sftp, &abs, "", &mut hashes).await?,
+        MappingKind::Pattern => {
+            let name_glob = local_path.rsplit(['/', '\\']).next().unwrap_or_default();
+            let pattern = glob::Pattern::new(name_glob)
+                .map_err(|e| format!("无效的 glob 模式: {}", e))?;
+            for name in list_dir(sftp, &abs).await? {
+                if !pattern.matches(&name) {
+                    continue;
+                }
+                if let Some(hash) = hash_file(sftp, &format!("{}/{}", abs, name)).await? {
+                    hashes.insert(name, hash);
+                }
+            }
+        }
+    }
+    Ok(if hashes.is_empty() { None } else { Some(hashes) })
+}
+
+async fn hash_file(sftp: &SftpSession, abs: &str) -> Result<Option<String>, String> {
+    match sftp.metadata(abs).await {
+        Ok(metadata) if metadata.file_type().is_file() => {}
+        _ => return Ok(None),
+    }
+    let data = sftp
+        .read(abs)
+        .await
+        .map_err(|e| format!("读取远程文件失败 {}: {}", abs, e))?;
+    Ok(Some(hex::encode(Sha256::digest(&data))))
+}
+
+async fn hash_dir(
+    sftp: &SftpSession,
+    abs: &str,
+    prefix: &str,
+    hashes: &mut FileHashes,
+) -> Result<(), String> {
+    for name in list_dir(sftp, abs).await? {
+        // 同步清单文件，不计入目录内容
+        if name == DIR_MANIFEST_NAME {
+            continue;
+        }
+        let child = format!("{}/{}", abs, name);
+        let relative = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+        // stat 跟随符号链接，与 find -L 一致
+        let Ok(metadata) = sftp.metadata(&child).await else {
+            continue;
+        };
+        if metadata.file_type().is_dir() {
+            Box::pin(hash_dir(sftp, &child, &relative, hashes)).await?;
+        } else if let Some(hash) = hash_file(sftp, &child).await? {
+            hashes.insert(relative, hash);
+        }
+    }
+    Ok(())
+}
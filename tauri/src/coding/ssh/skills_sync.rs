@@ -1,6 +1,8 @@
 //! Skills sync to SSH remote
 //!
 //! Full sync of managed skills to remote server's central repo with symlinks to tool directories.
+//! A manifest in the remote central repo records the content hash and tools of every synced
+//! skill, so unchanged skills are skipped and changed ones only upload their changed files.
 
 use std::collections::HashSet;
 
@@ -8,18 +10,21 @@ use log::info;
 use tauri::{AppHandle, Emitter};
 
 use super::commands::get_ssh_config_internal;
+use super::manifest::{self, SkillsManifest, SyncedSkill};
 use super::session::SshSession;
 use super::sync::{
     check_remote_symlink_exists, create_remote_symlink, list_remote_dir, read_remote_file_raw,
-    remove_remote_path, sync_directory, write_remote_file,
+    detect_remote_tools, remove_remote_path, sync_directory, write_remote_file,
 };
 use super::types::SyncProgress;
 use crate::coding::skills::central_repo::{resolve_central_repo_path, resolve_skill_central_path};
+use crate::coding::remote_tools::installed_tool_keys;
 use crate::coding::skills::skill_store;
 use crate::coding::tools::builtin::BUILTIN_TOOLS;
 use crate::DbState;
 
 const SSH_CENTRAL_DIR: &str = "~/.ai-toolbox/skills";
+const SKILLS_MANIFEST_NAME: &str = ".ai-toolbox-skills.json";
 
 /// Get the remote skills directory path for a tool key
 fn get_remote_tool_skills_dir(tool_key: &str) -> Option<String> {
@@ -85,9 +90,20 @@ pub async fn sync_skills_to_ssh(
             current: 0,
             total: total_skills,
             message: format!("Skills 同步: 0/{}", total_skills),
+            connection_id: session.conn().map(|c| c.id.clone()),
         },
     );
 
+    // Skills synced last time, unchanged skills are skipped without touching the remote
+    let manifest_path = format!("{}/{}", SSH_CENTRAL_DIR, SKILLS_MANIFEST_NAME);
+    let previous: SkillsManifest = manifest::read_remote_manifest(session, &manifest_path)
+        .await
+        .unwrap_or_default();
+    let mut current = SkillsManifest::default();
+
+    // Only link skills into tools that are installed on the remote
+    let installed_tools = installed_tool_keys(detect_remote_tools(session).await);
+
     // 1. Get existing skills in remote central repo
     let existing_remote_skills = list_remote_dir(session, SSH_CENTRAL_DIR).await.unwrap_or_default();
 
@@ -125,6 +141,7 @@ pub async fn sync_skills_to_ssh(
                     "Skills 同步: {}/{} - {}",
                     current_idx, total_skills, skill.name
                 ),
+                connection_id: session.conn().map(|c| c.id.clone()),
             },
         );
 
@@ -140,16 +157,39 @@ pub async fn sync_skills_to_ssh(
 
         let remote_target = format!("{}/{}", SSH_CENTRAL_DIR, skill.name);
         let hash_file = format!("{}/.synced_hash", remote_target);
-
-        // Check if content needs updating using content_hash
-        let remote_hash = read_remote_file_raw(session, &hash_file)
-            .await
-            .unwrap_or_default()
-            .trim()
-            .to_string();
         let local_hash = skill.content_hash.as_deref().unwrap_or("");
+        let mut tools: Vec<String> = skill
+            .enabled_tools
+            .iter()
+            .filter(|tool| installed_tools.contains(*tool))
+            .cloned()
+            .collect();
+        tools.sort();
+        let synced = SyncedSkill {
+            content_hash: local_hash.to_string(),
+            tools,
+        };
 
-        let needs_update = remote_hash != local_hash;
+        // Same content and tools as the last sync: nothing to do
+        let previous_skill = previous.skills.get(&skill.name);
+        if !local_hash.is_empty() && previous_skill == Some(&synced) {
+            current.skills.insert(skill.name.clone(), synced);
+            continue;
+        }
+
+        // Check if content needs updating using content_hash, falling back to the
+        // hash file written by earlier versions. Skills without hash always go through
+        // the incremental directory sync.
+        let remote_hash = match previous_skill {
+            Some(previous_skill) => previous_skill.content_hash.clone(),
+            None => read_remote_file_raw(session, &hash_file)
+                .await
+                .unwrap_or_default()
+                .trim()
+                .to_string(),
+        };
+
+        let needs_update = local_hash.is_empty() || remote_hash != local_hash;
 
         if needs_update {
             let source_str = source.to_string_lossy().to_string();
@@ -173,8 +213,8 @@ pub async fn sync_skills_to_ssh(
             }
         }
 
-        // Ensure symlinks for each enabled tool
-        for tool_key in &skill.enabled_tools {
+        // Ensure symlinks for each enabled tool installed on the remote
+        for tool_key in &synced.tools {
             if let Some(remote_skills_dir) = get_remote_tool_skills_dir(tool_key) {
                 let link_path = format!("{}/{}", remote_skills_dir, skill.name);
                 if !check_remote_symlink_exists(session, &link_path, &remote_target).await {
@@ -183,9 +223,8 @@ pub async fn sync_skills_to_ssh(
             }
         }
 
-        // Remove symlinks for tools that are no longer enabled
-        let enabled_set: HashSet<&str> =
-            skill.enabled_tools.iter().map(|s| s.as_str()).collect();
+        // Remove symlinks for tools that are no longer enabled or not installed
+        let enabled_set: HashSet<&str> = synced.tools.iter().map(|s| s.as_str()).collect();
         for tool_key in get_all_skill_tool_keys() {
             if !enabled_set.contains(tool_key) {
                 if let Some(remote_skills_dir) = get_remote_tool_skills_dir(tool_key) {
@@ -194,6 +233,12 @@ pub async fn sync_skills_to_ssh(
                 }
             }
         }
+
+        current.skills.insert(skill.name.clone(), synced);
+    }
+
+    if let Err(e) = manifest::write_remote_manifest(session, &manifest_path, &current).await {
+        log::warn!("Skills SSH sync: failed to write sync manifest: {}", e);
     }
 
     info!(
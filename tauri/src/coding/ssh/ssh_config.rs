@@ -0,0 +1,469 @@
+//! 解析 OpenSSH 客户端配置（~/.ssh/config）并导入为 SSH 连接预设
+//!
+//! 读取 Host 块中的 HostName、Port、User、IdentityFile 和 ProxyJump。
+//! 与 OpenSSH 一致，每个选项取第一个匹配块中的值，因此文件末尾的 `Host *`
+//! 可作为默认值。不支持 Match 和 Include。
+
+use serde::{Deserialize, Serialize};
+
+use super::types::SSHConnection;
+
+/// 一个可导入的 Host 条目（通配符 Host 不会单独列出）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SshConfigHost {
+    /// Host 别名，导入后作为连接名称
+    pub alias: String,
+    pub host_name: String,
+    pub port: u16,
+    pub user: String,
+    pub identity_file: String,
+    /// 跳板机，按连接顺序
+    pub proxy_jump: Vec<SshConfigJump>,
+}
+
+/// ProxyJump 中的一跳：[user@]host[:port]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SshConfigJump {
+    pub host: String,
+    pub port: Option<u16>,
+    pub user: Option<String>,
+}
+
+/// 配置文件中的一个 Host 块
+struct HostBlock {
+    patterns: Vec<String>,
+    options: Vec<(String, String)>,
+}
+
+/// 解析后的 SSH 客户端配置
+pub struct SshConfig {
+    blocks: Vec<HostBlock>,
+}
+
+impl SshConfig {
+    /// 读取本机 ~/.ssh/config，文件不存在时返回空配置
+    pub fn read_local() -> Result<Self, String> {
+        let path = dirs::home_dir()
+            .ok_or("无法获取用户主目录")?
+            .join(".ssh")
+            .join("config");
+        if !path.exists() {
+            return Ok(Self::parse(""));
+        }
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| format!("读取 {} 失败: {}", path.display(), e))?;
+        Ok(Self::parse(&content))
+    }
+
+    pub fn parse(content: &str) -> Self {
+        Self {
+            blocks: parse_blocks(content),
+        }
+    }
+
+    /// 所有非通配符 Host 别名及其生效的选项
+    pub fn hosts(&self) -> Vec<SshConfigHost> {
+        let mut aliases: Vec<&str> = Vec::new();
+        for block in &self.blocks {
+            for pattern in &block.patterns {
+                let concrete = !pattern.contains(['*', '?', '!']);
+                if concrete && !aliases.contains(&pattern.as_str()) {
+                    aliases.push(pattern);
+                }
+            }
+        }
+
+        aliases.into_iter().map(|alias| self.host(alias)).collect()
+    }
+
+    /// 计算主机名生效的选项：依次检查匹配的块，每个选项取第一次出现的值
+    pub fn host(&self, alias: &str) -> SshConfigHost {
+        resolve_host(alias, &self.blocks)
+    }
+}
+
+/// 按块拆分配置，Host 之前的选项视为对所有主机生效
+fn parse_blocks(content: &str) -> Vec<HostBlock> {
+    let mut blocks = vec![HostBlock {
+        patterns: vec!["*".to_string()],
+        options: vec![],
+    }];
+    // Match 块的条件无法在这里求值，跳过其中的选项
+    let mut in_match = false;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        // 关键字与值之间可以是空白或 =
+        let is_separator = |c: char| c.is_whitespace() || c == '=';
+        let (key, value) = match line.find(is_separator) {
+            Some(pos) => (&line[..pos], line[pos..].trim_start_matches(is_separator)),
+            None => (line, ""),
+        };
+        let key = key.to_ascii_lowercase();
+        let value = value.trim().trim_matches('"').to_string();
+
+        match key.as_str() {
+            "host" => {
+                in_match = false;
+                blocks.push(HostBlock {
+                    patterns: value.split_whitespace().map(String::from).collect(),
+                    options: vec![],
+                });
+            }
+            "match" => in_match = true,
+            _ if !in_match => {
+                if let Some(block) = blocks.last_mut() {
+                    block.options.push((key, value));
+                }
+            }
+            _ => {}
+        }
+    }
+    blocks
+}
+
+fn resolve_host(alias: &str, blocks: &[HostBlock]) -> SshConfigHost {
+    let mut host_name = None;
+    let mut port = None;
+    let mut user = None;
+    let mut identity_file = None;
+    let mut proxy_jump = None;
+
+    for block in blocks.iter().filter(|b| host_matches(alias, &b.patterns)) {
+        for (key, value) in &block.options {
+            match key.as_str() {
+                "hostname" if host_name.is_none() => host_name = Some(value.replace("%h", alias)),
+                "port" if port.is_none() => port = value.parse::<u16>().ok(),
+                "user" if user.is_none() => user = Some(value.clone()),
+                "identityfile" if identity_file.is_none() => {
+                    identity_file = Some(value.replace("%d", "~").replace("%h", alias))
+                }
+                "proxyjump" if proxy_jump.is_none() => proxy_jump = Some(parse_proxy_jump(value)),
+                _ => {}
+            }
+        }
+    }
+
+    SshConfigHost {
+        alias: alias.to_string(),
+        host_name: host_name.unwrap_or_else(|| alias.to_string()),
+        port: port.unwrap_or(22),
+        user: user.unwrap_or_else(local_user_name),
+        identity_file: identity_file.unwrap_or_default(),
+        proxy_jump: proxy_jump.unwrap_or_default(),
+    }
+}
+
+/// 未配置 User 时 ssh 使用本机当前用户名
+fn local_user_name() -> String {
+    for var in ["USER", "USERNAME"] {
+        if let Ok(name) = std::env::var(var) {
+            if !name.trim().is_empty() {
+                return name.trim().to_string();
+            }
+        }
+    }
+    std::process::Command::new("whoami")
+        .output()
+        .ok()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        // Windows 的 whoami 输出为 域\用户名
+        .map(|name| name.rsplit('\\').next().unwrap_or_default().to_string())
+        .unwrap_or_default()
+}
+
+/// 解析 ProxyJump 值：逗号分隔的 [user@]host[:port] 或 ssh://[user@]host[:port]，none 表示不使用
+fn parse_proxy_jump(value: &str) -> Vec<SshConfigJump> {
+    if value.eq_ignore_ascii_case("none") {
+        return vec![];
+    }
+
+    value
+        .split(',')
+        .map(|hop| hop.trim().trim_start_matches("ssh://").trim_end_matches('/'))
+        .filter(|hop| !hop.is_empty())
+        .map(|hop| {
+            let (user, rest) = match hop.rsplit_once('@') {
+                Some((user, rest)) => (Some(user.to_string()), rest),
+                None => (None, hop),
+            };
+            let (host, port) = match rest.rsplit_once(':') {
+                Some((host, port)) if port.parse::<u16>().is_ok() => (host, port.parse().ok()),
+                _ => (rest, None),
+            };
+            SshConfigJump {
+                host: host.trim_matches(['[', ']']).to_string(),
+                port,
+                user,
+            }
+        })
+        .collect()
+}
+
+/// 将选中的 Host 别名转换为连接预设
+///
+/// ProxyJump 中的跳板机同样导入为连接（跳板机自身的 ProxyJump 也会保留），
+/// 与已有连接或本次导入的连接同名时复用该连接。返回新建的连接。
+pub fn build_connections(
+    config: &SshConfig,
+    aliases: &[String],
+    existing: &[SSHConnection],
+    default_identity: &str,
+) -> Result<Vec<SSHConnection>, String> {
+    let mut builder = ConnectionBuilder {
+        config,
+        existing,
+        default_identity,
+        created: Vec::new(),
+        next_sort_order: existing.iter().map(|c| c.sort_order + 1).max().unwrap_or(0),
+    };
+    for alias in aliases {
+        let host = config.host(alias);
+        builder.connection_for(&host, &mut Vec::new())?;
+    }
+    Ok(builder.created)
+}
+
+struct ConnectionBuilder<'a> {
+    config: &'a SshConfig,
+    existing: &'a [SSHConnection],
+    default_identity: &'a str,
+    created: Vec<SSHConnection>,
+    next_sort_order: u32,
+}
+
+impl ConnectionBuilder<'_> {
+    /// 返回主机对应的连接 ID，不存在时创建
+    fn connection_for(
+        &mut self,
+        host: &SshConfigHost,
+        visiting: &mut Vec<String>,
+    ) -> Result<String, String> {
+        let found = self
+            .existing
+            .iter()
+            .chain(self.created.iter())
+            .find(|c| c.name == host.alias);
+        if let Some(conn) = found {
+            return Ok(conn.id.clone());
+        }
+        if visiting.contains(&host.alias) {
+            return Err(format!("ProxyJump 配置存在循环: {}", host.alias));
+        }
+
+        visiting.push(host.alias.clone());
+        let mut proxy_jump = Vec::new();
+        for hop in &host.proxy_jump {
+            let hop_host = self.jump_host(hop);
+            proxy_jump.push(self.connection_for(&hop_host, visiting)?);
+        }
+        visiting.pop();
+
+        let identity_file = if host.identity_file.is_empty() {
+            self.default_identity.to_string()
+        } else {
+            host.identity_file.clone()
+        };
+        // 没有可用的私钥文件时使用 SSH agent 认证
+        let auth_method = if identity_file.is_empty() { "agent" } else { "key" };
+        let conn = SSHConnection {
+            id: format!("ssh-{}", uuid::Uuid::new_v4().simple()),
+            name: host.alias.clone(),
+            host: host.host_name.clone(),
+            port: host.port,
+            username: host.user.clone(),
+            auth_method: auth_method.to_string(),
+            password: String::new(),
+            private_key_path: identity_file,
+            private_key_content: String::new(),
+            passphrase: String::new(),
+            sort_order: self.next_sort_order,
+            host_key: String::new(),
+            sync_target: false,
+            proxy_jump,
+            sftp_only: false,
+        };
+        self.next_sort_order += 1;
+        let id = conn.id.clone();
+        self.created.push(conn);
+        Ok(id)
+    }
+
+    /// ProxyJump 中的一跳按主机名套用配置，指定了用户或端口时作为单独的连接
+    fn jump_host(&self, hop: &SshConfigJump) -> SshConfigHost {
+        let mut host = self.config.host(&hop.host);
+        if hop.user.is_none() && hop.port.is_none() {
+            return host;
+        }
+        if let Some(user) = &hop.user {
+            host.user = user.clone();
+            host.alias = format!("{}@{}", user, host.alias);
+        }
+        if let Some(port) = hop.port {
+            host.port = port;
+            host.alias = format!("{}:{}", host.alias, port);
+        }
+        host
+    }
+}
+
+/// Host 模式匹配：任一模式匹配且没有被 ! 模式排除
+fn host_matches(alias: &str, patterns: &[String]) -> bool {
+    let mut matched = false;
+    for pattern in patterns {
+        if let Some(negated) = pattern.strip_prefix('!') {
+            if glob_match(negated, alias) {
+                return false;
+            }
+        } else if glob_match(pattern, alias) {
+            matched = true;
+        }
+    }
+    matched
+}
+
+/// 支持 * 和 ? 的通配符匹配
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+# bastion
+Host bastion
+    HostName bastion.example.com
+    User ops
+    Port 2222
+
+Host app app-2
+    HostName 10.0.0.%h
+    ProxyJump bastion,admin@10.0.0.1:2200
+    IdentityFile ~/.ssh/app_key
+
+Match host foo
+    User ignored
+
+Host *.internal !skip.internal
+    User internal
+
+Host *
+    User default
+    IdentityFile %d/.ssh/id_ed25519
+"#;
+
+    #[test]
+    fn test_parse_ssh_config() {
+        let hosts = SshConfig::parse(CONFIG).hosts();
+        let aliases: Vec<&str> = hosts.iter().map(|h| h.alias.as_str()).collect();
+        assert_eq!(aliases, vec!["bastion", "app", "app-2"]);
+
+        let bastion = &hosts[0];
+        assert_eq!(bastion.host_name, "bastion.example.com");
+        assert_eq!(bastion.port, 2222);
+        assert_eq!(bastion.user, "ops");
+        assert_eq!(bastion.identity_file, "~/.ssh/id_ed25519");
+        assert!(bastion.proxy_jump.is_empty());
+
+        let app = &hosts[1];
+        assert_eq!(app.host_name, "10.0.0.app");
+        assert_eq!(app.port, 22);
+        assert_eq!(app.user, "default");
+        assert_eq!(app.identity_file, "~/.ssh/app_key");
+        assert_eq!(
+            app.proxy_jump,
+            vec![
+                SshConfigJump { host: "bastion".to_string(), port: None, user: None },
+                SshConfigJump {
+                    host: "10.0.0.1".to_string(),
+                    port: Some(2200),
+                    user: Some("admin".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_connections() {
+        let config = SshConfig::parse(CONFIG);
+        let mut existing = build_connections(&config, &["bastion".to_string()], &[], "").unwrap();
+        existing[0].sort_order = 3;
+        let bastion_id = existing[0].id.clone();
+
+        let created =
+            build_connections(&config, &["app".to_string()], &existing, "~/.ssh/id_rsa").unwrap();
+        assert_eq!(created.len(), 2);
+
+        let (jump, app) = (&created[0], &created[1]);
+        assert_eq!(jump.name, "admin@10.0.0.1:2200");
+        assert_eq!(jump.host, "10.0.0.1");
+        assert_eq!(jump.port, 2200);
+        assert_eq!(jump.username, "admin");
+        assert_eq!(jump.private_key_path, "~/.ssh/id_ed25519");
+        assert_eq!(jump.sort_order, 4);
+
+        assert_eq!(app.host, "10.0.0.app");
+        assert_eq!(app.proxy_jump, vec![bastion_id, jump.id.clone()]);
+        assert_eq!(app.sort_order, 5);
+    }
+
+    #[test]
+    fn test_build_connections_agent() {
+        let config = SshConfig::parse("Host dev\n  HostName dev.example.com\n");
+        let created = build_connections(&config, &["dev".to_string()], &[], "").unwrap();
+        assert_eq!(created[0].auth_method, "agent");
+        assert_eq!(created[0].private_key_path, "");
+    }
+
+    #[test]
+    fn test_missing_user_defaults_to_local_user() {
+        let config = SshConfig::parse("Host dev\n  HostName dev.example.com\n");
+        let created = build_connections(&config, &["dev".to_string()], &[], "").unwrap();
+        assert!(!created[0].username.is_empty());
+        assert_eq!(created[0].username, local_user_name());
+    }
+
+    #[test]
+    fn test_build_connections_cycle() {
+        let config = SshConfig::parse("Host a\n  ProxyJump b\nHost b\n  ProxyJump a\n");
+        assert!(build_connections(&config, &["a".to_string()], &[], "").is_err());
+    }
+
+    #[test]
+    fn test_host_matches() {
+        let patterns = vec!["*.internal".to_string(), "!skip.internal".to_string()];
+        assert!(host_matches("db.internal", &patterns));
+        assert!(!host_matches("skip.internal", &patterns));
+        assert!(!host_matches("db.example.com", &patterns));
+        assert!(glob_match("web-?", "web-1"));
+        assert!(!glob_match("web-?", "web-10"));
+    }
+}
@@ -1,16 +1,23 @@
 use std::path::Path;
+use super::host_key::HostKeyVerdict;
+use super::manifest::{self, DirManifest, ManifestDiff, DIR_MANIFEST_NAME};
 use super::session::{self, upload_file_via_sftp, SshSession};
 use super::types::{SSHConnection, SSHConnectionResult, SSHFileMapping, SyncResult};
+use super::sftp_ops;
+use crate::coding::remote_tools::{
+    detect_dir_candidates, detection_script, parse_detection_output, tool_status, RemoteToolStatus,
+};
+use crate::coding::tools::BUILTIN_TOOLS;
 
 // ============================================================================
 // Connection Testing
 // ============================================================================
 
 /// 测试 SSH 连接（独立短连接，不复用主连接）
-/// 用于测试未保存的连接配置
-pub async fn test_connection(conn: &SSHConnection) -> SSHConnectionResult {
-    match session::test_connection_with_command(conn, "uname -a").await {
-        Ok(output) => {
+/// 用于测试未保存的连接配置，jumps 为依次经过的跳板机
+pub async fn test_connection(conn: &SSHConnection, jumps: &[SSHConnection]) -> SSHConnectionResult {
+    match session::test_connection_with_command(conn, jumps, "uname -a").await {
+        Ok((output, verdict, fingerprint)) => {
             let server_info = output.trim().to_string();
             SSHConnectionResult {
                 connected: true,
@@ -20,16 +27,53 @@ pub async fn test_connection(conn: &SSHConnection) -> SSHConnectionResult {
                 } else {
                     Some(server_info)
                 },
+                host_key_fingerprint: Some(fingerprint),
+                host_key_status: Some(verdict.status().to_string()),
+                host_key: match verdict {
+                    HostKeyVerdict::New(key) => Some(key),
+                    _ => None,
+                },
             }
         }
         Err(e) => SSHConnectionResult {
             connected: false,
             error: Some(e),
             server_info: None,
+            host_key_fingerprint: None,
+            host_key_status: None,
+            host_key: None,
         },
     }
 }
 
+// ============================================================================
+// Remote Tool Detection
+// ============================================================================
+
+/// 检测远程主机上安装了哪些工具（检测目录和 CLI）
+pub async fn detect_remote_tools(session: &SshSession) -> Result<Vec<RemoteToolStatus>, String> {
+    if session.sftp_only() {
+        // 纯 SFTP 模式只能检查目录，无法查找 CLI
+        let sftp = session.create_sftp_session().await?;
+        let mut tools = Vec::new();
+        for tool in BUILTIN_TOOLS {
+            let (dirs, ignore) = detect_dir_candidates(tool);
+            let mut found = None;
+            for dir in dirs {
+                if sftp_ops::has_content(&sftp, &dir, ignore).await {
+                    found = Some(dir);
+                    break;
+                }
+            }
+            tools.push(tool_status(tool, found, None));
+        }
+        return Ok(tools);
+    }
+
+    let output = session.exec_command(&detection_script()).await?;
+    Ok(parse_detection_output(&output))
+}
+
 // ============================================================================
 // Path Expansion
 // ============================================================================
@@ -55,11 +99,12 @@ pub async fn sync_single_file(
         return Ok(vec![]);
     }
 
-    let remote_target = remote_path.replace("~", "$HOME");
-
-    // 创建远程目录
-    let mkdir_cmd = format!("mkdir -p \"$(dirname \"{}\")\"", remote_target);
-    session.exec_command(&mkdir_cmd).await?;
+    // 创建远程目录（纯 SFTP 模式下由上传时创建）
+    if !session.sftp_only() {
+        let remote_target = remote_path.replace("~", "$HOME");
+        let mkdir_cmd = format!("mkdir -p \"$(dirname \"{}\")\"", remote_target);
+        session.exec_command(&mkdir_cmd).await?;
+    }
 
     // SFTP 上传文件
     session.upload_file(&expanded, remote_path).await?;
@@ -68,7 +113,9 @@ pub async fn sync_single_file(
 }
 
 /// 同步整个目录到远程（通过 SFTP）
-/// 使用临时目录 + mv 实现原子替换，防止上传中断导致数据丢失
+///
+/// 远程目录中有同步清单时增量同步：只上传内容变化的文件并删除本地已不存在的文件，
+/// 没有变化时不上传任何内容（返回空列表）。没有清单时完整上传。
 pub async fn sync_directory(
     local_path: &str,
     remote_path: &str,
@@ -80,14 +127,80 @@ pub async fn sync_directory(
         return Ok(vec![]);
     }
 
-    let remote_target = remote_path.replace("~", "$HOME");
-
     // 安全检查：禁止对根路径或家目录执行操作
     let trimmed = remote_path.trim();
     if trimmed.is_empty() || trimmed == "/" || trimmed == "~" || trimmed == "$HOME" {
         return Err(format!("拒绝同步到危险路径: '{}'", remote_path));
     }
 
+    let local_manifest = DirManifest::from_local_dir(Path::new(&expanded))?;
+    let manifest_path = format!("{}/{}", remote_path.trim_end_matches('/'), DIR_MANIFEST_NAME);
+
+    match manifest::read_remote_manifest::<DirManifest>(session, &manifest_path).await {
+        Some(remote_manifest) => {
+            let diff = local_manifest.diff(&remote_manifest);
+            if diff.is_empty() {
+                return Ok(vec![]);
+            }
+            sync_directory_changes(&expanded, remote_path, session, &diff).await?;
+        }
+        None => sync_directory_full(&expanded, remote_path, session).await?,
+    }
+
+    // 文件全部上传后再写入清单
+    manifest::write_remote_manifest(session, &manifest_path, &local_manifest).await?;
+
+    Ok(vec![format!("{} -> {}", local_path, remote_path)])
+}
+
+/// 按清单差异增量同步：上传变化的文件，删除本地已不存在的文件
+async fn sync_directory_changes(
+    local_dir: &str,
+    remote_path: &str,
+    session: &SshSession,
+    diff: &ManifestDiff,
+) -> Result<(), String> {
+    let remote_base = remote_path.trim_end_matches('/');
+
+    if !diff.changed.is_empty() {
+        let sftp = session.create_sftp_session().await?;
+        for relative in &diff.changed {
+            let local_file = Path::new(local_dir).join(relative);
+            upload_file_via_sftp(
+                &sftp,
+                &local_file.to_string_lossy(),
+                &format!("{}/{}", remote_base, relative),
+            )
+            .await?;
+        }
+    }
+
+    // 通过 SFTP 删除，远程清单中的路径不经过 shell
+    if !diff.removed.is_empty() {
+        let sftp = session.create_sftp_session().await?;
+        for relative in &diff.removed {
+            sftp_ops::remove_all(&sftp, &format!("{}/{}", remote_base, relative)).await?;
+        }
+    }
+
+    log::info!(
+        "增量同步 {}: 上传 {} 个文件，删除 {} 个文件",
+        remote_path,
+        diff.changed.len(),
+        diff.removed.len()
+    );
+    Ok(())
+}
+
+/// 完整上传目录
+/// 使用临时目录 + mv 实现原子替换，防止上传中断导致数据丢失
+async fn sync_directory_full(
+    expanded: &str,
+    remote_path: &str,
+    session: &SshSession,
+) -> Result<(), String> {
+    let remote_target = remote_path.replace("~", "$HOME");
+
     // 使用临时目录上传，完成后原子替换
     let tmp_suffix = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
@@ -96,12 +209,16 @@ pub async fn sync_directory(
     let tmp_remote_path = format!("{}.tmp_{}", remote_path, tmp_suffix);
     let tmp_remote_target = format!("{}.tmp_{}", remote_target, tmp_suffix);
 
+    if session.sftp_only() {
+        return sync_directory_full_sftp(expanded, remote_path, &tmp_remote_path, session).await;
+    }
+
     // 创建远程父目录
     let mkdir_cmd = format!("mkdir -p \"$(dirname \"{}\")\"", remote_target);
     session.exec_command(&mkdir_cmd).await?;
 
     // SFTP 递归上传到临时目录（upload_dir 内部会展开 ~ 和 $HOME）
-    session.upload_dir(&expanded, &tmp_remote_path).await?;
+    session.upload_dir(expanded, &tmp_remote_path).await?;
 
     // 原子替换：rm 旧目录 + mv 临时目录到目标
     let swap_cmd = format!(
@@ -116,7 +233,28 @@ pub async fn sync_directory(
         return Err(format!("目录替换失败: {}", e));
     }
 
-    Ok(vec![format!("{} -> {}", local_path, remote_path)])
+    Ok(())
+}
+
+/// 纯 SFTP 模式的完整上传：上传到临时目录后通过 SFTP 删除旧目录并重命名
+async fn sync_directory_full_sftp(
+    expanded: &str,
+    remote_path: &str,
+    tmp_remote_path: &str,
+    session: &SshSession,
+) -> Result<(), String> {
+    let sftp = session.create_sftp_session().await?;
+    sftp_ops::mkdir_parent(&sftp, remote_path).await?;
+
+    session.upload_dir(expanded, tmp_remote_path).await?;
+
+    if let Err(e) = sftp_ops::replace(&sftp, tmp_remote_path, remote_path).await {
+        // 替换失败，清理临时目录
+        let _ = sftp_ops::remove_all(&sftp, tmp_remote_path).await;
+        return Err(format!("目录替换失败: {}", e));
+    }
+
+    Ok(())
 }
 
 /// 同步符合 glob 模式的文件到远程
@@ -137,15 +275,18 @@ pub async fn sync_pattern_files(
         return Ok(vec![]);
     }
 
-    let remote_target = remote_dir.replace("~", "$HOME");
-
-    // 创建远程目录
-    let mkdir_cmd = format!("mkdir -p \"{}\"", remote_target);
-    session.exec_command(&mkdir_cmd).await?;
-
     // 复用同一个 SFTP session 上传所有文件
     let sftp = session.create_sftp_session().await?;
 
+    // 创建远程目录
+    if session.sftp_only() {
+        sftp_ops::mkdir_p(&sftp, remote_dir).await?;
+    } else {
+        let remote_target = remote_dir.replace("~", "$HOME");
+        let mkdir_cmd = format!("mkdir -p \"{}\"", remote_target);
+        session.exec_command(&mkdir_cmd).await?;
+    }
+
     let mut synced = vec![];
     for file_path in &matches {
         let file_str = file_path.to_string_lossy().to_string();
@@ -270,6 +411,11 @@ pub fn check_file_encoding(content: &str, file_path: &str) -> Result<(), String>
 /// 适用于我们自己控制的文件（hash 文件等），不需要编码检测。
 /// 对于用户配置文件（claude.json, opencode.json 等），应使用 `read_remote_file`。
 pub async fn read_remote_file_raw(session: &SshSession, path: &str) -> Result<String, String> {
+    if session.sftp_only() {
+        let sftp = session.create_sftp_session().await?;
+        return sftp_ops::read_file(&sftp, path).await;
+    }
+
     let remote_path = path.replace("~", "$HOME");
 
     let command = format!(
@@ -296,6 +442,11 @@ pub async fn read_remote_file(session: &SshSession, path: &str) -> Result<String
         return Ok(content);
     }
 
+    // SFTP-only hosts cannot run iconv
+    if session.sftp_only() {
+        return check_file_encoding(&content, path).map(|()| content);
+    }
+
     // Non-UTF-8 detected, try iconv GBK→UTF-8 on remote
     log::warn!("File {} is non-UTF-8, attempting remote iconv GBK→UTF-8...", path);
 
@@ -321,6 +472,11 @@ pub async fn write_remote_file(
     path: &str,
     content: &str,
 ) -> Result<(), String> {
+    if session.sftp_only() {
+        let sftp = session.create_sftp_session().await?;
+        return sftp_ops::write_file(&sftp, path, content.as_bytes()).await;
+    }
+
     let remote_path = path.replace("~", "$HOME");
 
     let command = format!(
@@ -339,6 +495,11 @@ pub async fn create_remote_symlink(
     target: &str,
     link_path: &str,
 ) -> Result<(), String> {
+    if session.sftp_only() {
+        let sftp = session.create_sftp_session().await?;
+        return sftp_ops::symlink(&sftp, target, link_path).await;
+    }
+
     let target_expanded = target.replace("~", "$HOME");
     let link_expanded = link_path.replace("~", "$HOME");
 
@@ -359,6 +520,11 @@ pub async fn remove_remote_path(session: &SshSession, path: &str) -> Result<(),
         return Err(format!("拒绝删除危险路径: '{}'", path));
     }
 
+    if session.sftp_only() {
+        let sftp = session.create_sftp_session().await?;
+        return sftp_ops::remove_all(&sftp, path).await;
+    }
+
     let remote_path = path.replace("~", "$HOME");
     let command = format!("rm -rf \"{}\"", remote_path);
 
@@ -368,6 +534,11 @@ pub async fn remove_remote_path(session: &SshSession, path: &str) -> Result<(),
 
 /// 列出远程目录中的子目录
 pub async fn list_remote_dir(session: &SshSession, path: &str) -> Result<Vec<String>, String> {
+    if session.sftp_only() {
+        let sftp = session.create_sftp_session().await?;
+        return sftp_ops::list_dir(&sftp, path).await;
+    }
+
     let remote_path = path.replace("~", "$HOME");
     let command = format!(
         "if [ -d \"{}\" ]; then ls -1 \"{}\"; fi",
@@ -389,6 +560,16 @@ pub async fn check_remote_symlink_exists(
     link_path: &str,
     expected_target: &str,
 ) -> bool {
+    if session.sftp_only() {
+        let Ok(sftp) = session.create_sftp_session().await else {
+            return false;
+        };
+        let Ok(expected) = sftp_ops::resolve(&sftp, expected_target).await else {
+            return false;
+        };
+        return sftp_ops::read_link(&sftp, link_path).await.as_deref() == Some(expected.as_str());
+    }
+
     let link_expanded = link_path.replace("~", "$HOME");
     let target_expanded = expected_target.replace("~", "$HOME");
     let command = format!(
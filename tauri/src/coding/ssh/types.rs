@@ -16,12 +16,25 @@ pub struct SSHConnection {
     pub host: String,
     pub port: u16,
     pub username: String,
-    pub auth_method: String, // "key" | "password"
+    pub auth_method: String, // "key" | "password" | "agent"
     pub password: String,
     pub private_key_path: String,
     pub private_key_content: String,
     pub passphrase: String,
     pub sort_order: u32,
+    /// Trusted server host key in OpenSSH format, pinned on first connect
+    #[serde(default)]
+    pub host_key: String,
+    /// Synced together with the active connection
+    #[serde(default)]
+    pub sync_target: bool,
+    /// Jump hosts (ProxyJump) as IDs of other connections, first hop first.
+    /// A jump host's own proxy_jump is connected before it.
+    #[serde(default)]
+    pub proxy_jump: Vec<String>,
+    /// SFTP-only account or non-POSIX shell: sync without running remote commands
+    #[serde(default)]
+    pub sftp_only: bool,
 }
 
 // ============================================================================
@@ -57,11 +70,36 @@ pub struct SSHSyncConfig {
     pub sync_skills: bool,
     pub file_mappings: Vec<SSHFileMapping>,
     pub connections: Vec<SSHConnection>,
+    /// 配置、MCP、Skills 变更后自动同步（防抖）
+    #[serde(default = "default_auto_sync")]
+    pub auto_sync: bool,
+    /// 定时同步间隔（分钟），0 表示不定时同步
+    #[serde(default)]
+    pub sync_interval_minutes: u32,
     pub last_sync_time: Option<String>,
     pub last_sync_status: String, // "success" | "error" | "never"
     pub last_sync_error: Option<String>,
 }
 
+fn default_auto_sync() -> bool {
+    true
+}
+
+impl SSHSyncConfig {
+    /// Connections to sync: the active connection first, then the other sync targets
+    pub fn sync_targets(&self) -> Vec<&SSHConnection> {
+        let active = self
+            .connections
+            .iter()
+            .filter(|c| c.id == self.active_connection_id);
+        let others = self
+            .connections
+            .iter()
+            .filter(|c| c.sync_target && c.id != self.active_connection_id);
+        active.chain(others).collect()
+    }
+}
+
 impl Default for SSHSyncConfig {
     fn default() -> Self {
         Self {
@@ -71,6 +109,8 @@ impl Default for SSHSyncConfig {
             sync_skills: true,
             file_mappings: vec![],
             connections: vec![],
+            auto_sync: true,
+            sync_interval_minutes: 0,
             last_sync_time: None,
             last_sync_status: "never".to_string(),
             last_sync_error: None,
@@ -89,6 +129,23 @@ pub struct SSHConnectionResult {
     pub connected: bool,
     pub error: Option<String>,
     pub server_info: Option<String>,
+    /// SHA256 fingerprint of the server host key
+    pub host_key_fingerprint: Option<String>,
+    /// "pinned" | "knownHosts" | "new" | "mismatch"
+    pub host_key_status: Option<String>,
+    /// Server host key (OpenSSH format) when it was trusted for the first time
+    pub host_key: Option<String>,
+}
+
+/// Last sync result of one sync target
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SSHHostSyncStatus {
+    pub connection_id: String,
+    pub connection_name: String,
+    pub last_sync_time: Option<String>,
+    pub last_sync_status: String, // "success" | "error" | "never"
+    pub last_sync_error: Option<String>,
 }
 
 /// SSH status result
@@ -100,4 +157,6 @@ pub struct SSHStatusResult {
     pub last_sync_time: Option<String>,
     pub last_sync_status: String,
     pub last_sync_error: Option<String>,
+    /// Per-host status of all sync targets
+    pub hosts: Vec<SSHHostSyncStatus>,
 }
@@ -0,0 +1,376 @@
+//! Sync plan: what a push to SSH / WSL would do to the files on the remote side
+//!
+//! For every file mapping the local content, the current remote content and the remote
+//! content right after our last push are hashed. Comparing the three tells whether the
+//! remote copy is missing, identical, just older than the local one, or was modified on
+//! the remote side since the last push (and would be overwritten).
+//!
+//! The SSH and WSL modules run `remote_hash_script` with their own transport and record
+//! the remote hashes after each push with `record_pushed_hashes`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use walkdir::WalkDir;
+
+use crate::coding::ssh::DIR_MANIFEST_NAME;
+use crate::DbState;
+
+/// Printed by the remote script when the remote path exists
+const EXISTS_MARKER: &str = "__ai_toolbox_exists__";
+
+/// Relative path ("" for a single file) -> SHA-256
+pub type FileHashes = BTreeMap<String, String>;
+
+/// What a file mapping points at
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MappingKind {
+    File,
+    Directory,
+    /// Files matching a glob in the local directory, copied into the remote directory
+    Pattern,
+}
+
+impl MappingKind {
+    pub fn of(is_directory: bool, is_pattern: bool) -> Self {
+        if is_directory {
+            Self::Directory
+        } else if is_pattern {
+            Self::Pattern
+        } else {
+            Self::File
+        }
+    }
+}
+
+/// Plan for one file mapping
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncPlanItem {
+    pub mapping_id: String,
+    pub name: String,
+    pub module: String,
+    pub local_path: String,
+    pub remote_path: String,
+    /// "missing" | "identical" | "older" | "modified" | "skipped" (nothing to push) | "error"
+    pub status: String,
+    /// Pushing would overwrite changes made on the remote side
+    pub destructive: bool,
+    pub local_hash: Option<String>,
+    pub remote_hash: Option<String>,
+    /// Remote hash recorded right after the last push
+    pub pushed_hash: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Plan for one sync target (an SSH host or the WSL distro)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncPlan {
+    pub target_id: String,
+    pub target_name: String,
+    pub items: Vec<SyncPlanItem>,
+    pub errors: Vec<String>,
+}
+
+/// Hash the local side of a mapping; `None` when there is nothing to push
+pub fn local_hashes(path: &Path, kind: MappingKind) -> Result<Option<FileHashes>, String> {
+    let mut hashes = FileHashes::new();
+    match kind {
+        MappingKind::File => {
+            if !path.is_file() {
+                return Ok(None);
+            }
+            hashes.insert(String::new(), hash_file(path)?);
+        }
+        MappingKind::Directory => {
+            if !path.is_dir() {
+                return Ok(None);
+            }
+            for entry in WalkDir::new(path).follow_links(true) {
+                let entry = entry.map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+                if !entry.file_type().is_file() || entry.file_name() == DIR_MANIFEST_NAME {
+                    continue;
+                }
+                let relative = entry
+                    .path()
+                    .strip_prefix(path)
+                    .map_err(|e| format!("Failed to resolve relative path: {}", e))?
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy().to_string())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                hashes.insert(relative, hash_file(entry.path())?);
+            }
+        }
+        MappingKind::Pattern => {
+            let pattern = path.to_string_lossy();
+            let matches = glob::glob(&pattern).map_err(|e| format!("Invalid glob pattern: {}", e))?;
+            for file in matches.filter_map(|entry| entry.ok()).filter(|p| p.is_file()) {
+                let name = file
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                hashes.insert(name, hash_file(&file)?);
+            }
+        }
+    }
+    Ok(if hashes.is_empty() { None } else { Some(hashes) })
+}
+
+fn hash_file(path: &Path) -> Result<String, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok(hex::encode(Sha256::digest(&bytes)))
+}
+
+/// POSIX shell script printing the SHA-256 of every remote file of a mapping
+///
+/// For patterns `remote_path` is the target directory and `local_path` supplies the
+/// file name glob.
+pub fn remote_hash_script(
+    remote_path: &str,
+    local_path: &str,
+    kind: MappingKind,
+) -> Result<String, String> {
+    let path = remote_path.replace("~", "$HOME");
+    let hash_fn = "h() { if command -v sha256sum >/dev/null 2>&1; then sha256sum \"$1\"; else shasum -a 256 \"$1\"; fi; }";
+
+    let body = match kind {
+        MappingKind::File => format!(
+            "if [ -f \"{path}\" ]; then echo {EXISTS_MARKER}; h \"{path}\"; fi",
+        ),
+        MappingKind::Directory => format!(
+            "if [ -d \"{path}\" ]; then cd \"{path}\" && echo {EXISTS_MARKER} && \
+             find -L . -type f ! -name '{DIR_MANIFEST_NAME}' | while IFS= read -r f; do h \"$f\"; done; fi",
+        ),
+        MappingKind::Pattern => {
+            let glob = local_path.rsplit(['/', '\\']).next().unwrap_or_default();
+            let safe = !glob.is_empty()
+                && glob
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "._-*?".contains(c));
+            if !safe {
+                return Err(format!("Unsupported file pattern: {}", glob));
+            }
+            format!(
+                "if [ -d \"{path}\" ]; then cd \"{path}\" && echo {EXISTS_MARKER} && \
+                 for f in {glob}; do [ -f \"$f\" ] && h \"$f\"; done; fi",
+            )
+        }
+    };
+    Ok(format!("{} {}; true", hash_fn, body))
+}
+
+/// Parse the output of `remote_hash_script`; `None` when the remote side is missing or empty
+pub fn parse_remote_hashes(output: &str, kind: MappingKind) -> Option<FileHashes> {
+    let mut lines = output.lines().map(str::trim_end);
+    lines.find(|line| *line == EXISTS_MARKER)?;
+
+    let mut hashes = FileHashes::new();
+    for line in lines {
+        let Some((hash, name)) = line.split_once(' ') else {
+            continue;
+        };
+        if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            continue;
+        }
+        let name = name.trim_start_matches([' ', '*']);
+        let key = match kind {
+            MappingKind::File => String::new(),
+            _ => name.trim_start_matches("./").to_string(),
+        };
+        hashes.insert(key, hash.to_lowercase());
+    }
+    if hashes.is_empty() {
+        None
+    } else {
+        Some(hashes)
+    }
+}
+
+/// Single hash over all files of a mapping
+pub fn combined_hash(hashes: &FileHashes) -> String {
+    let mut hasher = Sha256::new();
+    for (path, hash) in hashes {
+        hasher.update(path.as_bytes());
+        hasher.update([0]);
+        hasher.update(hash.as_bytes());
+        hasher.update(b"\n");
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Compare local, remote and last pushed hashes: (status, destructive)
+pub fn classify(
+    local: Option<&str>,
+    remote: Option<&str>,
+    pushed: Option<&str>,
+) -> (&'static str, bool) {
+    match (local, remote) {
+        (None, _) => ("skipped", false),
+        (Some(_), None) => ("missing", false),
+        (Some(local), Some(remote)) if local == remote => ("identical", false),
+        (Some(_), Some(remote)) if pushed == Some(remote) => ("older", false),
+        // Changed on the remote side since our last push, or never pushed by us
+        _ => ("modified", true),
+    }
+}
+
+/// Build the plan item of a mapping from the hashes
+#[allow(clippy::too_many_arguments)]
+pub fn plan_item(
+    mapping_id: &str,
+    name: &str,
+    module: &str,
+    local_path: &str,
+    remote_path: &str,
+    local: Result<Option<FileHashes>, String>,
+    remote: Result<Option<FileHashes>, String>,
+    pushed: Option<&String>,
+) -> SyncPlanItem {
+    let mut item = SyncPlanItem {
+        mapping_id: mapping_id.to_string(),
+        name: name.to_string(),
+        module: module.to_string(),
+        local_path: local_path.to_string(),
+        remote_path: remote_path.to_string(),
+        status: "error".to_string(),
+        destructive: false,
+        local_hash: None,
+        remote_hash: None,
+        pushed_hash: pushed.cloned(),
+        error: None,
+    };
+
+    let (local, remote) = match (local, remote) {
+        (Ok(local), Ok(remote)) => (local, remote),
+        (Err(e), _) | (_, Err(e)) => {
+            item.error = Some(e);
+            return item;
+        }
+    };
+    item.local_hash = local.as_ref().map(combined_hash);
+    item.remote_hash = remote.as_ref().map(combined_hash);
+
+    let (status, destructive) = classify(
+        item.local_hash.as_deref(),
+        item.remote_hash.as_deref(),
+        item.pushed_hash.as_deref(),
+    );
+    item.status = status.to_string();
+    item.destructive = destructive;
+    item
+}
+
+/// Remote hashes recorded after the last push to a target: mapping ID -> hash
+pub async fn get_pushed_hashes(state: &DbState, target: &str) -> Result<HashMap<String, String>, String> {
+    let db = state.0.lock().await;
+    let records: Vec<Value> = db
+        .query("SELECT mapping_id, hash FROM sync_pushed_hash WHERE target = $target")
+        .bind(("target", target.to_string()))
+        .await
+        .map_err(|e| format!("Failed to query pushed hashes: {}", e))?
+        .take(0)
+        .unwrap_or_default();
+
+    Ok(records
+        .into_iter()
+        .filter_map(|r| {
+            let mapping_id = r.get("mapping_id")?.as_str()?.to_string();
+            let hash = r.get("hash")?.as_str()?.to_string();
+            Some((mapping_id, hash))
+        })
+        .collect())
+}
+
+/// Record the remote hashes right after a push; `None` clears the record (remote missing)
+pub async fn record_pushed_hashes(
+    state: &DbState,
+    target: &str,
+    hashes: Vec<(String, Option<String>)>,
+) -> Result<(), String> {
+    let db = state.0.lock().await;
+    let now = chrono::Local::now().to_rfc3339();
+    for (mapping_id, hash) in hashes {
+        let record_id = format!("{}_{}", target, mapping_id);
+        match hash {
+            Some(hash) => {
+                db.query("UPSERT type::thing('sync_pushed_hash', $id) CONTENT $data")
+                    .bind(("id", record_id))
+                    .bind((
+                        "data",
+                        json!({
+                            "target": target,
+                            "mapping_id": mapping_id,
+                            "hash": hash,
+                            "pushed_at": now,
+                        }),
+                    ))
+                    .await
+                    .map_err(|e| format!("Failed to record pushed hash: {}", e))?;
+            }
+            None => {
+                db.query("DELETE type::thing('sync_pushed_hash', $id)")
+                    .bind(("id", record_id))
+                    .await
+                    .map_err(|e| format!("Failed to clear pushed hash: {}", e))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Forget the pushed hashes of a target (e.g. a deleted SSH connection)
+pub async fn clear_pushed_hashes(state: &DbState, target: &str) -> Result<(), String> {
+    let db = state.0.lock().await;
+    db.query("DELETE sync_pushed_hash WHERE target = $target")
+        .bind(("target", target.to_string()))
+        .await
+        .map_err(|e| format!("Failed to clear pushed hashes: {}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify(None, Some("a"), None), ("skipped", false));
+        assert_eq!(classify(Some("a"), None, Some("b")), ("missing", false));
+        assert_eq!(classify(Some("a"), Some("a"), None), ("identical", false));
+        assert_eq!(classify(Some("a"), Some("b"), Some("b")), ("older", false));
+        assert_eq!(classify(Some("a"), Some("c"), Some("b")), ("modified", true));
+        assert_eq!(classify(Some("a"), Some("c"), None), ("modified", true));
+    }
+
+    #[test]
+    fn test_remote_hashes_match_local() {
+        let dir = std::env::temp_dir().join(format!("ai-toolbox-plan-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::write(dir.join("a.txt"), "hello").unwrap();
+        std::fs::write(dir.join("sub").join("b.txt"), "world").unwrap();
+        std::fs::write(dir.join(DIR_MANIFEST_NAME), "{}").unwrap();
+
+        let local = local_hashes(&dir, MappingKind::Directory).unwrap().unwrap();
+        let single = local_hashes(&dir.join("a.txt"), MappingKind::File).unwrap().unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let output = format!(
+            "{}\n{}  ./a.txt\n{}  ./sub/b.txt\n",
+            EXISTS_MARKER, local["a.txt"], local["sub/b.txt"]
+        );
+        let remote = parse_remote_hashes(&output, MappingKind::Directory).unwrap();
+        assert_eq!(combined_hash(&remote), combined_hash(&local));
+
+        let output = format!("{}\n{} */home/u/a.txt\n", EXISTS_MARKER, single[""]);
+        let remote = parse_remote_hashes(&output, MappingKind::File).unwrap();
+        assert_eq!(remote, single);
+
+        assert!(parse_remote_hashes("", MappingKind::File).is_none());
+        assert!(remote_hash_script("~/x", "~/a/$(rm).md", MappingKind::Pattern).is_err());
+    }
+}
@@ -108,15 +108,15 @@ pub const BUILTIN_TOOLS: &[BuiltinTool] = &[
         mcp_config_format: Some("json"),
         mcp_field: Some("mcpServers"),
     },
-    // Goose - Skills only
+    // Goose - supports both Skills and MCP (MCP servers are "extensions")
     BuiltinTool {
         key: "goose",
         display_name: "Goose",
         relative_skills_dir: Some("~/.config/goose/skills"),
         relative_detect_dir: Some("~/.config/goose"),
-        mcp_config_path: None,
-        mcp_config_format: None,
-        mcp_field: None,
+        mcp_config_path: Some("~/.config/goose/config.yaml"),
+        mcp_config_format: Some("yaml"),
+        mcp_field: Some("extensions"),
     },
     // GitHub Copilot - supports both Skills and MCP
     // MCP path uses VSCode plugin config path (same as Amp)
@@ -149,6 +149,16 @@ pub const BUILTIN_TOOLS: &[BuiltinTool] = &[
         mcp_config_format: Some("json"),
         mcp_field: Some("mcpServers"),
     },
+    // Continue - MCP only
+    BuiltinTool {
+        key: "continue",
+        display_name: "Continue",
+        relative_skills_dir: None,
+        relative_detect_dir: Some("~/.continue"),
+        mcp_config_path: Some("~/.continue/config.yaml"),
+        mcp_config_format: Some("yaml"),
+        mcp_field: Some("mcpServers"),
+    },
     // Windsurf - supports both Skills and MCP
     BuiltinTool {
         key: "windsurf",
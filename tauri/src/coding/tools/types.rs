@@ -15,7 +15,7 @@ pub struct BuiltinTool {
     pub relative_detect_dir: Option<&'static str>,
     // MCP related (optional)
     pub mcp_config_path: Option<&'static str>,
-    pub mcp_config_format: Option<&'static str>, // "json" | "toml"
+    pub mcp_config_format: Option<&'static str>, // "json" | "jsonc" | "toml" | "yaml"
     pub mcp_field: Option<&'static str>,         // field name in config file
 }
 
@@ -159,6 +159,17 @@ pub struct McpFormatConfig {
     pub requires_enabled: bool,
     /// Default tool type when type field is missing (e.g., "local" for OpenCode)
     pub default_tool_type: &'static str,
+    /// Field name for the stdio command ("command" or "cmd")
+    pub command_field: &'static str,
+    /// Field name for the http/sse URL ("url" or "uri")
+    pub url_field: &'static str,
+    /// Field repeating the server name inside each entry (e.g. Goose `name`)
+    pub name_field: Option<&'static str>,
+    /// Timeout in seconds written with each server (e.g. Goose `timeout`)
+    pub timeout_secs: Option<u64>,
+    /// Whether servers are a list of entries identified by `name_field` (Continue)
+    /// instead of a map keyed by server name
+    pub list_entries: bool,
 }
 
 impl McpFormatConfig {
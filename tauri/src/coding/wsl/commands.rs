@@ -2,6 +2,7 @@ use super::{sync, adapter};
 use super::types::{FileMapping, SyncProgress, SyncResult, WSLErrorResult, WSLDetectResult, WSLStatusResult, WSLSyncConfig};
 use crate::db::DbState;
 use crate::coding::{open_code, oh_my_opencode, oh_my_opencode_slim};
+use crate::coding::remote_tools::RemoteToolMatrix;
 use tauri::Emitter;
 use chrono::Local;
 
@@ -39,6 +40,24 @@ pub fn wsl_get_distro_state(distro: String) -> String {
     sync::get_wsl_distro_state(&distro)
 }
 
+/// Detect which tools are installed in the configured WSL distro
+#[tauri::command]
+pub async fn wsl_detect_tools(state: tauri::State<'_, DbState>) -> Result<RemoteToolMatrix, String> {
+    let config = wsl_get_config(state).await?;
+    let distro = sync::get_effective_distro(&config.distro)?;
+
+    let mut matrix = RemoteToolMatrix {
+        target_id: format!("wsl:{}", distro),
+        target_name: distro.clone(),
+        ..Default::default()
+    };
+    match sync::detect_wsl_tools(&distro) {
+        Ok(tools) => matrix.tools = tools,
+        Err(e) => matrix.error = Some(e),
+    }
+    Ok(matrix)
+}
+
 // ============================================================================
 // WSL Config Commands
 // ============================================================================
@@ -270,6 +289,7 @@ pub(super) async fn do_full_sync(
         current: 0,
         total: total_files,
         message: format!("文件同步: 0/{}", total_files),
+        connection_id: None,
     });
 
     // Dynamically resolve config file paths for opencode and oh-my-opencode
@@ -294,6 +314,9 @@ pub(super) async fn do_full_sync(
         }
     }
 
+    // Record the WSL hashes as the baseline for the next sync plan
+    super::plan::record_pushed(state, &distro, &file_mappings, module, &result.errors).await;
+
     result
 }
 
@@ -326,6 +349,7 @@ fn sync_mappings_with_progress(
             current,
             total,
             message: format!("文件同步: {}/{} - {}", current, total, mapping.name),
+            connection_id: None,
         });
 
         match sync::sync_file_mapping(mapping, distro) {
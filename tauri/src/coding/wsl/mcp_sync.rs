@@ -10,14 +10,18 @@ use tauri::{AppHandle, Emitter};
 
 use super::adapter;
 use super::commands::resolve_dynamic_paths;
-use super::sync::{read_wsl_file, sync_mappings, write_wsl_file};
+use super::sync::{detect_wsl_tools, read_wsl_file, sync_mappings, write_wsl_file};
 use super::types::{FileMapping, SyncProgress, WSLSyncConfig};
 use crate::coding::mcp::command_normalize;
+use crate::coding::mcp::config_sync::apply_target_env_to_content;
+use crate::coding::mcp::env_vars::{self, EnvResolver};
 use crate::coding::mcp::mcp_store;
+use crate::coding::mcp::types::McpServer;
+use crate::coding::remote_tools::installed_tool_keys;
 use crate::DbState;
 
 /// Read WSL sync config directly from database (without tauri::State wrapper)
-async fn get_wsl_config(state: &DbState) -> Result<WSLSyncConfig, String> {
+pub(super) async fn get_wsl_config(state: &DbState) -> Result<WSLSyncConfig, String> {
     let db = state.0.lock().await;
 
     let config_result: Result<Vec<serde_json::Value>, _> = db
@@ -88,16 +92,24 @@ pub async fn sync_mcp_to_wsl(state: &DbState, app: AppHandle) -> Result<(), Stri
         current: 1,
         total: 2,
         message: "MCP 同步: Claude Code...".to_string(),
+        connection_id: None,
     });
 
+    // Only push MCP config to tools installed in WSL
+    let installed_tools = installed_tool_keys(detect_wsl_tools(&distro));
+
     // 1. Claude Code: directly modify WSL ~/.claude.json
     let servers = mcp_store::get_mcp_servers(state).await?;
+    // Variable values for this distro (`${NAME}` placeholders)
+    let env = EnvResolver::load(state, Some(&env_vars::wsl_target(&distro))).await?;
     let claude_servers: Vec<_> = servers
         .iter()
         .filter(|s| s.enabled_tools.contains(&"claude_code".to_string()))
         .collect();
 
-    if let Err(e) = sync_mcp_to_wsl_claude(&distro, &claude_servers) {
+    if !installed_tools.contains("claude_code") {
+        info!("Skipped claude.json MCP sync: Claude Code is not installed in WSL");
+    } else if let Err(e) = sync_mcp_to_wsl_claude(&distro, &claude_servers, &env) {
         log::warn!("Skipped claude.json MCP sync: {}", e);
         all_errors.push(format!("Claude Code: {}", e));
         let _ = app.emit(
@@ -113,6 +125,7 @@ pub async fn sync_mcp_to_wsl(state: &DbState, app: AppHandle) -> Result<(), Stri
         current: 2,
         total: 2,
         message: "MCP 同步: OpenCode/Codex...".to_string(),
+        connection_id: None,
     });
 
     // 2. OpenCode/Codex: sync config files via file mappings
@@ -122,6 +135,7 @@ pub async fn sync_mcp_to_wsl(state: &DbState, app: AppHandle) -> Result<(), Stri
             let mcp_mappings: Vec<_> = file_mappings
                 .into_iter()
                 .filter(|m| m.enabled && mcp_modules.contains(&m.module.as_str()))
+                .filter(|m| installed_tools.contains(&m.module))
                 .collect();
 
             if !mcp_mappings.is_empty() {
@@ -146,6 +160,10 @@ pub async fn sync_mcp_to_wsl(state: &DbState, app: AppHandle) -> Result<(), Stri
                     .collect();
                 for mapping in &resolved {
                     if mapping.enabled && is_mcp_config_file(&mapping.id) && synced_paths.contains(&mapping.wsl_path) {
+                        if let Err(e) = apply_target_env_to_wsl_mcp_file(&distro, &mapping.wsl_path, &mapping.module, &servers, &env) {
+                            log::warn!("Failed to apply MCP variables to {}: {}", mapping.wsl_path, e);
+                            all_errors.push(format!("{}: {}", mapping.wsl_path, e));
+                        }
                         if let Err(e) = strip_cmd_c_from_wsl_mcp_file(&distro, &mapping.wsl_path, &mapping.module) {
                             log::warn!("Failed to strip cmd /c from {}: {}", mapping.wsl_path, e);
                         }
@@ -187,7 +205,8 @@ pub async fn sync_mcp_to_wsl(state: &DbState, app: AppHandle) -> Result<(), Stri
 /// Sync MCP servers to WSL Claude Code ~/.claude.json
 fn sync_mcp_to_wsl_claude(
     distro: &str,
-    servers: &[&crate::coding::mcp::types::McpServer],
+    servers: &[&McpServer],
+    env: &EnvResolver,
 ) -> Result<(), String> {
     let wsl_config_path = "~/.claude.json";
 
@@ -205,7 +224,7 @@ fn sync_mcp_to_wsl_claude(
     // 3. Build mcpServers object
     let mut mcp_servers = serde_json::Map::new();
     for server in servers {
-        let server_config = build_standard_server_config(server);
+        let server_config = build_standard_server_config(&env.resolve_server(server)?);
         mcp_servers.insert(server.name.clone(), server_config);
     }
 
@@ -225,7 +244,7 @@ fn sync_mcp_to_wsl_claude(
 
 /// Build standard JSON server config for Claude Code format
 /// Note: Database stores normalized config (no cmd /c), but we add a safeguard here
-fn build_standard_server_config(server: &crate::coding::mcp::types::McpServer) -> Value {
+fn build_standard_server_config(server: &McpServer) -> Value {
     match server.server_type.as_str() {
         "stdio" => {
             let command = server
@@ -300,6 +319,29 @@ fn is_mcp_config_file(mapping_id: &str) -> bool {
     )
 }
 
+/// Apply this distro's variable values to the servers with placeholders in a synced
+/// MCP config file (the Windows copy holds the local values)
+fn apply_target_env_to_wsl_mcp_file(
+    distro: &str,
+    wsl_path: &str,
+    module: &str,
+    servers: &[McpServer],
+    env: &EnvResolver,
+) -> Result<(), String> {
+    if module == "codex" && !wsl_path.ends_with(".toml") {
+        return Ok(());
+    }
+    let content = read_wsl_file(distro, wsl_path)?;
+    let processed = apply_target_env_to_content(&content, module, servers, env)?;
+
+    if processed != content {
+        write_wsl_file(distro, wsl_path, &processed)?;
+        info!("Applied MCP variables to WSL config: {}", wsl_path);
+    }
+
+    Ok(())
+}
+
 /// Strip cmd /c from WSL MCP config file after sync.
 /// Selects the correct parser based on file extension rather than module name,
 /// so that JSON files are not accidentally parsed as TOML.
@@ -3,9 +3,13 @@ mod adapter;
 mod sync;
 mod commands;
 mod mcp_sync;
+mod plan;
+mod pull;
 mod skills_sync;
 
 pub use types::*;
 pub use commands::*;
+pub use plan::*;
+pub use pull::*;
 pub use mcp_sync::sync_mcp_to_wsl;
 pub use skills_sync::sync_skills_to_wsl;
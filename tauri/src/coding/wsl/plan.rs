@@ -0,0 +1,101 @@
+//! Sync plan: report the state of every mapped WSL file before a sync
+//!
+//! Compares the Windows content, the current WSL content and the WSL hashes recorded
+//! after the last push, so changes made inside the distro are visible before a sync
+//! overwrites them.
+
+use super::commands::{resolve_dynamic_paths, wsl_get_config};
+use super::sync::{expand_env_vars, get_effective_distro, run_wsl_bash};
+use super::types::FileMapping;
+use crate::coding::sync_plan::{
+    self, combined_hash, get_pushed_hashes, local_hashes, parse_remote_hashes, plan_item,
+    remote_hash_script, FileHashes, MappingKind, SyncPlan,
+};
+use crate::DbState;
+
+/// Target ID the pushed hashes of a distro are recorded under
+fn plan_target_id(distro: &str) -> String {
+    format!("wsl:{}", distro)
+}
+
+fn mapping_kind(mapping: &FileMapping) -> MappingKind {
+    MappingKind::of(mapping.is_directory, mapping.is_pattern)
+}
+
+/// Hash the WSL side of a mapping; `None` when it does not exist
+fn remote_hashes(distro: &str, mapping: &FileMapping) -> Result<Option<FileHashes>, String> {
+    let kind = mapping_kind(mapping);
+    let script = remote_hash_script(&mapping.wsl_path, &mapping.windows_path, kind)?;
+    let output = run_wsl_bash(distro, &script)?;
+    Ok(parse_remote_hashes(&output, kind))
+}
+
+/// Enabled mappings of the module with dynamic config paths resolved
+fn plan_mappings(mappings: &[FileMapping], module: Option<&str>) -> Vec<FileMapping> {
+    resolve_dynamic_paths(mappings.to_vec())
+        .into_iter()
+        .filter(|m| m.enabled)
+        .filter(|m| module.is_none() || Some(m.module.as_str()) == module)
+        .collect()
+}
+
+/// Record the WSL hashes after a sync as the baseline for detecting changes made in WSL.
+/// Mappings that failed to sync (`"<name>: <error>"` entries in `errors`) keep their old baseline.
+pub(super) async fn record_pushed(
+    state: &DbState,
+    distro: &str,
+    mappings: &[FileMapping],
+    module: Option<&str>,
+    errors: &[String],
+) {
+    let mut hashes = Vec::new();
+    for mapping in plan_mappings(mappings, module) {
+        let prefix = format!("{}: ", mapping.name);
+        if errors.iter().any(|e| e.starts_with(&prefix)) {
+            continue;
+        }
+        match remote_hashes(distro, &mapping) {
+            Ok(remote) => hashes.push((mapping.id.clone(), remote.as_ref().map(combined_hash))),
+            Err(e) => log::warn!("Failed to hash WSL files of {}: {}", mapping.name, e),
+        }
+    }
+
+    if let Err(e) = sync_plan::record_pushed_hashes(state, &plan_target_id(distro), hashes).await {
+        log::warn!("{}", e);
+    }
+}
+
+/// Build the sync plan: the state of every mapped file in WSL
+#[tauri::command]
+pub async fn wsl_sync_plan(
+    state: tauri::State<'_, DbState>,
+    module: Option<String>,
+) -> Result<SyncPlan, String> {
+    let config = wsl_get_config(state.clone()).await?;
+    let distro = get_effective_distro(&config.distro)?;
+    let target_id = plan_target_id(&distro);
+    let pushed = get_pushed_hashes(&state, &target_id).await?;
+
+    let mut plan = SyncPlan {
+        target_id,
+        target_name: distro.clone(),
+        ..Default::default()
+    };
+    for mapping in plan_mappings(&config.file_mappings, module.as_deref()) {
+        let local = expand_env_vars(&mapping.windows_path)
+            .and_then(|path| local_hashes(std::path::Path::new(&path), mapping_kind(&mapping)));
+        let remote = remote_hashes(&distro, &mapping);
+        plan.items.push(plan_item(
+            &mapping.id,
+            &mapping.name,
+            &mapping.module,
+            &mapping.windows_path,
+            &mapping.wsl_path,
+            local,
+            remote,
+            pushed.get(&mapping.id),
+        ));
+    }
+
+    Ok(plan)
+}
@@ -0,0 +1,114 @@
+//! Reverse sync: pull MCP servers and skills from WSL
+//!
+//! Reads `~/.claude.json`, `~/.codex/config.toml`, `opencode.jsonc` and the tool
+//! skills directories inside the distro and imports the items the user selected.
+
+use super::mcp_sync::get_wsl_config;
+use super::sync::{copy_wsl_dir_to_windows, get_effective_distro, list_wsl_dir, read_wsl_file};
+use crate::coding::mcp::env_vars::wsl_target;
+use crate::coding::remote_pull::{
+    diff_mcp_config, diff_skills, emit_pull_changes, import_mcp_item, import_skill_dir,
+    item_label, remote_skill_dirs, should_import, skill_download_dir, PullItem, PullPreview,
+    PullResult, REMOTE_MCP_SOURCES,
+};
+use crate::DbState;
+
+/// Read MCP configs and skills directories from WSL and compare them with the database
+#[tauri::command]
+pub async fn wsl_pull_preview(state: tauri::State<'_, DbState>) -> Result<PullPreview, String> {
+    let config = get_wsl_config(&state).await?;
+    let distro = get_effective_distro(&config.distro)?;
+
+    let mut preview = PullPreview::default();
+
+    for (tool, paths) in REMOTE_MCP_SOURCES {
+        for path in paths.iter() {
+            let content = match read_wsl_file(&distro, path) {
+                Ok(content) => content,
+                Err(e) => {
+                    preview.errors.push(format!("{}: {}", path, e));
+                    break;
+                }
+            };
+            if content.trim().is_empty() {
+                continue;
+            }
+            match diff_mcp_config(&state, &wsl_target(&distro), tool, &content).await {
+                Ok(items) => preview.items.extend(items),
+                Err(e) => preview.errors.push(format!("{}: {}", path, e)),
+            }
+            break;
+        }
+    }
+
+    for (tool, dir) in remote_skill_dirs() {
+        let names = match list_wsl_dir(&distro, &dir) {
+            Ok(names) => names,
+            Err(e) => {
+                preview.errors.push(format!("{}: {}", dir, e));
+                continue;
+            }
+        };
+        match diff_skills(&state, tool, &dir, names).await {
+            Ok(items) => preview.items.extend(items),
+            Err(e) => preview.errors.push(format!("{}: {}", dir, e)),
+        }
+    }
+
+    Ok(preview)
+}
+
+/// Import the selected MCP servers and skills from WSL
+#[tauri::command]
+pub async fn wsl_pull_import(
+    state: tauri::State<'_, DbState>,
+    app: tauri::AppHandle,
+    items: Vec<PullItem>,
+) -> Result<PullResult, String> {
+    let config = get_wsl_config(&state).await?;
+    let distro = get_effective_distro(&config.distro)?;
+
+    let mut result = PullResult::default();
+    let mut imported = Vec::new();
+
+    for item in items {
+        if !should_import(&item, &imported, &mut result) {
+            continue;
+        }
+
+        let outcome = match item.kind.as_str() {
+            "mcp" => import_mcp_item(&state, &wsl_target(&distro), &item).await,
+            "skill" => {
+                let remote_path = item.remote_path.clone().unwrap_or_default();
+                let local_dir = skill_download_dir(&item.name);
+                let outcome = match copy_wsl_dir_to_windows(
+                    &distro,
+                    &remote_path,
+                    &local_dir.to_string_lossy(),
+                ) {
+                    Ok(()) => {
+                        let source_ref = format!("wsl://{}/{}", distro, remote_path);
+                        import_skill_dir(&app, &state, &item, &local_dir, source_ref).await
+                    }
+                    Err(e) => Err(e),
+                };
+                if let Some(temp_dir) = local_dir.parent() {
+                    let _ = std::fs::remove_dir_all(temp_dir);
+                }
+                outcome
+            }
+            other => Err(format!("Unknown item kind: {}", other)),
+        };
+
+        match outcome {
+            Ok(()) => {
+                result.imported.push(item_label(&item));
+                imported.push((item.kind, item.name));
+            }
+            Err(e) => result.errors.push(format!("{}: {}", item_label(&item), e)),
+        }
+    }
+
+    emit_pull_changes(&app, &imported);
+    Ok(result)
+}
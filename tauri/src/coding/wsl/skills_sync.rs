@@ -9,11 +9,12 @@ use tauri::{AppHandle, Emitter};
 
 use super::adapter;
 use super::sync::{
-    check_wsl_symlink_exists, create_wsl_symlink, list_wsl_dir, read_wsl_file_raw, remove_wsl_path,
+    check_wsl_symlink_exists, create_wsl_symlink, detect_wsl_tools, list_wsl_dir, read_wsl_file_raw, remove_wsl_path,
     sync_directory, write_wsl_file,
 };
 use super::types::{SyncProgress, WSLSyncConfig};
 use crate::coding::skills::central_repo::{resolve_central_repo_path, resolve_skill_central_path};
+use crate::coding::remote_tools::installed_tool_keys;
 use crate::coding::skills::skill_store;
 use crate::coding::tools::builtin::BUILTIN_TOOLS;
 use crate::DbState;
@@ -105,6 +106,7 @@ pub async fn sync_skills_to_wsl(state: &DbState, app: AppHandle) -> Result<(), S
         current: 0,
         total: total_skills,
         message: format!("Skills 同步: 0/{}", total_skills),
+        connection_id: None,
     });
 
     // 1. Get existing skills in WSL central repo
@@ -129,6 +131,9 @@ pub async fn sync_skills_to_wsl(state: &DbState, app: AppHandle) -> Result<(), S
         }
     }
 
+    // Only link skills into tools that are installed in WSL
+    let installed_tools = installed_tool_keys(detect_wsl_tools(&distro));
+
     // 4. Sync/update each skill
     let mut synced_count = 0;
     for (idx, skill) in skills.iter().enumerate() {
@@ -141,6 +146,7 @@ pub async fn sync_skills_to_wsl(state: &DbState, app: AppHandle) -> Result<(), S
             current: current_idx,
             total: total_skills,
             message: format!("Skills 同步: {}/{} - {}", current_idx, total_skills, skill.name),
+            connection_id: None,
         });
 
         let source = resolve_skill_central_path(&skill.central_path, &central_dir);
@@ -177,8 +183,13 @@ pub async fn sync_skills_to_wsl(state: &DbState, app: AppHandle) -> Result<(), S
             }
         }
 
-        // Ensure symlinks for each enabled tool
-        for tool_key in &skill.enabled_tools {
+        // Ensure symlinks for each enabled tool installed in WSL
+        let linked_tools: Vec<&String> = skill
+            .enabled_tools
+            .iter()
+            .filter(|tool| installed_tools.contains(*tool))
+            .collect();
+        for tool_key in &linked_tools {
             if let Some(wsl_skills_dir) = get_wsl_tool_skills_dir(tool_key) {
                 let link_path = format!("{}/{}", wsl_skills_dir, skill.name);
                 if !check_wsl_symlink_exists(&distro, &link_path, &wsl_target) {
@@ -187,9 +198,8 @@ pub async fn sync_skills_to_wsl(state: &DbState, app: AppHandle) -> Result<(), S
             }
         }
 
-        // Remove symlinks for tools that are no longer enabled
-        let enabled_set: HashSet<&str> =
-            skill.enabled_tools.iter().map(|s| s.as_str()).collect();
+        // Remove symlinks for tools that are no longer enabled or not installed
+        let enabled_set: HashSet<&str> = linked_tools.iter().map(|s| s.as_str()).collect();
         for tool_key in get_all_skill_tool_keys() {
             if !enabled_set.contains(tool_key) {
                 if let Some(wsl_skills_dir) = get_wsl_tool_skills_dir(tool_key) {
@@ -1,6 +1,7 @@
 use std::path::Path;
 use std::process::Command;
 use super::types::{FileMapping, SyncResult, WSLDetectResult};
+use crate::coding::remote_tools::{detection_script, parse_detection_output, RemoteToolStatus};
 
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
@@ -710,6 +711,53 @@ pub fn list_wsl_dir(distro: &str, wsl_path: &str) -> Result<Vec<String>, String>
         .collect())
 }
 
+/// Run a bash command in WSL and return its stdout
+pub fn run_wsl_bash(distro: &str, command: &str) -> Result<String, String> {
+    let output = create_wsl_command()
+        .args(["-d", distro, "--exec", "bash", "-c", command])
+        .output()
+        .map_err(|e| format!("Failed to execute WSL command: {}", e))?;
+
+    if output.status.success() {
+        Ok(decode_wsl_output(&output.stdout))
+    } else {
+        let stderr = decode_wsl_output(&output.stderr);
+        Err(format!("WSL command failed: {}", stderr.trim()))
+    }
+}
+
+/// Detect which tools are installed in WSL (detect directories and CLI binaries)
+pub fn detect_wsl_tools(distro: &str) -> Result<Vec<RemoteToolStatus>, String> {
+    let output = run_wsl_bash(distro, &detection_script())?;
+    Ok(parse_detection_output(&output))
+}
+
+/// Copy a WSL directory to Windows (reverse of `sync_directory`, dereferencing symlinks)
+pub fn copy_wsl_dir_to_windows(distro: &str, wsl_path: &str, windows_path: &str) -> Result<(), String> {
+    let wsl_source_path = wsl_path.replace("~", "$HOME");
+    let wsl_target_path = windows_to_wsl_path(windows_path)?;
+
+    let command = format!(
+        "mkdir -p \"$(dirname \"{}\")\" && rm -rf \"{}\" && cp -rL \"{}\" \"{}\" 2>&1",
+        wsl_target_path, wsl_target_path, wsl_source_path, wsl_target_path
+    );
+
+    let output = create_wsl_command()
+        .args(["-d", distro, "--exec", "bash", "-c", &command])
+        .output()
+        .map_err(|e| format!("Failed to execute WSL copy command: {}", e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        let message = decode_wsl_output(&output.stdout).trim().to_string();
+        Err(format!(
+            "Failed to copy '{}' from WSL to '{}': {}",
+            wsl_path, windows_path, message
+        ))
+    }
+}
+
 /// Check if a WSL symlink exists and points to the expected target
 pub fn check_wsl_symlink_exists(distro: &str, link_path: &str, expected_target: &str) -> bool {
     let link_expanded = link_path.replace("~", "$HOME");
@@ -118,4 +118,7 @@ pub struct SyncProgress {
     pub total: u32,
     /// Overall progress message
     pub message: String,
+    /// SSH connection the progress belongs to (SSH sync to multiple hosts)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connection_id: Option<String>,
 }
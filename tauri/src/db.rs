@@ -4,6 +4,10 @@ use tokio::sync::Mutex;
 
 pub struct DbState(pub Arc<Mutex<Surreal<surrealdb::engine::local::Db>>>);
 
+/// Version of the stored data layout, recorded in backup manifests.
+/// Bump when records change in a way older versions cannot read.
+pub const SCHEMA_VERSION: u32 = 1;
+
 /// Run database migrations
 /// 
 /// Note: With the adapter layer pattern, database migrations are no longer needed.
@@ -20,6 +20,7 @@ pub mod auto_launch;
 pub mod coding;
 pub mod db;
 pub mod http_client;
+pub mod secrets;
 pub mod settings;
 pub mod single_instance;
 pub mod tray;
@@ -554,6 +555,11 @@ fn setup_linux_wayland_webview_workaround() -> u8 {
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // 作为 MCP 网关 stdio 入口启动时只转发消息，不启动窗口
+    if let Some(args) = coding::mcp::gateway::shim_args() {
+        std::process::exit(coding::mcp::gateway::run_shim(args));
+    }
+
     // 初始化日志系统
     let log_file = init_logging();
     if let Some(ref path) = log_file {
@@ -702,6 +708,11 @@ pub fn run() {
                 }
             }
 
+            // Open the secrets vault before any record is read
+            if let Err(e) = secrets::init(&app_data_dir) {
+                error!("密钥库初始化失败: {}", e);
+            }
+
             let db_path = app_data_dir.join("database");
             info!("数据库路径: {:?}", db_path);
 
@@ -734,6 +745,11 @@ pub fn run() {
                 }
                 info!("数据库迁移完成");
 
+                // Move plaintext secrets of existing records into the vault
+                if let Err(e) = secrets::seal_stored_secrets(&db).await {
+                    warn!("迁移明文密钥失败: {}", e);
+                }
+
                 // Initialize default provider models in database
                 info!("正在初始化默认提供商模型...");
                 let db_state = DbState(Arc::new(Mutex::new(db.clone())));
@@ -751,11 +767,13 @@ pub fn run() {
                 info!("数据库状态已注册到应用");
 
                 // 注册 SSH 会话状态
-                let ssh_session = coding::ssh::SshSessionState(
-                    std::sync::Arc::new(tokio::sync::Mutex::new(coding::ssh::SshSession::new()))
-                );
+                let ssh_session = coding::ssh::SshSessionState::default();
                 app.manage(ssh_session);
+                app.manage(coding::ssh::SshAutoSyncState::default());
                 info!("SSH 会话状态已注册到应用");
+
+                // 注册 MCP 网关状态
+                app.manage(coding::mcp::gateway::McpGatewayState::default());
             });
 
             // Create system tray
@@ -921,113 +939,47 @@ pub fn run() {
 
             // SSH sync listeners (all platforms)
             {
-                // SSH sync request listeners (module-specific)
-                let app_ssh1 = app_handle.clone();
-                let app_ssh1_clone = app_ssh1.clone();
-                tauri::async_runtime::spawn(async move {
-                    let _ = app_ssh1.listen("ssh-sync-request-opencode", move |_event| {
-                        let app = app_ssh1_clone.clone();
-                        tauri::async_runtime::spawn(async move {
-                            let db_state = app.state::<crate::DbState>();
-                            let session_state = app.state::<coding::ssh::SshSessionState>();
-                            let _ = coding::ssh::ssh_sync(
-                                db_state,
-                                session_state,
-                                app.clone(),
-                                Some("opencode".to_string()),
-                            )
-                            .await;
-                        });
-                    });
-                    std::future::pending::<()>().await;
-                });
-
-                let app_ssh2 = app_handle.clone();
-                let app_ssh2_clone = app_ssh2.clone();
-                tauri::async_runtime::spawn(async move {
-                    let _ = app_ssh2.listen("ssh-sync-request-claude", move |_event| {
-                        let app = app_ssh2_clone.clone();
-                        tauri::async_runtime::spawn(async move {
-                            let db_state = app.state::<crate::DbState>();
-                            let session_state = app.state::<coding::ssh::SshSessionState>();
-                            let _ = coding::ssh::ssh_sync(
-                                db_state,
-                                session_state,
-                                app.clone(),
-                                Some("claude".to_string()),
-                            )
-                            .await;
-                        });
-                    });
-                    std::future::pending::<()>().await;
-                });
-
-                let app_ssh3 = app_handle.clone();
-                let app_ssh3_clone = app_ssh3.clone();
-                tauri::async_runtime::spawn(async move {
-                    let _ = app_ssh3.listen("ssh-sync-request-codex", move |_event| {
-                        let app = app_ssh3_clone.clone();
-                        tauri::async_runtime::spawn(async move {
-                            let db_state = app.state::<crate::DbState>();
-                            let session_state = app.state::<coding::ssh::SshSessionState>();
-                            let _ = coding::ssh::ssh_sync(
-                                db_state,
-                                session_state,
-                                app.clone(),
-                                Some("codex".to_string()),
-                            )
-                            .await;
+                // SSH sync request listeners (module-specific, debounced)
+                for module in ["opencode", "claude", "codex"] {
+                    let app_ssh = app_handle.clone();
+                    let app_ssh_clone = app_ssh.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let _ = app_ssh.listen(format!("ssh-sync-request-{}", module), move |_event| {
+                            coding::ssh::request_auto_sync(
+                                &app_ssh_clone,
+                                coding::ssh::AutoSyncTrigger::Module(module.to_string()),
+                            );
                         });
+                        std::future::pending::<()>().await;
                     });
-                    std::future::pending::<()>().await;
-                });
+                }
 
-                // MCP-changed listener - triggers MCP SSH sync
+                // MCP-changed listener - triggers MCP SSH sync (debounced)
                 let app_ssh_mcp = app_handle.clone();
                 let app_ssh_mcp_clone = app_ssh_mcp.clone();
                 tauri::async_runtime::spawn(async move {
                     let _ = app_ssh_mcp.listen("mcp-changed", move |_event| {
-                        let app = app_ssh_mcp_clone.clone();
-                        tauri::async_runtime::spawn(async move {
-                            let db_state = app.state::<crate::DbState>();
-                            let session_state = app.state::<coding::ssh::SshSessionState>();
-                            let mut session = session_state.0.lock().await;
-                            // SSH 未配置连接时跳过
-                            if session.conn().is_none() {
-                                return;
-                            }
-                            if session.ensure_connected().await.is_err() {
-                                return;
-                            }
-                            let _ = coding::ssh::sync_mcp_to_ssh(&db_state, &session, app.clone()).await;
-                        });
+                        coding::ssh::request_auto_sync(&app_ssh_mcp_clone, coding::ssh::AutoSyncTrigger::Mcp);
                     });
                     std::future::pending::<()>().await;
                 });
 
-                // Skills-changed listener - triggers Skills SSH sync
+                // Skills-changed listener - triggers Skills SSH sync (debounced)
                 let app_ssh_skills = app_handle.clone();
                 let app_ssh_skills_clone = app_ssh_skills.clone();
                 tauri::async_runtime::spawn(async move {
                     let _ = app_ssh_skills.listen("skills-changed", move |_event| {
-                        let app = app_ssh_skills_clone.clone();
-                        tauri::async_runtime::spawn(async move {
-                            let db_state = app.state::<crate::DbState>();
-                            let session_state = app.state::<coding::ssh::SshSessionState>();
-                            let mut session = session_state.0.lock().await;
-                            // SSH 未配置连接时跳过
-                            if session.conn().is_none() {
-                                return;
-                            }
-                            if session.ensure_connected().await.is_err() {
-                                return;
-                            }
-                            let _ = coding::ssh::sync_skills_to_ssh(&db_state, &session, app.clone()).await;
-                        });
+                        coding::ssh::request_auto_sync(&app_ssh_skills_clone, coding::ssh::AutoSyncTrigger::Skills);
                     });
                     std::future::pending::<()>().await;
                 });
 
+                // Interval SSH sync (checks the configured interval every minute)
+                coding::ssh::start_scheduled_sync(app_handle.clone());
+
+                // MCP gateway
+                coding::mcp::gateway::start_gateway_if_enabled(app_handle.clone());
+
                 // SSH sync on app startup (delayed)
                 let app_ssh_startup = app_handle.clone();
                 tauri::async_runtime::spawn(async move {
@@ -1045,38 +997,24 @@ pub fn run() {
                         }
                     };
 
-                    if !config.enabled || config.active_connection_id.is_empty() {
+                    if !config.enabled || config.sync_targets().is_empty() {
                         return;
                     }
 
-                    // 找到活动连接，建立主连接
-                    if let Some(conn) = config
-                        .connections
-                        .iter()
-                        .find(|c| c.id == config.active_connection_id)
-                    {
-                        let mut session = session_state.0.lock().await;
-                        if let Err(e) = session.connect(conn).await {
-                            log::warn!("SSH 启动主连接失败: {}", e);
-                            return;
-                        }
-
-                        // 主连接建立后，执行首次同步
-                        if session.try_acquire_sync_lock() {
-                            let result = coding::ssh::do_full_sync(
-                                &db_state,
-                                &app_ssh_startup,
-                                &session,
-                                &config,
-                                None,
-                            )
-                            .await;
-                            session.release_sync_lock();
-                            let _ =
-                                coding::ssh::update_sync_status(&db_state, &result).await;
-                            let _ = app_ssh_startup.emit("ssh-sync-completed", result);
-                        }
+                    // 为每个同步目标建立连接并执行首次同步
+                    let result = coding::ssh::sync_all_targets(
+                        &db_state,
+                        &session_state,
+                        &app_ssh_startup,
+                        &config,
+                        None,
+                    )
+                    .await;
+                    if !result.success {
+                        log::warn!("SSH 启动同步失败: {:?}", result.errors);
                     }
+                    let _ = coding::ssh::update_sync_status(&db_state, &result).await;
+                    let _ = app_ssh_startup.emit("ssh-sync-completed", result);
                 });
 
                 // SSH: 定时健康检查（每60秒）
@@ -1089,21 +1027,23 @@ pub fn run() {
                         tokio::time::sleep(Duration::from_secs(60)).await;
 
                         let session_state = app_ssh_health.state::<coding::ssh::SshSessionState>();
-                        let mut session = session_state.0.lock().await;
+                        for session in session_state.sessions().await {
+                            let mut session = session.lock().await;
 
-                        // 只在有配置的连接时检查
-                        if session.conn().is_none() {
-                            continue;
-                        }
+                            // 只在有配置的连接时检查
+                            if session.conn().is_none() {
+                                continue;
+                            }
 
-                        if !session.is_alive() {
-                            log::info!("SSH 健康检查：连接已断开，尝试重连...");
-                            if let Err(e) = session.ensure_connected().await {
-                                log::warn!("SSH 重连失败: {}", e);
-                                let _ = app_ssh_health.emit("ssh-connection-status", "disconnected");
-                            } else {
-                                log::info!("SSH 重连成功");
-                                let _ = app_ssh_health.emit("ssh-connection-status", "connected");
+                            if !session.is_alive() {
+                                log::info!("SSH 健康检查：连接已断开，尝试重连...");
+                                if let Err(e) = session.ensure_connected().await {
+                                    log::warn!("SSH 重连失败: {}", e);
+                                    let _ = app_ssh_health.emit("ssh-connection-status", "disconnected");
+                                } else {
+                                    log::info!("SSH 重连成功");
+                                    let _ = app_ssh_health.emit("ssh-connection-status", "connected");
+                                }
                             }
                         }
                     }
@@ -1189,6 +1129,9 @@ pub fn run() {
             // Start auto-backup scheduler
             settings::backup::auto_backup::start_auto_backup_scheduler(app_handle.clone());
 
+            // Start config drift watcher
+            coding::config_drift::start_config_drift_watcher(app_handle.clone());
+
             info!("setup() 完成，应用即将启动");
             Ok(())
         })
@@ -1262,6 +1205,7 @@ pub fn run() {
             // Backup - Local
             settings::backup::backup_database,
             settings::backup::restore_database,
+            settings::backup::get_backup_manifest,
             settings::backup::get_database_path,
             settings::backup::open_app_data_dir,
             // Backup - WebDAV
@@ -1270,6 +1214,11 @@ pub fn run() {
             settings::backup::restore_from_webdav,
             settings::backup::test_webdav_connection,
             settings::backup::delete_webdav_backup,
+            settings::backup::test_s3_connection,
+            settings::backup::backup_to_s3,
+            settings::backup::list_s3_backups,
+            settings::backup::restore_from_s3,
+            settings::backup::delete_s3_backup,
             // Claude Code
             coding::claude_code::list_claude_providers,
             coding::claude_code::create_claude_provider,
@@ -1281,6 +1230,13 @@ pub fn run() {
             coding::claude_code::reveal_claude_config_folder,
             coding::claude_code::read_claude_settings,
             coding::claude_code::apply_claude_config,
+            coding::claude_code::preview_claude_config,
+            coding::claude_code::list_claude_projects,
+            coding::claude_code::create_claude_project,
+            coding::claude_code::update_claude_project,
+            coding::claude_code::delete_claude_project,
+            coding::claude_code::apply_claude_project_provider,
+            coding::claude_code::preview_claude_project_provider,
             coding::claude_code::toggle_claude_code_provider_disabled,
             coding::claude_code::get_claude_common_config,
             coding::claude_code::save_claude_common_config,
@@ -1295,6 +1251,7 @@ pub fn run() {
             coding::open_code::get_opencode_config_path_info,
             coding::open_code::read_opencode_config,
             coding::open_code::save_opencode_config,
+            coding::open_code::preview_opencode_config,
             coding::open_code::get_opencode_common_config,
             coding::open_code::save_opencode_common_config,
             coding::open_code::fetch_provider_models,
@@ -1323,11 +1280,25 @@ pub fn run() {
             coding::codex::reorder_codex_providers,
             coding::codex::select_codex_provider,
             coding::codex::apply_codex_config,
+            coding::codex::preview_codex_config,
             coding::codex::toggle_codex_provider_disabled,
             coding::codex::read_codex_settings,
             coding::codex::get_codex_common_config,
             coding::codex::save_codex_common_config,
             coding::codex::save_codex_local_config,
+            // Config Snapshots
+            coding::config_writer::config_list_snapshots,
+            coding::config_writer::config_restore_snapshot,
+            // Secrets Vault
+            secrets::secrets_get_status,
+            secrets::secrets_unlock,
+            secrets::secrets_lock,
+            secrets::secrets_set_master_password,
+            secrets::secrets_use_system_key,
+            // Config Drift
+            coding::config_drift::config_drift_list,
+            coding::config_drift::config_drift_ignore,
+            coding::config_drift::config_drift_reapply,
             // Tray
             tray::refresh_tray_menu,
             // Oh My OpenCode
@@ -1336,6 +1307,7 @@ pub fn run() {
             coding::oh_my_opencode::update_oh_my_opencode_config,
             coding::oh_my_opencode::delete_oh_my_opencode_config,
             coding::oh_my_opencode::apply_oh_my_opencode_config,
+            coding::oh_my_opencode::preview_oh_my_opencode_config,
             coding::oh_my_opencode::reorder_oh_my_opencode_configs,
             coding::oh_my_opencode::toggle_oh_my_opencode_config_disabled,
             coding::oh_my_opencode::get_oh_my_opencode_config_path_info,
@@ -1349,6 +1321,7 @@ pub fn run() {
             coding::oh_my_opencode_slim::update_oh_my_opencode_slim_config,
             coding::oh_my_opencode_slim::delete_oh_my_opencode_slim_config,
             coding::oh_my_opencode_slim::apply_oh_my_opencode_slim_config,
+            coding::oh_my_opencode_slim::preview_oh_my_opencode_slim_config,
             coding::oh_my_opencode_slim::reorder_oh_my_opencode_slim_configs,
             coding::oh_my_opencode_slim::toggle_oh_my_opencode_slim_config_disabled,
             coding::oh_my_opencode_slim::get_oh_my_opencode_slim_config_path_info,
@@ -1361,6 +1334,8 @@ pub fn run() {
             coding::wsl::wsl_check_distro,
             coding::wsl::wsl_get_distro_state,
             coding::wsl::wsl_get_config,
+            coding::wsl::wsl_pull_preview,
+            coding::wsl::wsl_pull_import,
             coding::wsl::wsl_save_config,
             coding::wsl::wsl_add_file_mapping,
             coding::wsl::wsl_update_file_mapping,
@@ -1374,6 +1349,15 @@ pub fn run() {
             coding::wsl::wsl_open_folder,
             // SSH Sync
             coding::ssh::ssh_test_connection,
+            coding::ssh::ssh_reset_host_key,
+            coding::ssh::ssh_sync_plan,
+            coding::wsl::wsl_sync_plan,
+            coding::ssh::ssh_detect_remote_tools,
+            coding::wsl::wsl_detect_tools,
+            coding::ssh::ssh_list_ssh_config_hosts,
+            coding::ssh::ssh_import_ssh_config,
+            coding::ssh::ssh_pull_preview,
+            coding::ssh::ssh_pull_import,
             coding::ssh::ssh_get_config,
             coding::ssh::ssh_save_config,
             coding::ssh::ssh_list_connections,
@@ -1432,11 +1416,23 @@ pub fn run() {
             coding::mcp::mcp_list_servers,
             coding::mcp::mcp_create_server,
             coding::mcp::mcp_update_server,
+            coding::mcp::mcp_check_server,
+            coding::mcp::mcp_list_env_vars,
+            coding::mcp::mcp_upsert_env_var,
+            coding::mcp::mcp_delete_env_var,
+            coding::mcp::gateway::mcp_gateway_status,
+            coding::mcp::gateway::mcp_set_gateway_enabled,
             coding::mcp::mcp_delete_server,
             coding::mcp::mcp_toggle_tool,
             coding::mcp::mcp_reorder_servers,
             coding::mcp::mcp_sync_to_tool,
             coding::mcp::mcp_sync_all,
+            coding::mcp::mcp_list_project_targets,
+            coding::mcp::mcp_list_projects,
+            coding::mcp::mcp_create_project,
+            coding::mcp::mcp_update_project,
+            coding::mcp::mcp_delete_project,
+            coding::mcp::mcp_sync_project,
             coding::mcp::mcp_import_from_tool,
             coding::mcp::mcp_get_tools,
             coding::mcp::mcp_scan_servers,
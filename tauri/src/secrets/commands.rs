@@ -0,0 +1,50 @@
+use tauri::Emitter;
+
+use super::migrate::seal_stored_secrets;
+use super::types::{VaultKeySource, VaultStatus};
+use super::vault;
+use crate::db::DbState;
+
+/// Get the secrets vault status
+#[tauri::command]
+pub async fn secrets_get_status() -> Result<VaultStatus, String> {
+    Ok(vault::status())
+}
+
+/// Unlock a master-password vault and seal secrets saved while it was locked
+#[tauri::command]
+pub async fn secrets_unlock(
+    state: tauri::State<'_, DbState>,
+    app: tauri::AppHandle,
+    password: String,
+) -> Result<VaultStatus, String> {
+    vault::unlock(&password)?;
+
+    let db = state.0.lock().await;
+    seal_stored_secrets(&db).await?;
+
+    // Provider lists and tray now show resolved values
+    let _ = app.emit("config-changed", "window");
+
+    Ok(vault::status())
+}
+
+/// Lock a master-password vault
+#[tauri::command]
+pub async fn secrets_lock() -> Result<VaultStatus, String> {
+    vault::lock()?;
+    Ok(vault::status())
+}
+
+/// Protect the vault with a master password, or change the current one
+#[tauri::command]
+pub async fn secrets_set_master_password(password: String) -> Result<VaultStatus, String> {
+    vault::set_master_password(&password)?;
+    Ok(vault::status())
+}
+
+/// Remove the master password and keep the vault key in the OS keyring (or key file)
+#[tauri::command]
+pub async fn secrets_use_system_key() -> Result<VaultKeySource, String> {
+    vault::use_system_key()
+}
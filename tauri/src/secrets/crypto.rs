@@ -0,0 +1,97 @@
+//! AES-256-GCM encryption, Argon2id key derivation and reference ids
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::Argon2;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+pub type VaultKey = [u8; 32];
+
+const NONCE_LEN: usize = 12;
+
+/// Generate random bytes from the OS RNG
+pub fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    OsRng.fill_bytes(&mut bytes);
+    bytes
+}
+
+/// Encrypt to nonce || ciphertext, authenticating `aad` along with the data
+pub fn encrypt_bytes(key: &VaultKey, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>, String> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, Payload { msg: plaintext, aad })
+        .map_err(|e| format!("Failed to encrypt data: {}", e))?;
+
+    let mut out = nonce.to_vec();
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+/// Decrypt a value produced by [`encrypt_bytes`] with the same `aad`
+pub fn decrypt_bytes(key: &VaultKey, data: &[u8], aad: &[u8]) -> Result<Vec<u8>, String> {
+    if data.len() <= NONCE_LEN {
+        return Err("Invalid encrypted data: too short".to_string());
+    }
+
+    let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    cipher
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad })
+        .map_err(|_| "Failed to decrypt data: wrong key or corrupted data".to_string())
+}
+
+/// Encrypt and encode as hex(nonce || ciphertext)
+pub fn encrypt(key: &VaultKey, plaintext: &[u8]) -> Result<String, String> {
+    encrypt_bytes(key, plaintext, &[]).map(hex::encode)
+}
+
+/// Decrypt a value produced by [`encrypt`]
+pub fn decrypt(key: &VaultKey, encoded: &str) -> Result<Vec<u8>, String> {
+    let data = hex::decode(encoded).map_err(|e| format!("Invalid encrypted secret: {}", e))?;
+    decrypt_bytes(key, &data, &[])
+        .map_err(|_| "Failed to decrypt secret: wrong key or corrupted data".to_string())
+}
+
+/// Derive the vault key from a master password (Argon2id, default parameters)
+pub fn derive_key(password: &str, salt: &[u8]) -> Result<VaultKey, String> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Failed to derive key from master password: {}", e))?;
+    Ok(key)
+}
+
+/// Stable id of a secret value. Keyed with a per-vault salt so the id in the
+/// database cannot be used to guess the value.
+pub fn reference_id(id_salt: &[u8], plaintext: &str) -> String {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(id_salt).expect("HMAC accepts any key length");
+    mac.update(plaintext.as_bytes());
+    hex::encode(&mac.finalize().into_bytes()[..16])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_roundtrip() {
+        let key: VaultKey = random_bytes();
+        let encoded = encrypt(&key, b"sk-test").unwrap();
+        assert_eq!(decrypt(&key, &encoded).unwrap(), b"sk-test");
+
+        let other: VaultKey = random_bytes();
+        assert!(decrypt(&other, &encoded).is_err());
+    }
+
+    #[test]
+    fn test_reference_id_is_stable_per_salt() {
+        let salt: [u8; 32] = random_bytes();
+        assert_eq!(reference_id(&salt, "a"), reference_id(&salt, "a"));
+        assert_ne!(reference_id(&salt, "a"), reference_id(&salt, "b"));
+        assert_eq!(reference_id(&salt, "a").len(), 32);
+    }
+}
@@ -0,0 +1,75 @@
+//! Sealing and revealing secret fields inside JSON values and JSON strings
+
+use serde_json::Value;
+
+use super::vault::{reveal, seal, REFERENCE_PREFIX};
+
+/// Secret env keys in a Claude Code provider `settings_config`
+pub const CLAUDE_SECRET_POINTERS: &[&str] = &["/env/ANTHROPIC_AUTH_TOKEN", "/env/ANTHROPIC_API_KEY"];
+
+/// Codex provider `settings_config`: everything under `auth` (auth.json content)
+pub const CODEX_SECRET_SUBTREES: &[&str] = &["/auth"];
+
+/// Seal the string values at the given JSON pointers
+pub fn seal_pointers(value: &mut Value, pointers: &[&str]) {
+    for pointer in pointers {
+        if let Some(Value::String(s)) = value.pointer_mut(pointer) {
+            *s = seal(s);
+        }
+    }
+}
+
+/// Seal every string below the given JSON pointers
+pub fn seal_subtrees(value: &mut Value, pointers: &[&str]) {
+    for pointer in pointers {
+        if let Some(subtree) = value.pointer_mut(pointer) {
+            map_strings(subtree, &|s| seal(s));
+        }
+    }
+}
+
+/// Replace every reference in a JSON value with its secret
+pub fn reveal_all(value: &mut Value) {
+    map_strings(value, &|s| reveal(s));
+}
+
+/// Apply `f` to a JSON document stored as a string.
+/// The original string is kept when it cannot be parsed or nothing changed.
+pub fn map_json_str(json: &str, f: impl FnOnce(&mut Value)) -> String {
+    let Ok(original) = serde_json::from_str::<Value>(json) else {
+        return json.to_string();
+    };
+    let mut value = original.clone();
+    f(&mut value);
+    if value == original {
+        return json.to_string();
+    }
+    serde_json::to_string(&value).unwrap_or_else(|_| json.to_string())
+}
+
+/// Reveal all references in a JSON document stored as a string
+pub fn reveal_json_str(json: &str) -> String {
+    if !json.contains(REFERENCE_PREFIX) {
+        return json.to_string();
+    }
+    map_json_str(json, reveal_all)
+}
+
+fn map_strings(value: &mut Value, f: &dyn Fn(&str) -> String) {
+    match value {
+        Value::String(s) => *s = f(s),
+        Value::Array(items) => items.iter_mut().for_each(|item| map_strings(item, f)),
+        Value::Object(map) => map.values_mut().for_each(|item| map_strings(item, f)),
+        _ => {}
+    }
+}
+
+/// Seal the API keys of a Claude Code provider `settings_config`
+pub fn seal_claude_settings(json: &str) -> String {
+    map_json_str(json, |value| seal_pointers(value, CLAUDE_SECRET_POINTERS))
+}
+
+/// Seal the auth.json content of a Codex provider `settings_config`
+pub fn seal_codex_settings(json: &str) -> String {
+    map_json_str(json, |value| seal_subtrees(value, CODEX_SECRET_SUBTREES))
+}
@@ -0,0 +1,104 @@
+//! Storage of the random vault key: OS keyring when available, otherwise a
+//! permission-restricted file in the vault directory.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::crypto::VaultKey;
+use super::types::VaultKeySource;
+use crate::coding::config_writer::writer::atomic_write_private;
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+const KEYRING_SERVICE: &str = "com.ai-toolbox";
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+const KEYRING_USER: &str = "secrets-vault";
+
+const KEY_FILE_NAME: &str = "vault.key";
+
+fn key_file_path(dir: &Path) -> PathBuf {
+    dir.join(KEY_FILE_NAME)
+}
+
+fn decode_key(encoded: &str) -> Result<VaultKey, String> {
+    let bytes = hex::decode(encoded.trim()).map_err(|e| format!("Invalid vault key: {}", e))?;
+    bytes
+        .try_into()
+        .map_err(|_| "Invalid vault key length".to_string())
+}
+
+/// Store a new system key. Tries the OS keyring first and falls back to the key file.
+pub fn store_system_key(dir: &Path, key: &VaultKey) -> Result<VaultKeySource, String> {
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    {
+        match keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)
+            .and_then(|entry| entry.set_password(&hex::encode(key)))
+        {
+            Ok(()) => {
+                let _ = fs::remove_file(key_file_path(dir));
+                return Ok(VaultKeySource::Keyring);
+            }
+            Err(e) => log::warn!("OS keyring unavailable, using key file: {}", e),
+        }
+    }
+
+    // Created 0600, the key is never readable by other users
+    atomic_write_private(&key_file_path(dir), hex::encode(key).as_bytes())?;
+    Ok(VaultKeySource::File)
+}
+
+/// Load the system key for the given source
+pub fn load_system_key(dir: &Path, source: VaultKeySource) -> Result<VaultKey, String> {
+    match source {
+        VaultKeySource::Keyring => {
+            #[cfg(any(target_os = "macos", target_os = "windows"))]
+            {
+                let encoded = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)
+                    .and_then(|entry| entry.get_password())
+                    .map_err(|e| format!("Failed to read vault key from OS keyring: {}", e))?;
+                decode_key(&encoded)
+            }
+            #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+            {
+                Err("OS keyring is not supported on this platform".to_string())
+            }
+        }
+        VaultKeySource::File => {
+            let path = key_file_path(dir);
+            let encoded = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read vault key file {}: {}", path.display(), e))?;
+            decode_key(&encoded)
+        }
+        VaultKeySource::Password => Err("Vault key is derived from the master password".to_string()),
+    }
+}
+
+/// Remove the stored system key (after switching to a master password)
+pub fn delete_system_key(dir: &Path) {
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    {
+        if let Ok(entry) = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER) {
+            let _ = entry.delete_credential();
+        }
+    }
+    let _ = fs::remove_file(key_file_path(dir));
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_key_file_is_private() {
+        let dir = std::env::temp_dir().join(format!("ai-toolbox-keystore-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let key = [7u8; 32];
+
+        assert_eq!(store_system_key(&dir, &key).unwrap(), VaultKeySource::File);
+        let mode = fs::metadata(key_file_path(&dir)).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(load_system_key(&dir, VaultKeySource::File).unwrap(), key);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,66 @@
+//! Move plaintext secrets of existing records into the vault.
+//!
+//! New writes are sealed by the database adapters; this pass covers records saved
+//! before the vault existed or while it was locked. Entries that no record references
+//! any more are kept: backups created before a change may still reference them.
+
+use serde_json::Value;
+use surrealdb::engine::local::Db;
+use surrealdb::Surreal;
+
+use super::fields::{seal_claude_settings, seal_codex_settings};
+use super::vault;
+use crate::coding::db_extract_id;
+
+/// Seal plaintext secrets in all known records. No-op while the vault is locked.
+pub async fn seal_stored_secrets(db: &Surreal<Db>) -> Result<(), String> {
+    if !vault::status().unlocked {
+        return Ok(());
+    }
+
+    seal_string_field(db, "claude_provider", "settings_config", seal_claude_settings).await?;
+    seal_string_field(db, "claude_applied_settings", "settings", seal_claude_settings).await?;
+    seal_string_field(db, "codex_provider", "settings_config", seal_codex_settings).await?;
+    for field in ["password", "passphrase", "private_key_content"] {
+        seal_string_field(db, "ssh_connection", field, vault::seal).await?;
+    }
+    for field in ["webdav.password", "s3.secret_key", "backup_encryption_password"] {
+        seal_string_field(db, "settings", field, vault::seal).await?;
+    }
+
+    Ok(())
+}
+
+/// Seal one string field (dotted path) of every record in a table
+async fn seal_string_field(
+    db: &Surreal<Db>,
+    table: &str,
+    field: &str,
+    seal: fn(&str) -> String,
+) -> Result<(), String> {
+    let records: Vec<Value> = db
+        .query(format!("SELECT type::string(id) as id, {} as value FROM {}", field, table))
+        .await
+        .map_err(|e| format!("Failed to query {}: {}", table, e))?
+        .take(0)
+        .map_err(|e| format!("Failed to parse {}: {}", table, e))?;
+
+    for record in records {
+        let Some(current) = record.get("value").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        let sealed = seal(current);
+        if sealed == current {
+            continue;
+        }
+
+        db.query(format!("UPDATE type::thing($table, $id) SET {} = $value", field))
+            .bind(("table", table.to_string()))
+            .bind(("id", db_extract_id(&record)))
+            .bind(("value", sealed))
+            .await
+            .map_err(|e| format!("Failed to seal secrets in {}: {}", table, e))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,22 @@
+//! Secrets Vault Module
+//!
+//! Keeps API keys, SSH passwords/keys and backup credentials out of the database.
+//! The database adapters seal secret fields into `secret://<id>` references on write
+//! and reveal them on read; the values are encrypted in `<app data>/secrets/vault.json`.
+//!
+//! The vault key is random and stored in the OS keyring, or in a 0600 key file when no
+//! keyring is available (headless Linux). Alternatively it is derived from a master
+//! password, in which case the vault is locked until unlocked from the settings page.
+
+pub mod commands;
+pub mod crypto;
+pub mod fields;
+pub mod keystore;
+pub mod migrate;
+pub mod types;
+pub mod vault;
+
+pub use commands::*;
+pub use migrate::seal_stored_secrets;
+pub use types::*;
+pub use vault::{check_resolved, init, is_reference, resolve, reveal, seal};
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+/// Where the vault encryption key comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum VaultKeySource {
+    /// Random key stored in the OS keyring (macOS Keychain / Windows Credential Manager)
+    Keyring,
+    /// Random key stored in a 0600 file next to the vault (headless Linux fallback)
+    File,
+    /// Key derived from a master password, must be unlocked after each start
+    Password,
+}
+
+/// Vault status for the settings page
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultStatus {
+    pub initialized: bool,
+    pub key_source: Option<VaultKeySource>,
+    pub unlocked: bool,
+    pub secret_count: usize,
+}
@@ -0,0 +1,500 @@
+//! Encrypted secret store
+//!
+//! Secrets live in `<app data>/secrets/vault.json`, encrypted with the vault key.
+//! Database records only hold `secret://<id>` references, so the SurrealDB files contain
+//! no plaintext credentials. Backups carry the vault entries in [`VaultExport`]: in
+//! plaintext only inside archives encrypted with the backup password, otherwise still
+//! encrypted with the vault key.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::crypto::{self, VaultKey};
+use super::keystore;
+use super::types::{VaultKeySource, VaultStatus};
+use crate::coding::config_writer::writer::atomic_write_private;
+
+/// Prefix of secret references stored in the database
+pub const REFERENCE_PREFIX: &str = "secret://";
+
+/// Known plaintext encrypted with the vault key, used to verify a key or password
+const KEY_CHECK: &[u8] = b"ai-toolbox-vault";
+
+static VAULT: OnceLock<Mutex<Vault>> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct VaultFile {
+    version: u32,
+    key_source: VaultKeySource,
+    /// Argon2 salt (hex), only for the master password source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    salt: Option<String>,
+    key_check: String,
+    /// HMAC key (hex) for reference ids
+    id_salt: String,
+    /// Reference id -> encrypted value
+    #[serde(default)]
+    entries: BTreeMap<String, String>,
+}
+
+struct Vault {
+    dir: PathBuf,
+    file: VaultFile,
+    key: Option<VaultKey>,
+}
+
+fn reference_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"secret://([0-9a-f]{32})").expect("valid regex"))
+}
+
+fn vault_file_path(dir: &Path) -> PathBuf {
+    dir.join("vault.json")
+}
+
+impl Vault {
+    /// New empty vault encrypted with `key`, written to `dir`
+    fn create(dir: PathBuf, key: VaultKey, key_source: VaultKeySource) -> Result<Self, String> {
+        let vault = Vault {
+            file: VaultFile {
+                version: 1,
+                key_source,
+                salt: None,
+                key_check: crypto::encrypt(&key, KEY_CHECK)?,
+                id_salt: hex::encode(crypto::random_bytes::<32>()),
+                entries: BTreeMap::new(),
+            },
+            dir,
+            key: Some(key),
+        };
+        vault.persist()?;
+        Ok(vault)
+    }
+
+    fn persist(&self) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(&self.file)
+            .map_err(|e| format!("Failed to serialize vault: {}", e))?;
+        // Key check, id salt and entries: readable by the owner only
+        atomic_write_private(&vault_file_path(&self.dir), json.as_bytes())
+    }
+
+    fn id_salt(&self) -> Vec<u8> {
+        hex::decode(&self.file.id_salt).unwrap_or_default()
+    }
+
+    fn unlocked_key(&self) -> Result<VaultKey, String> {
+        self.key
+            .ok_or_else(|| "Secrets vault is locked, unlock it with the master password".to_string())
+    }
+
+    /// Re-encrypt all entries and the key check with a new key
+    fn rekey(&mut self, new_key: VaultKey) -> Result<(), String> {
+        let old_key = self.unlocked_key()?;
+        let mut entries = BTreeMap::new();
+        for (id, encrypted) in &self.file.entries {
+            let plaintext = crypto::decrypt(&old_key, encrypted)?;
+            entries.insert(id.clone(), crypto::encrypt(&new_key, &plaintext)?);
+        }
+        self.file.entries = entries;
+        self.file.key_check = crypto::encrypt(&new_key, KEY_CHECK)?;
+        self.key = Some(new_key);
+        Ok(())
+    }
+
+    fn seal(&mut self, value: &str) -> Result<String, String> {
+        let key = self.unlocked_key()?;
+        let id = crypto::reference_id(&self.id_salt(), value);
+        if !self.file.entries.contains_key(&id) {
+            let encrypted = crypto::encrypt(&key, value.as_bytes())?;
+            self.file.entries.insert(id.clone(), encrypted);
+            self.persist()?;
+        }
+        Ok(format!("{}{}", REFERENCE_PREFIX, id))
+    }
+
+    fn resolve(&self, reference: &str, id: &str) -> Result<String, String> {
+        let key = self.unlocked_key()?;
+        let encrypted = self
+            .file
+            .entries
+            .get(id)
+            .ok_or_else(|| format!("Secret not found in vault: {}", reference))?;
+        let plaintext = crypto::decrypt(&key, encrypted)?;
+        String::from_utf8(plaintext).map_err(|e| format!("Invalid secret encoding: {}", e))
+    }
+
+    fn export(&self, plaintext: bool) -> Result<VaultExport, String> {
+        match (plaintext, self.key) {
+            (true, Some(key)) => {
+                let mut values = BTreeMap::new();
+                for (id, encrypted) in &self.file.entries {
+                    let plaintext = crypto::decrypt(&key, encrypted)?;
+                    let value = String::from_utf8(plaintext)
+                        .map_err(|e| format!("Invalid secret encoding: {}", e))?;
+                    values.insert(id.clone(), value);
+                }
+                Ok(VaultExport {
+                    values,
+                    ..Default::default()
+                })
+            }
+            _ => Ok(VaultExport {
+                values: BTreeMap::new(),
+                key_check: Some(self.file.key_check.clone()),
+                sealed: self.file.entries.clone(),
+            }),
+        }
+    }
+
+    fn import(&mut self, export: &VaultExport) -> Result<usize, String> {
+        let key = self.unlocked_key()?;
+        if !export.sealed.is_empty() {
+            let same_key = export
+                .key_check
+                .as_deref()
+                .and_then(|check| crypto::decrypt(&key, check).ok())
+                .is_some_and(|check| check == KEY_CHECK);
+            if !same_key {
+                return Err("The credentials in this backup are sealed with another secrets vault key. \
+                    Create the backup with backup encryption enabled to restore it on another installation"
+                    .to_string());
+            }
+        }
+
+        let mut added = 0;
+        for (id, value) in &export.values {
+            if !self.file.entries.contains_key(id) {
+                let encrypted = crypto::encrypt(&key, value.as_bytes())?;
+                self.file.entries.insert(id.clone(), encrypted);
+                added += 1;
+            }
+        }
+        for (id, encrypted) in &export.sealed {
+            if !self.file.entries.contains_key(id) {
+                self.file.entries.insert(id.clone(), encrypted.clone());
+                added += 1;
+            }
+        }
+        if added > 0 {
+            self.persist()?;
+        }
+        Ok(added)
+    }
+}
+
+fn verify_key(file: &VaultFile, key: &VaultKey) -> bool {
+    crypto::decrypt(key, &file.key_check)
+        .map(|check| check == KEY_CHECK)
+        .unwrap_or(false)
+}
+
+fn with_vault<T>(f: impl FnOnce(&mut Vault) -> Result<T, String>) -> Result<T, String> {
+    let vault = VAULT
+        .get()
+        .ok_or_else(|| "Secrets vault is not initialized".to_string())?;
+    let mut guard = vault
+        .lock()
+        .map_err(|_| "Secrets vault lock poisoned".to_string())?;
+    f(&mut guard)
+}
+
+/// Open the vault, creating it with a system key on first start.
+/// With a master password the vault stays locked until [`unlock`] is called.
+pub fn init(app_data_dir: &Path) -> Result<(), String> {
+    let dir = app_data_dir.join("secrets");
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create secrets directory: {}", e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = fs::set_permissions(&dir, fs::Permissions::from_mode(0o700));
+    }
+
+    let path = vault_file_path(&dir);
+    let vault = if path.exists() {
+        let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read vault: {}", e))?;
+        let file: VaultFile =
+            serde_json::from_str(&content).map_err(|e| format!("Failed to parse vault: {}", e))?;
+
+        let key = match file.key_source {
+            VaultKeySource::Password => None,
+            source => match keystore::load_system_key(&dir, source) {
+                Ok(key) if verify_key(&file, &key) => Some(key),
+                Ok(_) => {
+                    log::error!("Vault key does not match the secrets vault");
+                    None
+                }
+                Err(e) => {
+                    log::error!("{}", e);
+                    None
+                }
+            },
+        };
+        Vault { dir, file, key }
+    } else {
+        let key: VaultKey = crypto::random_bytes();
+        let key_source = keystore::store_system_key(&dir, &key)?;
+        Vault::create(dir, key, key_source)?
+    };
+
+    VAULT
+        .set(Mutex::new(vault))
+        .map_err(|_| "Secrets vault already initialized".to_string())
+}
+
+pub fn is_reference(value: &str) -> bool {
+    value.starts_with(REFERENCE_PREFIX)
+}
+
+/// Store a value in the vault and return its reference.
+/// Empty values and references are returned unchanged. When the vault is locked the
+/// plaintext is returned and sealed by the next [`super::seal_stored_secrets`] run.
+pub fn seal(value: &str) -> String {
+    if value.is_empty() || is_reference(value) {
+        return value.to_string();
+    }
+
+    with_vault(|vault| vault.seal(value)).unwrap_or_else(|e| {
+        log::warn!("Secret kept in plaintext: {}", e);
+        value.to_string()
+    })
+}
+
+/// Resolve a reference to its value
+pub fn resolve(value: &str) -> Result<String, String> {
+    let Some(id) = value.strip_prefix(REFERENCE_PREFIX) else {
+        return Ok(value.to_string());
+    };
+
+    with_vault(|vault| vault.resolve(value, id))
+}
+
+/// Resolve a reference, keeping the reference when it cannot be resolved.
+/// Used by the database adapters, which cannot fail.
+pub fn reveal(value: &str) -> String {
+    if !is_reference(value) {
+        return value.to_string();
+    }
+    resolve(value).unwrap_or_else(|e| {
+        log::warn!("{}", e);
+        value.to_string()
+    })
+}
+
+/// Fail when content about to be written or used still holds unresolved references
+pub fn check_resolved(content: &str) -> Result<(), String> {
+    if reference_regex().is_match(content) {
+        return Err("Secrets vault is locked or missing a secret, unlock the vault and retry".to_string());
+    }
+    Ok(())
+}
+
+/// Vault entries carried in a backup archive
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct VaultExport {
+    /// Reference id -> plaintext value, only in archives encrypted with the backup password
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub values: BTreeMap<String, String>,
+    /// Key check of the vault whose key encrypted `sealed`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_check: Option<String>,
+    /// Reference id -> value encrypted with the vault key
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub sealed: BTreeMap<String, String>,
+}
+
+/// Export all entries for a backup. `plaintext` is only set for archives that get
+/// encrypted with the backup password; a locked vault is always exported sealed.
+pub fn export(plaintext: bool) -> Result<VaultExport, String> {
+    with_vault(|vault| vault.export(plaintext))
+}
+
+/// Add the entries of a backup to the vault, keeping existing entries.
+/// Returns the number of entries added. Nothing is added on error.
+pub fn import(export: &VaultExport) -> Result<usize, String> {
+    if export.values.is_empty() && export.sealed.is_empty() {
+        return Ok(0);
+    }
+
+    with_vault(|vault| vault.import(export))
+}
+
+pub fn status() -> VaultStatus {
+    with_vault(|vault| {
+        Ok(VaultStatus {
+            initialized: true,
+            key_source: Some(vault.file.key_source),
+            unlocked: vault.key.is_some(),
+            secret_count: vault.file.entries.len(),
+        })
+    })
+    .unwrap_or(VaultStatus {
+        initialized: false,
+        key_source: None,
+        unlocked: false,
+        secret_count: 0,
+    })
+}
+
+/// Unlock a master-password vault
+pub fn unlock(password: &str) -> Result<(), String> {
+    with_vault(|vault| {
+        let salt = vault
+            .file
+            .salt
+            .as_deref()
+            .ok_or_else(|| "Vault is not protected by a master password".to_string())
+            .and_then(|s| hex::decode(s).map_err(|e| format!("Invalid vault salt: {}", e)))?;
+        let key = crypto::derive_key(password, &salt)?;
+        if !verify_key(&vault.file, &key) {
+            return Err("Wrong master password".to_string());
+        }
+        vault.key = Some(key);
+        Ok(())
+    })
+}
+
+/// Forget the key of a master-password vault until it is unlocked again
+pub fn lock() -> Result<(), String> {
+    with_vault(|vault| {
+        if vault.file.key_source != VaultKeySource::Password {
+            return Err("Only a master-password vault can be locked".to_string());
+        }
+        vault.key = None;
+        Ok(())
+    })
+}
+
+/// Protect the vault with a master password (also used to change it).
+/// The vault must be unlocked.
+pub fn set_master_password(password: &str) -> Result<(), String> {
+    if password.is_empty() {
+        return Err("Master password cannot be empty".to_string());
+    }
+
+    with_vault(|vault| {
+        let salt: [u8; 16] = crypto::random_bytes();
+        let key = crypto::derive_key(password, &salt)?;
+        vault.rekey(key)?;
+        vault.file.key_source = VaultKeySource::Password;
+        vault.file.salt = Some(hex::encode(salt));
+        vault.persist()?;
+        keystore::delete_system_key(&vault.dir);
+        Ok(())
+    })
+}
+
+/// Switch back to a random key kept in the OS keyring (or the key file fallback).
+/// The vault must be unlocked.
+pub fn use_system_key() -> Result<VaultKeySource, String> {
+    with_vault(|vault| {
+        vault.unlocked_key()?;
+        let key: VaultKey = crypto::random_bytes();
+        let source = keystore::store_system_key(&vault.dir, &key)?;
+        vault.rekey(key)?;
+        vault.file.key_source = source;
+        vault.file.salt = None;
+        vault.persist()?;
+        Ok(source)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_vault(key: VaultKey) -> Vault {
+        let dir = std::env::temp_dir().join(format!("ai-toolbox-vault-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        Vault::create(dir, key, VaultKeySource::File).unwrap()
+    }
+
+    fn cleanup(vault: &Vault) {
+        let _ = fs::remove_dir_all(&vault.dir);
+    }
+
+    fn id_of(reference: &str) -> &str {
+        reference.strip_prefix(REFERENCE_PREFIX).unwrap()
+    }
+
+    #[test]
+    fn test_seal_resolve_and_rekey() {
+        let mut vault = temp_vault(crypto::random_bytes());
+        let reference = vault.seal("sk-test").unwrap();
+        assert!(is_reference(&reference));
+        assert_eq!(vault.seal("sk-test").unwrap(), reference);
+        assert_eq!(vault.resolve(&reference, id_of(&reference)).unwrap(), "sk-test");
+
+        // Persisted entries are encrypted
+        let content = fs::read_to_string(vault_file_path(&vault.dir)).unwrap();
+        assert!(!content.contains("sk-test"));
+
+        let old_key = vault.key.unwrap();
+        let new_key: VaultKey = crypto::random_bytes();
+        vault.rekey(new_key).unwrap();
+        assert_eq!(vault.resolve(&reference, id_of(&reference)).unwrap(), "sk-test");
+        assert!(verify_key(&vault.file, &new_key));
+        assert!(!verify_key(&vault.file, &old_key));
+
+        vault.key = None;
+        assert!(vault.resolve(&reference, id_of(&reference)).is_err());
+        cleanup(&vault);
+    }
+
+    #[test]
+    fn test_export_and_import() {
+        let key: VaultKey = crypto::random_bytes();
+        let mut source = temp_vault(key);
+        let reference = source.seal("sk-test").unwrap();
+        let id = id_of(&reference);
+
+        let plaintext = source.export(true).unwrap();
+        assert_eq!(plaintext.values.get(id).map(String::as_str), Some("sk-test"));
+        assert!(plaintext.sealed.is_empty() && plaintext.key_check.is_none());
+
+        let sealed = source.export(false).unwrap();
+        assert!(sealed.values.is_empty());
+        assert!(sealed.sealed.contains_key(id) && sealed.key_check.is_some());
+
+        // A locked vault is exported sealed even when plaintext is requested
+        source.key = None;
+        assert!(source.export(true).unwrap().values.is_empty());
+
+        // Sealed entries are accepted by a vault with the same key only
+        let mut same_key = temp_vault(key);
+        assert_eq!(same_key.import(&sealed).unwrap(), 1);
+        assert_eq!(same_key.import(&sealed).unwrap(), 0);
+        assert_eq!(same_key.resolve(&reference, id).unwrap(), "sk-test");
+
+        let mut foreign = temp_vault(crypto::random_bytes());
+        assert!(foreign.import(&sealed).is_err());
+        assert!(foreign.file.entries.is_empty());
+
+        // Plaintext entries are re-encrypted with the importing vault's key
+        assert_eq!(foreign.import(&plaintext).unwrap(), 1);
+        assert_eq!(foreign.resolve(&reference, id).unwrap(), "sk-test");
+
+        foreign.key = None;
+        assert!(foreign.import(&plaintext).is_err());
+
+        for vault in [&source, &same_key, &foreign] {
+            cleanup(vault);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_vault_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mut vault = temp_vault(crypto::random_bytes());
+        vault.seal("sk-test").unwrap();
+        let mode = fs::metadata(vault_file_path(&vault.dir)).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        cleanup(&vault);
+    }
+}
@@ -7,6 +7,7 @@
 
 use serde_json::{json, Value};
 use super::types::{AppSettings, WebDAVConfig, S3Config};
+use crate::secrets;
 
 /// Convert database JSON Value to AppSettings with fault tolerance
 /// Missing fields will use default values, never panics
@@ -30,6 +31,8 @@ pub fn from_db_value(value: Value) -> AppSettings {
         auto_backup_interval_days: get_u32(&value, "auto_backup_interval_days", 7),
         auto_backup_max_keep: get_u32(&value, "auto_backup_max_keep", 10),
         last_auto_backup_time: get_opt_str(&value, "last_auto_backup_time"),
+        backup_encryption_enabled: get_bool(&value, "backup_encryption_enabled", false),
+        backup_encryption_password: secrets::reveal(&get_str(&value, "backup_encryption_password", "")),
     }
 }
 
@@ -37,10 +40,22 @@ pub fn from_db_value(value: Value) -> AppSettings {
 pub fn to_db_value(settings: &AppSettings) -> Value {
     // Use serde to serialize the entire structure
     // This ensures all types are properly converted
-    serde_json::to_value(settings).unwrap_or_else(|e| {
+    let mut value = serde_json::to_value(settings).unwrap_or_else(|e| {
         eprintln!("Failed to serialize settings: {}", e);
         json!({})
-    })
+    });
+
+    // Store backup credentials as vault references
+    for pointer in [
+        "/webdav/password",
+        "/s3/secret_key",
+        "/backup_encryption_password",
+    ] {
+        if let Some(Value::String(secret)) = value.pointer_mut(pointer) {
+            *secret = secrets::seal(secret);
+        }
+    }
+    value
 }
 
 // Helper functions for safe field extraction
@@ -82,7 +97,7 @@ fn get_webdav(value: &Value) -> WebDAVConfig {
         WebDAVConfig {
             url: get_str(webdav, "url", ""),
             username: get_str(webdav, "username", ""),
-            password: get_str(webdav, "password", ""),
+            password: secrets::reveal(&get_str(webdav, "password", "")),
             remote_path: get_str(webdav, "remote_path", ""),
         }
     } else {
@@ -96,7 +111,7 @@ fn get_s3(value: &Value) -> S3Config {
     if let Some(s3) = s3 {
         S3Config {
             access_key: get_str(s3, "access_key", ""),
-            secret_key: get_str(s3, "secret_key", ""),
+            secret_key: secrets::reveal(&get_str(s3, "secret_key", "")),
             bucket: get_str(s3, "bucket", ""),
             region: get_str(s3, "region", ""),
             prefix: get_str(s3, "prefix", ""),
@@ -3,7 +3,8 @@ use log::{error, info, warn};
 use std::time::Duration;
 use tauri::{Emitter, Manager};
 
-use super::utils::{create_backup_zip, get_db_path};
+use super::s3::{delete_s3_backup_internal, list_s3_backups_internal, upload_s3_backup_internal};
+use super::utils::{create_backup_archive, get_db_path};
 use super::webdav::{delete_webdav_backup_internal, list_webdav_backups_internal};
 use crate::db::DbState;
 use crate::http_client;
@@ -92,7 +93,7 @@ async fn check_and_perform_backup(app_handle: &tauri::AppHandle) -> Result<(), S
 
             info!("Auto-backup is due, performing local backup...");
 
-            match perform_local_backup(app_handle, &settings).await {
+            match perform_local_backup(app_handle, &db_state, &settings).await {
                 Ok(()) => {
                     info!("Auto-backup (local) completed successfully");
 
@@ -121,12 +122,51 @@ async fn check_and_perform_backup(app_handle: &tauri::AppHandle) -> Result<(), S
 
             Ok(())
         }
+        "s3" => {
+            if settings.s3.bucket.is_empty() {
+                return Ok(());
+            }
+
+            info!("Auto-backup is due, performing S3 backup...");
+
+            match upload_s3_backup_internal(app_handle, &db_state, &settings.s3).await {
+                Ok(_) => {
+                    info!("Auto-backup (S3) completed successfully");
+
+                    let now = Utc::now().to_rfc3339();
+                    update_last_auto_backup_time(&db_state, &now).await?;
+                    let _ = app_handle.emit("auto-backup-completed", &now);
+
+                    if settings.auto_backup_max_keep > 0 {
+                        if let Err(e) = cleanup_old_s3_backups(
+                            &db_state,
+                            &settings.s3,
+                            settings.auto_backup_max_keep,
+                        )
+                        .await
+                        {
+                            warn!("Auto-backup S3 cleanup failed: {}", e);
+                        }
+                    }
+                }
+                Err(e) => {
+                    warn!("Auto-backup (S3) failed: {}", e);
+
+                    // Update last_auto_backup_time even on failure to prevent retry every 10 minutes
+                    let now = Utc::now().to_rfc3339();
+                    update_last_auto_backup_time(&db_state, &now).await?;
+                    let _ = app_handle.emit("auto-backup-failed", &e);
+                }
+            }
+
+            Ok(())
+        }
         _ => Ok(()),
     }
 }
 
 /// Read AppSettings from database
-async fn read_settings(
+pub(crate) async fn read_settings(
     db_state: &DbState,
 ) -> Result<crate::settings::types::AppSettings, String> {
     let db = db_state.0.lock().await;
@@ -170,7 +210,7 @@ async fn perform_webdav_backup(
     settings: &crate::settings::types::AppSettings,
 ) -> Result<(), String> {
     let db_path = get_db_path(app_handle)?;
-    let zip_data = create_backup_zip(app_handle, &db_path)?;
+    let zip_data = create_backup_archive(app_handle, db_state, &db_path).await?;
 
     let timestamp = Local::now().format("%Y%m%d-%H%M%S");
     let backup_filename = format!("ai-toolbox-backup-{}.zip", timestamp);
@@ -213,10 +253,11 @@ async fn perform_webdav_backup(
 /// Perform a local backup
 async fn perform_local_backup(
     app_handle: &tauri::AppHandle,
+    db_state: &DbState,
     settings: &crate::settings::types::AppSettings,
 ) -> Result<(), String> {
     let db_path = get_db_path(app_handle)?;
-    let zip_data = create_backup_zip(app_handle, &db_path)?;
+    let zip_data = create_backup_archive(app_handle, db_state, &db_path).await?;
 
     let backup_dir = std::path::Path::new(&settings.local_backup_path);
     if !backup_dir.exists() {
@@ -288,6 +329,33 @@ async fn cleanup_old_webdav_backups(
     Ok(())
 }
 
+/// Cleanup old S3 backups, keeping only the latest `max_keep` files
+async fn cleanup_old_s3_backups(
+    db_state: &DbState,
+    config: &crate::settings::types::S3Config,
+    max_keep: u32,
+) -> Result<(), String> {
+    let backups = list_s3_backups_internal(db_state, config).await?;
+
+    if backups.len() <= max_keep as usize {
+        return Ok(());
+    }
+
+    let to_delete = &backups[max_keep as usize..];
+    info!(
+        "Auto-backup cleanup: deleting {} old S3 backup(s)",
+        to_delete.len()
+    );
+
+    for backup in to_delete {
+        if let Err(e) = delete_s3_backup_internal(db_state, config, &backup.filename).await {
+            warn!("Failed to delete old backup {}: {}", backup.filename, e);
+        }
+    }
+
+    Ok(())
+}
+
 /// Cleanup old local backups, keeping only the latest `max_keep` files
 fn cleanup_old_local_backups(backup_path: &str, max_keep: u32) -> Result<(), String> {
     let backup_dir = std::path::Path::new(backup_path);
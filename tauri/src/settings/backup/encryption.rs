@@ -0,0 +1,122 @@
+//! Passphrase-based encryption of backup archives
+//!
+//! Encrypted backup layout:
+//! `AITBKENC` magic (8) | format version (1) | Argon2id salt (16) | nonce (12) | AES-256-GCM ciphertext
+//!
+//! The header is authenticated as associated data. Files without the magic are plain
+//! ZIP archives from earlier versions and are restored as before.
+
+use super::auto_backup::read_settings;
+use crate::db::DbState;
+use crate::secrets::crypto;
+
+const MAGIC: &[u8; 8] = b"AITBKENC";
+const FORMAT_VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+const HEADER_LEN: usize = MAGIC.len() + 1 + SALT_LEN;
+
+/// Whether the data is an encrypted backup (as opposed to a plain ZIP)
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Encrypt a backup archive with a passphrase
+pub fn encrypt_archive(data: &[u8], password: &str) -> Result<Vec<u8>, String> {
+    if password.is_empty() {
+        return Err("Backup password cannot be empty".to_string());
+    }
+
+    let salt: [u8; SALT_LEN] = crypto::random_bytes();
+    let key = crypto::derive_key(password, &salt)?;
+
+    let mut out = Vec::with_capacity(HEADER_LEN + data.len() + 28);
+    out.extend_from_slice(MAGIC);
+    out.push(FORMAT_VERSION);
+    out.extend_from_slice(&salt);
+    let ciphertext = crypto::encrypt_bytes(&key, data, &out)?;
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+/// Decrypt a backup produced by [`encrypt_archive`]
+pub fn decrypt_archive(data: &[u8], password: &str) -> Result<Vec<u8>, String> {
+    if !is_encrypted(data) || data.len() < HEADER_LEN {
+        return Err("Invalid encrypted backup".to_string());
+    }
+
+    let version = data[MAGIC.len()];
+    if version != FORMAT_VERSION {
+        return Err(format!(
+            "Unsupported encrypted backup version: {}, please update AI Toolbox",
+            version
+        ));
+    }
+
+    let (header, payload) = data.split_at(HEADER_LEN);
+    let key = crypto::derive_key(password, &header[MAGIC.len() + 1..])?;
+    crypto::decrypt_bytes(&key, payload, header)
+        .map_err(|_| "Failed to decrypt backup: wrong password or corrupted file".to_string())
+}
+
+/// Encrypt a new backup archive when backup encryption is enabled in the settings
+pub(crate) async fn encrypt_if_enabled(db_state: &DbState, data: Vec<u8>) -> Result<Vec<u8>, String> {
+    let settings = read_settings(db_state).await?;
+    if !settings.backup_encryption_enabled {
+        return Ok(data);
+    }
+
+    let password = settings.backup_encryption_password;
+    if password.is_empty() {
+        return Err("Backup encryption is enabled but no backup password is set".to_string());
+    }
+    crate::secrets::check_resolved(&password)?;
+
+    encrypt_archive(&data, &password)
+}
+
+/// Decrypt a downloaded or opened backup if it is encrypted.
+/// Uses the given password, falling back to the configured backup password.
+pub(crate) async fn decrypt_if_encrypted(
+    db_state: &DbState,
+    data: Vec<u8>,
+    password: Option<String>,
+) -> Result<Vec<u8>, String> {
+    if !is_encrypted(&data) {
+        return Ok(data);
+    }
+
+    let password = match password.filter(|p| !p.is_empty()) {
+        Some(password) => password,
+        None => {
+            let configured = read_settings(db_state).await?.backup_encryption_password;
+            if configured.is_empty() || crate::secrets::is_reference(&configured) {
+                return Err("Backup is encrypted, enter the backup password to restore it".to_string());
+            }
+            configured
+        }
+    };
+
+    decrypt_archive(&data, &password)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_roundtrip() {
+        let encrypted = encrypt_archive(b"PK\x03\x04zip", "correct horse").unwrap();
+        assert!(is_encrypted(&encrypted));
+        assert_eq!(decrypt_archive(&encrypted, "correct horse").unwrap(), b"PK\x03\x04zip");
+        assert!(decrypt_archive(&encrypted, "wrong").is_err());
+    }
+
+    #[test]
+    fn test_header_is_authenticated() {
+        let mut encrypted = encrypt_archive(b"data", "pw").unwrap();
+        encrypted[MAGIC.len() + 1] ^= 1;
+        assert!(decrypt_archive(&encrypted, "pw").is_err());
+
+        assert!(!is_encrypted(b"PK\x03\x04legacy zip"));
+    }
+}
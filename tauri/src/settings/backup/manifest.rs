@@ -0,0 +1,339 @@
+//! Backup manifest: `manifest.json` at the archive root describing the backup
+//!
+//! Restores validate the manifest (format and schema version, per-entry SHA-256)
+//! before touching existing data. Archives without a manifest predate it and are
+//! only checked for unreadable or corrupted entries.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::io::{Cursor, Read, Seek, Write};
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+use crate::db::SCHEMA_VERSION;
+
+pub const MANIFEST_NAME: &str = "manifest.json";
+/// Vault entries referenced by the database records, see [`crate::secrets::vault::VaultExport`]
+pub const SECRETS_ENTRY: &str = "secrets/vault.json";
+const MANIFEST_FORMAT_VERSION: u32 = 1;
+
+/// Content of `manifest.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format_version: u32,
+    pub app_version: String,
+    /// Database schema version, see [`crate::db::SCHEMA_VERSION`]
+    pub schema_version: u32,
+    pub created_at: String,
+    pub host: String,
+    /// Keys of the modules in [`BACKUP_MODULES`] contained in the backup
+    pub modules: Vec<String>,
+    /// Archive path -> SHA-256 (hex) of the file content
+    pub entries: BTreeMap<String, String>,
+}
+
+/// A part of the backup that can be restored on its own
+pub struct BackupModule {
+    pub key: &'static str,
+    /// Database tables holding the module's records
+    pub tables: &'static [&'static str],
+    /// Archive directory with the module's config files
+    pub archive_dir: Option<&'static str>,
+}
+
+pub const BACKUP_MODULES: &[BackupModule] = &[
+    BackupModule {
+        key: "opencode",
+        tables: &[
+            "opencode_common_config",
+            "opencode_favorite_provider",
+            "opencode_favorite_plugin",
+            "oh_my_opencode_config",
+            "oh_my_opencode_global_config",
+            "oh_my_opencode_slim_config",
+            "oh_my_opencode_slim_global_config",
+        ],
+        archive_dir: Some("external-configs/opencode/"),
+    },
+    BackupModule {
+        key: "claude",
+        tables: &[
+            "claude_provider",
+            "claude_common_config",
+            "claude_applied_settings",
+            "claude_project",
+        ],
+        archive_dir: Some("external-configs/claude/"),
+    },
+    BackupModule {
+        key: "codex",
+        tables: &["codex_provider", "codex_common_config"],
+        archive_dir: Some("external-configs/codex/"),
+    },
+    BackupModule {
+        key: "mcp",
+        tables: &[
+            "mcp_server",
+            "mcp_project",
+            "favorite_mcp",
+            "mcp_preferences",
+            "mcp_env_var",
+        ],
+        archive_dir: None,
+    },
+    BackupModule {
+        key: "skills",
+        tables: &[
+            "skill",
+            "skill_repo",
+            "skill_settings",
+            "skill_preferences",
+            "custom_tool",
+        ],
+        archive_dir: Some("skills/"),
+    },
+    BackupModule {
+        key: "sync",
+        tables: &[
+            "ssh_connection",
+            "ssh_sync_config",
+            "ssh_file_mapping",
+            "ssh_host_sync_status",
+            "wsl_sync_config",
+            "wsl_file_mapping",
+            "sync_pushed_hash",
+        ],
+        archive_dir: None,
+    },
+    BackupModule {
+        key: "settings",
+        tables: &["settings"],
+        archive_dir: None,
+    },
+];
+
+pub fn module_by_key(key: &str) -> Option<&'static BackupModule> {
+    BACKUP_MODULES.iter().find(|m| m.key == key)
+}
+
+/// Module whose config files contain the given archive entry
+pub fn module_of_entry(name: &str) -> Option<&'static BackupModule> {
+    BACKUP_MODULES
+        .iter()
+        .find(|m| m.archive_dir.is_some_and(|dir| name.starts_with(dir)))
+}
+
+/// Host name of this machine, best effort
+fn host_name() -> String {
+    if let Ok(name) = std::env::var("COMPUTERNAME").or_else(|_| std::env::var("HOSTNAME")) {
+        if !name.is_empty() {
+            return name;
+        }
+    }
+    if let Ok(name) = std::fs::read_to_string("/etc/hostname") {
+        if !name.trim().is_empty() {
+            return name.trim().to_string();
+        }
+    }
+    std::process::Command::new("hostname")
+        .output()
+        .ok()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_default()
+}
+
+fn read_entry<R: Read + Seek>(archive: &mut ZipArchive<R>, index: usize) -> Result<(String, Option<Vec<u8>>), String> {
+    let mut file = archive
+        .by_index(index)
+        .map_err(|e| format!("Failed to read zip entry: {}", e))?;
+    let name = file.name().replace('\\', "/");
+    if file.is_dir() {
+        return Ok((name, None));
+    }
+
+    let mut content = Vec::new();
+    file.read_to_end(&mut content)
+        .map_err(|e| format!("Backup entry {} is corrupted: {}", name, e))?;
+    Ok((name, Some(content)))
+}
+
+/// Append `manifest.json` with checksums of all entries to a backup ZIP
+pub fn add_manifest(app_version: &str, zip_data: Vec<u8>) -> Result<Vec<u8>, String> {
+    let mut archive = ZipArchive::new(Cursor::new(&zip_data))
+        .map_err(|e| format!("Failed to read zip archive: {}", e))?;
+
+    let mut entries = BTreeMap::new();
+    for i in 0..archive.len() {
+        if let (name, Some(content)) = read_entry(&mut archive, i)? {
+            entries.insert(name, hex::encode(Sha256::digest(&content)));
+        }
+    }
+
+    // A module is contained when the archive has its database records or its config files
+    let has_db = entries.keys().any(|name| name.starts_with("db/"));
+    let modules = BACKUP_MODULES
+        .iter()
+        .filter(|m| {
+            (has_db && !m.tables.is_empty())
+                || m.archive_dir
+                    .is_some_and(|dir| entries.keys().any(|name| name.starts_with(dir)))
+        })
+        .map(|m| m.key.to_string())
+        .collect();
+
+    let manifest = BackupManifest {
+        format_version: MANIFEST_FORMAT_VERSION,
+        app_version: app_version.to_string(),
+        schema_version: SCHEMA_VERSION,
+        created_at: chrono::Local::now().to_rfc3339(),
+        host: host_name(),
+        modules,
+        entries,
+    };
+    let json = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize backup manifest: {}", e))?;
+
+    let mut zip = ZipWriter::new_append(Cursor::new(zip_data))
+        .map_err(|e| format!("Failed to open zip for writing: {}", e))?;
+    let options =
+        SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    zip.start_file(MANIFEST_NAME, options)
+        .map_err(|e| format!("Failed to start file in zip: {}", e))?;
+    zip.write_all(json.as_bytes())
+        .map_err(|e| format!("Failed to write to zip: {}", e))?;
+    let buffer = zip
+        .finish()
+        .map_err(|e| format!("Failed to finish zip: {}", e))?;
+
+    Ok(buffer.into_inner())
+}
+
+/// Read `manifest.json`, `None` for backups created before manifests existed
+pub fn read_manifest<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<Option<BackupManifest>, String> {
+    let mut file = match archive.by_name(MANIFEST_NAME) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(format!("Failed to read backup manifest: {}", e)),
+    };
+
+    let mut content = String::new();
+    file.read_to_string(&mut content)
+        .map_err(|e| format!("Failed to read backup manifest: {}", e))?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| format!("Invalid backup manifest: {}", e))
+}
+
+/// Check a backup before restoring it: versions, checksums of every entry and the
+/// requested modules. Nothing is written.
+pub fn validate_archive<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    modules: Option<&[String]>,
+) -> Result<Option<BackupManifest>, String> {
+    let manifest = read_manifest(archive)?;
+
+    if let Some(manifest) = &manifest {
+        if manifest.format_version > MANIFEST_FORMAT_VERSION || manifest.schema_version > SCHEMA_VERSION {
+            return Err(format!(
+                "Backup was created by a newer version of AI Toolbox ({}), please update before restoring",
+                manifest.app_version
+            ));
+        }
+    }
+
+    let mut found = 0;
+    for i in 0..archive.len() {
+        let (name, Some(content)) = read_entry(archive, i)? else {
+            continue;
+        };
+        if name == MANIFEST_NAME {
+            continue;
+        }
+        let Some(manifest) = &manifest else {
+            continue;
+        };
+
+        let expected = manifest
+            .entries
+            .get(&name)
+            .ok_or_else(|| format!("Backup entry {} is not listed in the manifest", name))?;
+        if hex::encode(Sha256::digest(&content)) != *expected {
+            return Err(format!("Checksum mismatch for backup entry {}", name));
+        }
+        found += 1;
+    }
+
+    if let Some(manifest) = &manifest {
+        if found != manifest.entries.len() {
+            return Err(format!(
+                "Backup is incomplete: {} of {} entries found",
+                found,
+                manifest.entries.len()
+            ));
+        }
+    }
+
+    for key in modules.unwrap_or_default() {
+        if module_by_key(key).is_none() {
+            return Err(format!("Unknown backup module: {}", key));
+        }
+        if let Some(manifest) = &manifest {
+            if !manifest.modules.iter().any(|m| m == key) {
+                return Err(format!("Backup does not contain module: {}", key));
+            }
+        }
+    }
+
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zip_with(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in files {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_manifest_roundtrip_and_validation() {
+        let data = add_manifest("1.0.0", zip_with(&[("db/000001.log", b"data"), ("skills/a/SKILL.md", b"skill")])).unwrap();
+        let mut archive = ZipArchive::new(Cursor::new(data)).unwrap();
+
+        let manifest = validate_archive(&mut archive, Some(&["mcp".to_string()])).unwrap().unwrap();
+        assert_eq!(manifest.app_version, "1.0.0");
+        assert_eq!(manifest.entries.len(), 2);
+        assert!(validate_archive(&mut archive, Some(&["unknown".to_string()])).is_err());
+
+        // Only modules with content in the archive are listed
+        let data = add_manifest("1.0.0", zip_with(&[("skills/a/SKILL.md", b"skill")])).unwrap();
+        let mut archive = ZipArchive::new(Cursor::new(data)).unwrap();
+        let manifest = read_manifest(&mut archive).unwrap().unwrap();
+        assert_eq!(manifest.modules, vec!["skills"]);
+        assert!(validate_archive(&mut archive, Some(&["mcp".to_string()])).is_err());
+    }
+
+    #[test]
+    fn test_validation_detects_tampered_entry() {
+        let data = add_manifest("1.0.0", zip_with(&[("db/000001.log", b"data")])).unwrap();
+        let mut archive = ZipArchive::new(Cursor::new(data)).unwrap();
+        let manifest = read_manifest(&mut archive).unwrap().unwrap();
+
+        let tampered = zip_with(&[
+            ("db/000001.log", b"other"),
+            (MANIFEST_NAME, serde_json::to_string(&manifest).unwrap().as_bytes()),
+        ]);
+        let mut archive = ZipArchive::new(Cursor::new(tampered)).unwrap();
+        assert!(validate_archive(&mut archive, None).is_err());
+
+        // Legacy backups without manifest are accepted
+        let mut legacy = ZipArchive::new(Cursor::new(zip_with(&[("db/000001.log", b"data")]))).unwrap();
+        assert!(validate_archive(&mut legacy, None).unwrap().is_none());
+    }
+}
@@ -1,7 +1,12 @@
 pub mod auto_backup;
+pub mod encryption;
 pub mod local;
+pub mod manifest;
+pub mod restore;
+pub mod s3;
 pub mod utils;
 pub mod webdav;
 
 pub use local::*;
+pub use s3::*;
 pub use webdav::*;
@@ -0,0 +1,370 @@
+//! Restoring backup archives, completely or module by module
+//!
+//! A full restore replaces the database directory and all config files in the
+//! archive. A module restore copies only the module's tables from the backup
+//! database into the live one and extracts only the module's config files, so
+//! other modules keep their current data.
+
+use std::fs::{self, File};
+use std::io::{Cursor, Read, Seek};
+use std::path::{Path, PathBuf};
+use surrealdb::engine::local::SurrealKv;
+use surrealdb::Surreal;
+use tauri::Manager;
+use zip::ZipArchive;
+
+use super::manifest::{module_by_key, module_of_entry, validate_archive, MANIFEST_NAME, SECRETS_ENTRY};
+use super::utils::{get_db_path, get_opencode_restore_dir, get_skills_dir};
+use crate::db::DbState;
+use crate::secrets::vault::{self, VaultExport};
+
+/// Field holding the record key while records are copied between databases
+const RECORD_KEY_FIELD: &str = "__record_key";
+
+fn get_home_dir() -> Result<PathBuf, String> {
+    std::env::var("USERPROFILE")
+        .or_else(|_| std::env::var("HOME"))
+        .map(PathBuf::from)
+        .map_err(|_| "Failed to get home directory".to_string())
+}
+
+/// Check if this is a new format backup (with db/ prefix) or old format
+/// where the archive root is the database directory
+fn is_new_format<R: Read + Seek>(archive: &mut ZipArchive<R>) -> bool {
+    (0..archive.len()).any(|i| {
+        archive
+            .by_index(i)
+            .map(|f| f.name().starts_with("db/"))
+            .unwrap_or(false)
+    })
+}
+
+fn is_db_entry(name: &str, new_format: bool) -> bool {
+    !new_format || name.starts_with("db/")
+}
+
+fn non_empty(path: &str) -> Option<&str> {
+    if path.is_empty() {
+        None
+    } else {
+        Some(path)
+    }
+}
+
+/// Output path of an archive entry, `None` for entries that are not restored
+fn restore_target(
+    app_handle: &tauri::AppHandle,
+    name: &str,
+    new_format: bool,
+    db_path: &Path,
+) -> Result<Option<PathBuf>, String> {
+    if name == MANIFEST_NAME
+        || name == ".backup_marker"
+        || name == "db/.backup_marker"
+        || name.split('/').any(|part| part == "..")
+    {
+        return Ok(None);
+    }
+
+    // Old format: all files are database files
+    if !new_format {
+        return Ok(Some(db_path.join(name)));
+    }
+
+    let home_dir = get_home_dir()?;
+    let target = if let Some(relative) = name.strip_prefix("db/") {
+        non_empty(relative).map(|r| db_path.join(r))
+    } else if let Some(relative) = name.strip_prefix("external-configs/opencode/") {
+        // auth.json belongs in ~/.local/share/opencode/, config files in the config dir
+        // resolved from env/shell/default. MCP cmd /c normalization is handled by
+        // mcp_sync_all during the startup resync.
+        match non_empty(relative) {
+            Some("auth.json") => Some(
+                home_dir
+                    .join(".local")
+                    .join("share")
+                    .join("opencode")
+                    .join("auth.json"),
+            ),
+            Some(r) => Some(get_opencode_restore_dir()?.join(r)),
+            None => None,
+        }
+    } else if let Some(relative) = name.strip_prefix("external-configs/claude/") {
+        // Claude's MCP config is in ~/.claude.json, settings.json is copied as is
+        non_empty(relative).map(|r| home_dir.join(".claude").join(r))
+    } else if let Some(relative) = name.strip_prefix("external-configs/codex/") {
+        non_empty(relative).map(|r| home_dir.join(".codex").join(r))
+    } else if let Some(relative) = name.strip_prefix("skills/") {
+        match non_empty(relative) {
+            Some(r) => Some(get_skills_dir(app_handle)?.join(r)),
+            None => None,
+        }
+    } else {
+        None
+    };
+    Ok(target)
+}
+
+fn extract_entry<R: Read>(file: &mut R, name: &str, outpath: &Path) -> Result<(), String> {
+    if name.ends_with('/') {
+        return fs::create_dir_all(outpath).map_err(|e| format!("Failed to create directory: {}", e));
+    }
+
+    if let Some(parent) = outpath.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create parent directory: {}", e))?;
+    }
+    let mut outfile = File::create(outpath).map_err(|e| format!("Failed to create file: {}", e))?;
+    std::io::copy(file, &mut outfile).map_err(|e| format!("Failed to extract file: {}", e))?;
+    Ok(())
+}
+
+/// Extract the entries accepted by `filter`, with database files going to `db_path`
+fn extract_entries<R: Read + Seek>(
+    app_handle: &tauri::AppHandle,
+    archive: &mut ZipArchive<R>,
+    db_path: &Path,
+    filter: impl Fn(&str) -> bool,
+) -> Result<(), String> {
+    let new_format = is_new_format(archive);
+    for i in 0..archive.len() {
+        let mut file = archive
+            .by_index(i)
+            .map_err(|e| format!("Failed to read zip entry: {}", e))?;
+
+        // Windows backups may contain backslashes which need to be converted
+        let name = file.name().replace('\\', "/");
+        if !filter(&name) {
+            continue;
+        }
+        if let Some(outpath) = restore_target(app_handle, &name, new_format, db_path)? {
+            extract_entry(&mut file, &name, &outpath)?;
+        }
+    }
+    Ok(())
+}
+
+/// Replace the database directory and all config files with the backup
+fn restore_full<R: Read + Seek>(
+    app_handle: &tauri::AppHandle,
+    archive: &mut ZipArchive<R>,
+) -> Result<(), String> {
+    let db_path = get_db_path(app_handle)?;
+
+    // Remove existing database directory
+    if db_path.exists() {
+        fs::remove_dir_all(&db_path)
+            .map_err(|e| format!("Failed to remove existing database: {}", e))?;
+    }
+    fs::create_dir_all(&db_path)
+        .map_err(|e| format!("Failed to create database directory: {}", e))?;
+
+    extract_entries(app_handle, archive, &db_path, |_| true)
+}
+
+/// Replace the records of `tables` in the live database with those of the database at `source_path`
+async fn copy_tables(source_path: &Path, db_state: &DbState, tables: &[&str]) -> Result<(), String> {
+    let source = Surreal::new::<SurrealKv>(source_path.to_path_buf())
+        .await
+        .map_err(|e| format!("Failed to open backup database: {}", e))?;
+    source
+        .use_ns("ai_toolbox")
+        .use_db("main")
+        .await
+        .map_err(|e| format!("Failed to open backup database: {}", e))?;
+
+    let mut copies = Vec::new();
+    for table in tables {
+        let records: Vec<serde_json::Value> = source
+            .query(format!(
+                "SELECT *, record::id(id) AS {} OMIT id FROM type::table($table)",
+                RECORD_KEY_FIELD
+            ))
+            .bind(("table", table.to_string()))
+            .await
+            .map_err(|e| format!("Failed to read {} from backup: {}", table, e))?
+            .take(0)
+            .map_err(|e| format!("Failed to parse {} from backup: {}", table, e))?;
+        copies.push((*table, records));
+    }
+    drop(source);
+
+    // One transaction for all tables: a failure leaves the module's current records in place
+    let mut sql = String::from("BEGIN TRANSACTION;\n");
+    let mut bindings = Vec::new();
+    for (i, (table, records)) in copies.into_iter().enumerate() {
+        let mut rows = Vec::with_capacity(records.len());
+        for mut record in records {
+            let key = record
+                .as_object_mut()
+                .and_then(|obj| obj.remove(RECORD_KEY_FIELD))
+                .ok_or_else(|| format!("Backup record in {} has no id", table))?;
+            rows.push(serde_json::json!({ "key": key, "data": record }));
+        }
+        sql.push_str(&format!(
+            "DELETE type::table($table{0});\nFOR $row IN $rows{0} {{ UPSERT type::thing($table{0}, $row.key) CONTENT $row.data; }};\n",
+            i
+        ));
+        bindings.push((table, rows));
+    }
+    sql.push_str("COMMIT TRANSACTION;");
+
+    let db = db_state.0.lock().await;
+    let mut query = db.query(sql);
+    for (i, (table, rows)) in bindings.iter().enumerate() {
+        query = query
+            .bind((format!("table{}", i), table.to_string()))
+            .bind((format!("rows{}", i), rows.clone()));
+    }
+    let response = query
+        .await
+        .map_err(|e| format!("Failed to restore tables {}: {}", tables.join(", "), e))?;
+    response
+        .check()
+        .map_err(|e| format!("Failed to restore tables {}: {}", tables.join(", "), e))?;
+
+    for (table, rows) in &bindings {
+        log::info!("Restored table {} from backup ({} records)", table, rows.len());
+    }
+    Ok(())
+}
+
+/// Add the vault entries of the backup to the secrets vault, so that the restored
+/// records can resolve their secret references. Backups without entries predate them.
+fn import_secrets<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<(), String> {
+    let mut file = match archive.by_name(SECRETS_ENTRY) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(()),
+        Err(e) => return Err(format!("Failed to read backup secrets: {}", e)),
+    };
+    let mut content = String::new();
+    file.read_to_string(&mut content)
+        .map_err(|e| format!("Failed to read backup secrets: {}", e))?;
+    let secrets: VaultExport =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid backup secrets: {}", e))?;
+
+    let added = vault::import(&secrets)?;
+    if added > 0 {
+        log::info!("Restored {} secrets from backup", added);
+    }
+    Ok(())
+}
+
+/// Restore only the given modules: their tables and config files
+async fn restore_modules<R: Read + Seek>(
+    app_handle: &tauri::AppHandle,
+    db_state: &DbState,
+    archive: &mut ZipArchive<R>,
+    keys: &[String],
+) -> Result<(), String> {
+    let modules: Vec<_> = keys.iter().filter_map(|key| module_by_key(key)).collect();
+    let tables: Vec<&str> = modules.iter().flat_map(|m| m.tables.iter().copied()).collect();
+    let new_format = is_new_format(archive);
+
+    // Tables first: the backup database is extracted to a temporary directory
+    if !tables.is_empty() {
+        let temp_db_path = std::env::temp_dir().join(format!("ai-toolbox-restore-{}", uuid::Uuid::new_v4()));
+        let result = match extract_entries(app_handle, archive, &temp_db_path, |name| {
+            is_db_entry(name, new_format)
+        }) {
+            Ok(()) => copy_tables(&temp_db_path, db_state, &tables).await,
+            Err(e) => Err(e),
+        };
+        let _ = fs::remove_dir_all(&temp_db_path);
+        result?;
+    }
+
+    let db_path = get_db_path(app_handle)?;
+    extract_entries(app_handle, archive, &db_path, |name| {
+        !is_db_entry(name, new_format)
+            && module_of_entry(name).is_some_and(|m| modules.iter().any(|s| s.key == m.key))
+    })
+}
+
+/// Validate and restore a backup archive. `modules` selects a module restore,
+/// `None` restores everything.
+pub async fn restore_backup(
+    app_handle: &tauri::AppHandle,
+    db_state: &DbState,
+    zip_data: Vec<u8>,
+    modules: Option<Vec<String>>,
+) -> Result<(), String> {
+    let mut archive = ZipArchive::new(Cursor::new(zip_data))
+        .map_err(|e| format!("Failed to read zip archive: {}", e))?;
+
+    // Nothing is removed before the whole archive has been checked
+    validate_archive(&mut archive, modules.as_deref())?;
+    // Secrets first: a locked vault or foreign vault key stops the restore before any data changes
+    import_secrets(&mut archive)?;
+
+    match modules {
+        Some(modules) if modules.is_empty() => return Err("No backup modules selected".to_string()),
+        Some(modules) => restore_modules(app_handle, db_state, &mut archive, &modules).await?,
+        None => restore_full(app_handle, &mut archive)?,
+    }
+
+    // Create resync flag file to trigger skills and MCP resync on next startup
+    let app_data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let resync_flag = app_data_dir.join(".resync_required");
+    let _ = fs::write(&resync_flag, "1");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn open_db(path: &Path) -> Surreal<surrealdb::engine::local::Db> {
+        let db = Surreal::new::<SurrealKv>(path.to_path_buf()).await.unwrap();
+        db.use_ns("ai_toolbox").use_db("main").await.unwrap();
+        db
+    }
+
+    #[tokio::test]
+    async fn test_copy_tables_replaces_records() {
+        let dir = std::env::temp_dir().join(format!("ai-toolbox-restore-test-{}", uuid::Uuid::new_v4()));
+        let source_path = dir.join("source");
+        {
+            let source = open_db(&source_path).await;
+            source
+                .query("CREATE mcp_server:alpha CONTENT { name: 'alpha' }; CREATE favorite_mcp:beta CONTENT { name: 'beta' };")
+                .await
+                .unwrap()
+                .check()
+                .unwrap();
+        }
+
+        let live = open_db(&dir.join("live")).await;
+        live.query("CREATE mcp_server:stale CONTENT { name: 'stale' }; CREATE skill:kept CONTENT { name: 'kept' };")
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        let db_state = DbState(std::sync::Arc::new(tokio::sync::Mutex::new(live)));
+
+        copy_tables(&source_path, &db_state, &["mcp_server", "favorite_mcp"]).await.unwrap();
+
+        let db = db_state.0.lock().await;
+        let names = |table: &'static str| {
+            let db = &db;
+            async move {
+                let names: Vec<String> = db
+                    .query("SELECT VALUE name FROM type::table($table) ORDER BY name")
+                    .bind(("table", table))
+                    .await
+                    .unwrap()
+                    .take(0)
+                    .unwrap();
+                names
+            }
+        };
+        assert_eq!(names("mcp_server").await, vec!["alpha"]);
+        assert_eq!(names("favorite_mcp").await, vec!["beta"]);
+        assert_eq!(names("skill").await, vec!["kept"]);
+        drop(db);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -5,7 +5,12 @@ use walkdir::WalkDir;
 use zip::write::SimpleFileOptions;
 use zip::ZipWriter;
 
+use super::auto_backup::read_settings;
+use super::encryption::encrypt_if_enabled;
+use super::manifest::{add_manifest, SECRETS_ENTRY};
 use crate::coding::open_code::shell_env;
+use crate::db::DbState;
+use crate::secrets::vault;
 
 /// Get database directory path
 pub fn get_db_path(app_handle: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
@@ -176,8 +181,24 @@ fn add_file_to_zip<W: Write + std::io::Seek>(
     Ok(())
 }
 
-/// Create a temporary backup zip file and return its contents as bytes
-pub fn create_backup_zip(app_handle: &tauri::AppHandle, db_path: &Path) -> Result<Vec<u8>, String> {
+/// Create a backup archive, encrypted when backup encryption is enabled in the settings
+pub async fn create_backup_archive(
+    app_handle: &tauri::AppHandle,
+    db_state: &DbState,
+    db_path: &Path,
+) -> Result<Vec<u8>, String> {
+    let encrypted = read_settings(db_state).await?.backup_encryption_enabled;
+    let zip_data = create_backup_zip(app_handle, db_path, encrypted)?;
+    encrypt_if_enabled(db_state, zip_data).await
+}
+
+/// Create a temporary backup zip file (with `manifest.json`) and return its contents as bytes.
+/// `plaintext_secrets` is only set for archives that get encrypted with the backup password.
+pub fn create_backup_zip(
+    app_handle: &tauri::AppHandle,
+    db_path: &Path,
+    plaintext_secrets: bool,
+) -> Result<Vec<u8>, String> {
     use std::io::Cursor;
 
     let mut buffer = Cursor::new(Vec::new());
@@ -328,9 +349,22 @@ pub fn create_backup_zip(app_handle: &tauri::AppHandle, db_path: &Path) -> Resul
             }
         }
 
+        // Vault entries, without them the secret references in the database are dangling
+        match vault::export(plaintext_secrets) {
+            Ok(secrets) => {
+                let json = serde_json::to_string(&secrets)
+                    .map_err(|e| format!("Failed to serialize secrets: {}", e))?;
+                zip.start_file(SECRETS_ENTRY, options)
+                    .map_err(|e| format!("Failed to start file in zip: {}", e))?;
+                zip.write_all(json.as_bytes())
+                    .map_err(|e| format!("Failed to write to zip: {}", e))?;
+            }
+            Err(e) => log::warn!("Backup created without secrets: {}", e),
+        }
+
         zip.finish()
             .map_err(|e| format!("Failed to finish zip: {}", e))?;
     }
 
-    Ok(buffer.into_inner())
+    add_manifest(&app_handle.package_info().version.to_string(), buffer.into_inner())
 }
@@ -52,6 +52,12 @@ pub struct AppSettings {
     pub auto_backup_max_keep: u32,
     /// Last auto backup time in ISO 8601 format
     pub last_auto_backup_time: Option<String>,
+    /// Encrypt backup archives with `backup_encryption_password` (default: false)
+    #[serde(default)]
+    pub backup_encryption_enabled: bool,
+    /// Passphrase for backup encryption, stored in the secrets vault
+    #[serde(default)]
+    pub backup_encryption_password: String,
 }
 
 impl Default for AppSettings {
@@ -73,6 +79,8 @@ impl Default for AppSettings {
             auto_backup_interval_days: 7,
             auto_backup_max_keep: 10,
             last_auto_backup_time: None,
+            backup_encryption_enabled: false,
+            backup_encryption_password: String::new(),
         }
     }
 }
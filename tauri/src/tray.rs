@@ -106,6 +106,20 @@ pub fn create_tray<R: Runtime>(app: &AppHandle<R>) -> Result<(), Box<dyn std::er
                     // Refresh tray menu to update checkmarks
                     let _ = refresh_tray_menus(&app_handle).await;
                 });
+            } else if event_id.starts_with("claude_project_") {
+                // Parse: claude_project_{project_id}\x01{provider_id}
+                let remaining = event_id.strip_prefix("claude_project_").unwrap();
+                if let Some(sep_pos) = remaining.find('\x01') {
+                    let project_id = remaining[..sep_pos].to_string();
+                    let provider_id = remaining[sep_pos + 1..].to_string();
+                    let app_handle = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = claude_tray::apply_claude_project_provider(&app_handle, &project_id, &provider_id).await {
+                            eprintln!("Failed to apply Claude provider to project: {}", e);
+                        }
+                        let _ = refresh_tray_menus(&app_handle).await;
+                    });
+                }
             } else if event_id.starts_with("opencode_model_") {
                 // Parse: opencode_model_main|small_provider/model_id
                 let remaining = event_id.strip_prefix("opencode_model_").unwrap();
@@ -260,6 +274,11 @@ pub async fn refresh_tray_menus<R: Runtime>(app: &AppHandle<R>) -> Result<(), St
     } else {
         claude_tray::TrayProviderData { title: "──── Claude Code ────".to_string(), items: vec![] }
     };
+    let claude_projects = if claude_enabled {
+        claude_tray::get_claude_project_tray_data(app).await.unwrap_or_default()
+    } else {
+        Vec::new()
+    };
     let codex_data = if codex_enabled {
         codex_tray::get_codex_tray_data(app).await?
     } else {
@@ -467,6 +486,12 @@ pub async fn refresh_tray_menus<R: Runtime>(app: &AppHandle<R>) -> Result<(), St
             );
             claude_items.push(menu_item);
         }
+
+        // Registered projects: one submenu each, checked item is the pinned provider
+        for project in &claude_projects {
+            let submenu = build_claude_project_submenu(app, project)?;
+            claude_items.push(Box::new(submenu));
+        }
     }
 
     let codex_header = if codex_has_items {
@@ -604,6 +629,32 @@ async fn build_model_submenu<R: Runtime>(
     Ok(submenu)
 }
 
+/// Build a Claude project submenu with provider checkmarks
+fn build_claude_project_submenu<R: Runtime>(
+    app: &AppHandle<R>,
+    project: &claude_tray::TrayProjectItem,
+) -> Result<Submenu<R>, String> {
+    let submenu_id = format!("claude_project_menu_{}", project.id);
+    let submenu = Submenu::with_id(app, &submenu_id, format!("📁 {}", project.display_name), true)
+        .map_err(|e| e.to_string())?;
+
+    for provider in &project.providers {
+        let item_id = format!("claude_project_{}\x01{}", project.id, provider.id);
+        let menu_item = CheckMenuItem::with_id(
+            app,
+            &item_id,
+            &provider.display_name,
+            !provider.is_disabled,
+            provider.is_selected, // checked if pinned to this project
+            None::<&str>,
+        )
+        .map_err(|e| e.to_string())?;
+        submenu.append(&menu_item).map_err(|e| e.to_string())?;
+    }
+
+    Ok(submenu)
+}
+
 /// Build a skill submenu with tool checkmarks
 fn build_skill_submenu<R: Runtime>(
     app: &AppHandle<R>,